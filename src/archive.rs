@@ -0,0 +1,666 @@
+use bevy::utils::HashMap;
+use flate2::read::{DeflateDecoder, GzDecoder};
+use std::{
+    collections::BTreeMap,
+    io::Read,
+    sync::{Arc, Mutex},
+};
+
+/// The formats of archives whose entries can be loaded individually
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+/// File extensions of archives, and their formats
+const ARCHIVE_EXTENSIONS: &[(&str, Format)] = &[
+    (".zip", Format::Zip),
+    (".tar", Format::Tar),
+    (".tar.gz", Format::TarGz),
+    (".tgz", Format::TarGz),
+];
+
+fn format(url: &str) -> Option<Format> {
+    let url = url.to_ascii_lowercase();
+    ARCHIVE_EXTENSIONS
+        .iter()
+        .find(|(ext, _)| url.ends_with(ext))
+        .map(|(_, format)| *format)
+}
+
+/// Splits `path` into the url of an archive and the path of an entry inside it.
+///
+/// Entries are addressed as `https://cdn/pack.zip/textures/hero.png`, since bevy takes
+/// anything after a `#` for a label. The entry is empty for the archive itself.
+pub(crate) fn split(path: &str) -> Option<(&str, &str)> {
+    // Only look at the path, `https://example.zip/` is a host, not an archive
+    let host = path.find("://").map_or(0, |scheme| scheme + 3);
+    let path_start = path[host..]
+        .find('/')
+        .map_or(path.len(), |slash| host + slash);
+    let is_archive = |url: &str| url.len() > path_start && format(url).is_some();
+    if is_archive(path.trim_end_matches('/')) {
+        return Some((path.trim_end_matches('/'), ""));
+    }
+    path.match_indices('/').find_map(|(i, _)| {
+        let url = &path[..i];
+        is_archive(url).then(|| (url, path[i..].trim_matches('/')))
+    })
+}
+
+/// How an archive entry is stored
+#[derive(Debug, Clone, Copy)]
+enum Compression {
+    Stored,
+    Deflate,
+}
+
+/// Location of a file inside [`Archive::data`]
+#[derive(Debug, Clone)]
+struct Entry {
+    offset: usize,
+    len: usize,
+    size: usize,
+    compression: Compression,
+    crc32: Option<u32>,
+}
+
+/// A downloaded archive and the index of the files in it
+pub(crate) struct Archive {
+    data: Vec<u8>,
+    /// Entries by path, directories end with `/`
+    entries: BTreeMap<String, Option<Entry>>,
+}
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+impl Archive {
+    /// Indexes the archive downloaded from `url`, in the format implied by its
+    /// extension, which may not decompress to more than `limit`
+    pub(crate) fn parse(url: &str, data: Vec<u8>, limit: Option<u64>) -> Result<Self, String> {
+        match format(url) {
+            Some(Format::Zip) => Self::zip(data),
+            Some(Format::Tar) => Self::tar(data),
+            // Servers that send `Content-Encoding: gzip` for these got it decompressed already
+            Some(Format::TarGz) if !data.starts_with(&[0x1f, 0x8b]) => Self::tar(data),
+            Some(Format::TarGz) => {
+                let mut tar = Vec::new();
+                // A byte past the limit is enough to tell it's too large
+                let max_len = limit.map_or(u64::MAX, |limit| limit.saturating_add(1));
+                GzDecoder::new(&data[..])
+                    .take(max_len)
+                    .read_to_end(&mut tar)
+                    .map_err(|err| format!("failed to decompress archive: {err}"))?;
+                if let Some(limit) = limit.filter(|&limit| tar.len() as u64 > limit) {
+                    return Err(format!(
+                        "archive decompresses to more than the limit of {limit} bytes"
+                    ));
+                }
+                Self::tar(tar)
+            }
+            None => Err("unknown archive format".to_string()),
+        }
+    }
+
+    /// Indexes a zip archive
+    fn zip(data: Vec<u8>) -> Result<Self, String> {
+        const END_OF_DIRECTORY: u32 = 0x0605_4b50;
+        const DIRECTORY_ENTRY: u32 = 0x0201_4b50;
+        const LOCAL_HEADER: u32 = 0x0403_4b50;
+        let malformed = || "malformed zip archive".to_string();
+
+        // The end of central directory record is at the end, followed by a comment of up to 64k
+        let search_from = data.len().saturating_sub(22 + u16::MAX as usize);
+        let end = (search_from..data.len().saturating_sub(21))
+            .rev()
+            .find(|&at| u32_at(&data, at) == Some(END_OF_DIRECTORY))
+            .ok_or_else(malformed)?;
+        let count = u16_at(&data, end + 10).ok_or_else(malformed)?;
+        let directory = u32_at(&data, end + 16).ok_or_else(malformed)?;
+        if count == u16::MAX || directory == u32::MAX {
+            return Err("zip64 archives aren't supported".to_string());
+        }
+
+        let mut archive = Self {
+            data: Vec::new(),
+            entries: BTreeMap::new(),
+        };
+        let mut at = directory as usize;
+        for _ in 0..count {
+            if u32_at(&data, at) != Some(DIRECTORY_ENTRY) {
+                return Err(malformed());
+            }
+            let field = |offset| u16_at(&data, at + offset).ok_or_else(malformed);
+            let flags = field(8)?;
+            let method = field(10)?;
+            let name_len = field(28)? as usize;
+            let extra_len = field(30)? as usize;
+            let comment_len = field(32)? as usize;
+            let field = |offset| u32_at(&data, at + offset).ok_or_else(malformed);
+            let crc32 = field(16)?;
+            let len = field(20)? as usize;
+            let size = field(24)? as usize;
+            let header = field(42)? as usize;
+            let name = data
+                .get(at + 46..at + 46 + name_len)
+                .ok_or_else(malformed)?;
+            let name = String::from_utf8_lossy(name).into_owned();
+            at += 46 + name_len + extra_len + comment_len;
+
+            if name.ends_with('/') {
+                archive.insert(&name, None);
+                continue;
+            }
+            if u32_at(&data, header) != Some(LOCAL_HEADER) {
+                return Err(malformed());
+            }
+            let local_name_len = u16_at(&data, header + 26).ok_or_else(malformed)? as usize;
+            let local_extra_len = u16_at(&data, header + 28).ok_or_else(malformed)? as usize;
+            let offset = header + 30 + local_name_len + local_extra_len;
+            // Checked, the sizes come from the archive and usize is 32 bits on wasm
+            if offset.checked_add(len).is_none_or(|end| end > data.len()) {
+                return Err(malformed());
+            }
+            let compression = match (method, flags & 1) {
+                (_, 1) => return Err(format!("{name} is encrypted")),
+                (0, _) => Compression::Stored,
+                (8, _) => Compression::Deflate,
+                (method, _) => {
+                    return Err(format!(
+                        "{name} uses unsupported compression method {method}"
+                    ))
+                }
+            };
+            archive.insert(
+                &name,
+                Some(Entry {
+                    offset,
+                    len,
+                    size,
+                    compression,
+                    crc32: Some(crc32),
+                }),
+            );
+        }
+        archive.data = data;
+        Ok(archive)
+    }
+
+    /// Indexes a tar archive, in ustar, gnu or pax format
+    fn tar(data: Vec<u8>) -> Result<Self, String> {
+        const BLOCK: usize = 512;
+        let malformed = || "malformed tar archive".to_string();
+        let text = |bytes: &[u8]| {
+            let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..end]).into_owned()
+        };
+
+        let mut archive = Self {
+            data: Vec::new(),
+            entries: BTreeMap::new(),
+        };
+        // Set by gnu long name and pax headers, for the entry that follows
+        let mut long_name = None;
+        let mut at = 0;
+        while let Some(header) = data.get(at..at + BLOCK) {
+            if header.iter().all(|&b| b == 0) {
+                break;
+            }
+            let size = text(&header[124..136]);
+            let size = usize::from_str_radix(size.trim(), 8).map_err(|_| malformed())?;
+            let offset = at + BLOCK;
+            let end = offset.checked_add(size).ok_or_else(malformed)?;
+            let contents = data.get(offset..end).ok_or_else(malformed)?;
+            at = offset + size.div_ceil(BLOCK) * BLOCK;
+
+            let name = match long_name.take() {
+                Some(name) => name,
+                None if &header[257..262] == b"ustar" && header[345] != 0 => {
+                    format!("{}/{}", text(&header[345..500]), text(&header[..100]))
+                }
+                None => text(&header[..100]),
+            };
+            match header[156] {
+                b'0' | 0 | b'7' => archive.insert(
+                    &name,
+                    Some(Entry {
+                        offset,
+                        len: size,
+                        size,
+                        compression: Compression::Stored,
+                        crc32: None,
+                    }),
+                ),
+                b'5' => archive.insert(&format!("{}/", name.trim_end_matches('/')), None),
+                b'L' => long_name = Some(text(contents)),
+                b'x' => long_name = pax_path(contents),
+                // Links, devices and global pax headers
+                _ => {}
+            }
+        }
+        archive.data = data;
+        Ok(archive)
+    }
+
+    /// Adds an entry, along with its parent directories
+    fn insert(&mut self, name: &str, entry: Option<Entry>) {
+        let name = name.trim_start_matches("./").trim_start_matches('/');
+        let mut parent = 0;
+        while let Some(slash) = name[parent..].find('/') {
+            parent += slash + 1;
+            self.entries
+                .entry(name[..parent].to_string())
+                .or_insert(None);
+        }
+        if !name.is_empty() && !name.ends_with('/') {
+            self.entries.insert(name.to_string(), entry);
+        }
+    }
+
+    /// Reads the file at `path`, which may not be larger than `limit`, `None` if there
+    /// is no such file
+    pub(crate) fn read(&self, path: &str, limit: Option<u64>) -> Option<Result<Vec<u8>, String>> {
+        let entry = self.entries.get(path)?.as_ref()?;
+        if let Some(limit) = limit.filter(|&limit| entry.size as u64 > limit) {
+            return Some(Err(format!(
+                "{path} is larger than the limit of {limit} bytes"
+            )));
+        }
+        let data = &self.data[entry.offset..entry.offset + entry.len];
+        let bytes = match entry.compression {
+            Compression::Stored => data.to_vec(),
+            Compression::Deflate => {
+                let mut bytes = Vec::with_capacity(entry.size.min(16 * 1024 * 1024));
+                // A byte past the size is enough to tell it's not what the index says
+                let mut decoder = DeflateDecoder::new(data).take(entry.size as u64 + 1);
+                if let Err(err) = decoder.read_to_end(&mut bytes) {
+                    return Some(Err(format!("failed to decompress {path}: {err}")));
+                }
+                bytes
+            }
+        };
+        if bytes.len() != entry.size {
+            return Some(Err(format!("{path} has the wrong size")));
+        }
+        if entry
+            .crc32
+            .is_some_and(|crc32| crc32fast::hash(&bytes) != crc32)
+        {
+            return Some(Err(format!("{path} failed its checksum")));
+        }
+        Some(Ok(bytes))
+    }
+
+    /// Whether `path` is a directory in the archive, the empty path being the root
+    pub(crate) fn is_dir(&self, path: &str) -> bool {
+        path.is_empty() || self.entries.contains_key(&format!("{path}/"))
+    }
+
+    /// The files and directories directly inside the directory at `path`
+    pub(crate) fn list<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a str> {
+        let prefix = match path {
+            "" => String::new(),
+            path => format!("{path}/"),
+        };
+        self.entries
+            .keys()
+            .filter(move |name| {
+                let Some(rest) = name.strip_prefix(&prefix) else {
+                    return false;
+                };
+                let rest = rest.strip_suffix('/').unwrap_or(rest);
+                !rest.is_empty() && !rest.contains('/')
+            })
+            .map(|name| name.trim_end_matches('/'))
+    }
+}
+
+/// The `path` record of a pax extended header
+fn pax_path(header: &[u8]) -> Option<String> {
+    // Records look like `<length> <key>=<value>\n`
+    let header = String::from_utf8_lossy(header);
+    header.lines().find_map(|record| {
+        let (_, record) = record.split_once(' ')?;
+        record.strip_prefix("path=").map(str::to_string)
+    })
+}
+
+/// Archives that have been downloaded and indexed, by url
+#[derive(Default)]
+pub(crate) struct Archives(Mutex<HashMap<String, Arc<Archive>>>);
+
+impl Archives {
+    pub(crate) fn get(&self, url: &str) -> Option<Arc<Archive>> {
+        self.0.lock().unwrap().get(url).cloned()
+    }
+
+    pub(crate) fn insert(&self, url: &str, archive: Archive) -> Arc<Archive> {
+        let archive = Arc::new(archive);
+        self.0
+            .lock()
+            .unwrap()
+            .insert(url.to_string(), archive.clone());
+        archive
+    }
+
+    pub(crate) fn remove(&self, url: &str) {
+        self.0.lock().unwrap().remove(url);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::{
+        write::{DeflateEncoder, GzEncoder},
+        Compression as Level,
+    };
+    use std::io::Write;
+
+    use super::*;
+
+    /// A file in a test archive
+    struct File<'a> {
+        name: &'a str,
+        contents: &'a [u8],
+        deflate: bool,
+        /// The uncompressed size recorded in the zip directory, the real one if `None`
+        size: Option<u32>,
+    }
+
+    fn file<'a>(name: &'a str, contents: &'a [u8]) -> File<'a> {
+        File {
+            name,
+            contents,
+            deflate: false,
+            size: None,
+        }
+    }
+
+    fn zip(files: &[File]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut directory = Vec::new();
+        for file in files {
+            let (method, stored) = match file.deflate {
+                true => {
+                    let mut encoder = DeflateEncoder::new(Vec::new(), Level::default());
+                    encoder.write_all(file.contents).unwrap();
+                    (8u16, encoder.finish().unwrap())
+                }
+                false => (0, file.contents.to_vec()),
+            };
+            let crc32 = crc32fast::hash(file.contents);
+            let size = file.size.unwrap_or(file.contents.len() as u32);
+            let header = data.len() as u32;
+            // The local header, with a 4 byte extra field the directory doesn't have
+            data.extend(0x0403_4b50u32.to_le_bytes());
+            data.extend([20, 0, 0, 0]);
+            data.extend(method.to_le_bytes());
+            data.extend([0; 4]);
+            data.extend(crc32.to_le_bytes());
+            data.extend((stored.len() as u32).to_le_bytes());
+            data.extend(size.to_le_bytes());
+            data.extend((file.name.len() as u16).to_le_bytes());
+            data.extend(4u16.to_le_bytes());
+            data.extend(file.name.as_bytes());
+            data.extend([0xff; 4]);
+            data.extend(&stored);
+
+            directory.extend(0x0201_4b50u32.to_le_bytes());
+            directory.extend([20, 0, 20, 0, 0, 0]);
+            directory.extend(method.to_le_bytes());
+            directory.extend([0; 4]);
+            directory.extend(crc32.to_le_bytes());
+            directory.extend((stored.len() as u32).to_le_bytes());
+            directory.extend(size.to_le_bytes());
+            directory.extend((file.name.len() as u16).to_le_bytes());
+            directory.extend([0; 12]);
+            directory.extend(header.to_le_bytes());
+            directory.extend(file.name.as_bytes());
+        }
+        let offset = data.len() as u32;
+        data.extend(&directory);
+        data.extend(0x0605_4b50u32.to_le_bytes());
+        data.extend([0; 4]);
+        data.extend((files.len() as u16).to_le_bytes());
+        data.extend((files.len() as u16).to_le_bytes());
+        data.extend((directory.len() as u32).to_le_bytes());
+        data.extend(offset.to_le_bytes());
+        let comment = b"made by hand";
+        data.extend((comment.len() as u16).to_le_bytes());
+        data.extend(comment);
+        data
+    }
+
+    /// A tar header block for an entry of `kind`
+    fn tar_header(name: &str, kind: u8, size: usize) -> Vec<u8> {
+        let mut header = vec![0; 512];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[124..135].copy_from_slice(format!("{size:011o}").as_bytes());
+        header[156] = kind;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header
+    }
+
+    /// Appends an entry, padded to whole blocks
+    fn tar_entry(tar: &mut Vec<u8>, header: Vec<u8>, contents: &[u8]) {
+        tar.extend(header);
+        tar.extend(contents);
+        tar.resize(tar.len().div_ceil(512) * 512, 0);
+    }
+
+    fn tar() -> Vec<u8> {
+        let mut tar = Vec::new();
+        tar_entry(&mut tar, tar_header("./textures/", b'5', 0), &[]);
+        tar_entry(&mut tar, tar_header("textures/hero.png", b'0', 3), b"png");
+        let long_name = format!("{}/level.ron\0", "l".repeat(120));
+        let header = tar_header("././@LongLink", b'L', long_name.len());
+        tar_entry(&mut tar, header, long_name.as_bytes());
+        tar_entry(&mut tar, tar_header("ignored", b'0', 5), b"level");
+        let record = "path=sounds/jump.ogg\n";
+        let record = format!("{} {record}", record.len() + 3);
+        tar_entry(
+            &mut tar,
+            tar_header("PaxHeader", b'x', record.len()),
+            record.as_bytes(),
+        );
+        tar_entry(&mut tar, tar_header("ignored", b'0', 3), b"ogg");
+        let mut prefixed = tar_header("icon.png", b'0', 4);
+        prefixed[345..350].copy_from_slice(b"ui/hd");
+        tar_entry(&mut tar, prefixed, b"icon");
+        tar_entry(&mut tar, tar_header("link", b'2', 0), &[]);
+        tar.extend([0; 1024]);
+        tar
+    }
+
+    #[test]
+    fn splits_entry_paths() {
+        assert_eq!(
+            split("https://cdn.example.com/pack.zip/textures/hero.png"),
+            Some(("https://cdn.example.com/pack.zip", "textures/hero.png"))
+        );
+        assert_eq!(
+            split("https://cdn.example.com/pack.tar.gz/"),
+            Some(("https://cdn.example.com/pack.tar.gz", ""))
+        );
+        assert_eq!(split("https://example.zip/hero.png"), None);
+        assert_eq!(split("https://cdn.example.com/pack.zip#hero.png"), None);
+        assert_eq!(split("https://cdn.example.com/hero.png"), None);
+    }
+
+    #[test]
+    fn reads_zip_entries() {
+        let level = b"level level level level level".repeat(10);
+        let data = zip(&[
+            file("textures/", b""),
+            file("textures/hero.png", b"png"),
+            File {
+                deflate: true,
+                ..file("levels/1.ron", &level)
+            },
+        ]);
+        let archive = Archive::parse("https://cdn/pack.ZIP", data, None).unwrap();
+        assert_eq!(
+            archive.read("textures/hero.png", None),
+            Some(Ok(b"png".to_vec()))
+        );
+        assert_eq!(archive.read("levels/1.ron", None), Some(Ok(level)));
+        assert_eq!(archive.read("levels", None), None);
+        assert_eq!(archive.read("missing.png", None), None);
+        assert!(archive.is_dir("") && archive.is_dir("levels") && archive.is_dir("textures"));
+        assert!(!archive.is_dir("textures/hero.png"));
+        assert_eq!(archive.list("").collect::<Vec<_>>(), ["levels", "textures"]);
+        assert_eq!(
+            archive.list("textures").collect::<Vec<_>>(),
+            ["textures/hero.png"]
+        );
+    }
+
+    #[test]
+    fn rejects_corrupt_zip_entries() {
+        let mut data = zip(&[file("hero.png", b"png")]);
+        // Flip a byte of the contents, right after the local header
+        let contents = 30 + "hero.png".len() + 4;
+        data[contents] ^= 1;
+        let archive = Archive::parse("pack.zip", data, None).unwrap();
+        assert_eq!(
+            archive.read("hero.png", None),
+            Some(Err("hero.png failed its checksum".to_string()))
+        );
+        assert!(Archive::parse("pack.zip", b"not a zip".to_vec(), None).is_err());
+    }
+
+    #[test]
+    fn rejects_zip_entries_past_the_end() {
+        let mut data = zip(&[file("hero.png", b"png")]);
+        let directory = data
+            .windows(4)
+            .position(|window| window == 0x0201_4b50u32.to_le_bytes())
+            .unwrap();
+        // A compressed size that overflows when added to the offset on 32 bit targets
+        data[directory + 20..directory + 24].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            Archive::parse("pack.zip", data, None).err(),
+            Some("malformed zip archive".to_string())
+        );
+    }
+
+    #[test]
+    fn limits_zip_entry_size() {
+        let zeros = vec![0; 1024 * 1024];
+        let data = zip(&[
+            File {
+                deflate: true,
+                ..file("big.bin", &zeros)
+            },
+            // A zip bomb, inflating to far more than it claims
+            File {
+                deflate: true,
+                size: Some(16),
+                ..file("bomb.bin", &zeros)
+            },
+        ]);
+        let archive = Archive::parse("pack.zip", data, None).unwrap();
+        assert_eq!(
+            archive.read("big.bin", Some(1000)),
+            Some(Err(
+                "big.bin is larger than the limit of 1000 bytes".to_string()
+            ))
+        );
+        assert!(archive
+            .read("big.bin", Some(zeros.len() as u64))
+            .unwrap()
+            .is_ok());
+        assert_eq!(
+            archive.read("bomb.bin", None),
+            Some(Err("bomb.bin has the wrong size".to_string()))
+        );
+    }
+
+    #[test]
+    fn reads_tar_entries() {
+        let archive = Archive::parse("https://cdn/pack.tar", tar(), None).unwrap();
+        assert_eq!(
+            archive.read("textures/hero.png", None),
+            Some(Ok(b"png".to_vec()))
+        );
+        let long_name = format!("{}/level.ron", "l".repeat(120));
+        assert_eq!(archive.read(&long_name, None), Some(Ok(b"level".to_vec())));
+        assert_eq!(
+            archive.read("sounds/jump.ogg", None),
+            Some(Ok(b"ogg".to_vec()))
+        );
+        assert_eq!(
+            archive.read("ui/hd/icon.png", None),
+            Some(Ok(b"icon".to_vec()))
+        );
+        assert_eq!(archive.read("ignored", None), None);
+        assert_eq!(archive.read("link", None), None);
+        assert_eq!(
+            archive.list("").collect::<Vec<_>>(),
+            ["l".repeat(120).as_str(), "sounds", "textures", "ui"]
+        );
+        assert_eq!(
+            archive.read("textures/hero.png", Some(2)),
+            Some(Err(
+                "textures/hero.png is larger than the limit of 2 bytes".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn reads_tar_gz_entries() {
+        let mut encoder = GzEncoder::new(Vec::new(), Level::default());
+        encoder.write_all(&tar()).unwrap();
+        let data = encoder.finish().unwrap();
+        let archive = Archive::parse("pack.tgz", data.clone(), Some(64 * 1024)).unwrap();
+        assert_eq!(
+            archive.read("textures/hero.png", None),
+            Some(Ok(b"png".to_vec()))
+        );
+        // Already decompressed because of `Content-Encoding: gzip`
+        let archive = Archive::parse("pack.tar.gz", tar(), None).unwrap();
+        assert_eq!(
+            archive.read("sounds/jump.ogg", None),
+            Some(Ok(b"ogg".to_vec()))
+        );
+
+        let limit = tar().len() as u64 - 1;
+        assert_eq!(
+            Archive::parse("pack.tar.gz", data, Some(limit)).err(),
+            Some(format!(
+                "archive decompresses to more than the limit of {limit} bytes"
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_tar() {
+        let mut tar = Vec::new();
+        tar_entry(&mut tar, tar_header("hero.png", b'0', 3), b"png");
+        tar.truncate(514);
+        assert_eq!(
+            Archive::parse("pack.tar", tar, None).err(),
+            Some("malformed tar archive".to_string())
+        );
+
+        // The largest size the field holds
+        let mut tar = Vec::new();
+        tar_entry(
+            &mut tar,
+            tar_header("hero.png", b'0', 0o77777777777),
+            b"png",
+        );
+        assert_eq!(
+            Archive::parse("pack.tar", tar, None).err(),
+            Some("malformed tar archive".to_string())
+        );
+    }
+}
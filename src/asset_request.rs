@@ -0,0 +1,55 @@
+use bevy::prelude::default;
+
+use crate::{HeaderMap, Method};
+
+/// How assets of some urls are requested, for endpoints that don't serve them with a
+/// plain `GET`, set in [`WebAssetPlugin::asset_requests`](crate::WebAssetPlugin::asset_requests)
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{AssetRequest, WebAssetPlugin};
+/// App::new().add_plugin(WebAssetPlugin {
+///     asset_requests: vec![(
+///         "https://api.example.com/assets/*".to_string(),
+///         AssetRequest::json(r#"{ "format": "binary" }"#),
+///     )],
+///     ..default()
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct AssetRequest {
+    /// `GET` by default
+    pub method: Method,
+    /// Sent with every request, e.g. a GraphQL query
+    pub body: Option<Vec<u8>>,
+    /// Sent in addition to the configured headers, e.g. the `Content-Type` of `body`
+    pub headers: HeaderMap,
+}
+
+impl Default for AssetRequest {
+    fn default() -> Self {
+        Self {
+            method: Method::Get,
+            body: None,
+            headers: HeaderMap::new(),
+        }
+    }
+}
+
+impl AssetRequest {
+    /// A `POST` sending `body`
+    pub fn post(body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            method: Method::Post,
+            body: Some(body.into()),
+            ..default()
+        }
+    }
+
+    /// A `POST` sending the JSON `body`, e.g. a GraphQL query
+    pub fn json(body: impl Into<String>) -> Self {
+        let mut request = Self::post(body.into());
+        let _ = request.headers.insert("Content-Type", "application/json");
+        request
+    }
+}
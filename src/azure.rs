@@ -0,0 +1,246 @@
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+use std::{collections::BTreeMap, env, fmt};
+use url::Url;
+
+use crate::{clock::DateTime, s3::encode_key, HeaderMap, Method};
+
+/// The storage service version requests are signed for
+const API_VERSION: &str = "2021-08-06";
+
+/// How requests to Azure Blob Storage are authorized
+#[derive(Clone, PartialEq, Eq)]
+pub enum AzureCredentials {
+    /// A shared access signature, e.g. `sv=2021-08-06&ss=b&srt=o&sp=r&sig=...`,
+    /// appended to every url
+    Sas(String),
+    /// The base64 encoded account key, used to sign every request
+    SharedKey(String),
+}
+
+// Don't print secrets in logs
+impl fmt::Debug for AzureCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AzureCredentials::Sas(_) => f.write_str("AzureCredentials::Sas(..)"),
+            AzureCredentials::SharedKey(_) => f.write_str("AzureCredentials::SharedKey(..)"),
+        }
+    }
+}
+
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+/// How `azure://container/blob` paths are loaded from Azure Blob Storage
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{AzureConfig, AzureCredentials, WebAssetPlugin};
+/// App::new().add_plugin(WebAssetPlugin {
+///     azure: AzureConfig {
+///         account: Some("mygame".to_string()),
+///         credentials: Some(AzureCredentials::Sas("sv=2021-08-06&sp=r&sig=...".to_string())),
+///         ..default()
+///     },
+///     ..default()
+/// });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AzureConfig {
+    /// The storage account name, `AZURE_STORAGE_ACCOUNT` if not set
+    pub account: Option<String>,
+    /// The blob service url, defaults to `https://<account>.blob.core.windows.net`.
+    /// Useful for Azurite, e.g. `http://127.0.0.1:10000/devstoreaccount1`.
+    pub account_url: Option<String>,
+    /// If not set, `AZURE_STORAGE_SAS_TOKEN` or `AZURE_STORAGE_KEY` are used,
+    /// and requests are sent anonymously otherwise.
+    pub credentials: Option<AzureCredentials>,
+}
+
+/// [`AzureConfig`] with the environment applied
+#[derive(Debug, Clone)]
+pub(crate) struct Azure {
+    account: Option<String>,
+    account_url: Option<String>,
+    credentials: Option<AzureCredentials>,
+}
+
+impl AzureConfig {
+    pub(crate) fn resolve(&self) -> Azure {
+        let account = self
+            .account
+            .clone()
+            .or_else(|| var("AZURE_STORAGE_ACCOUNT"));
+        let account_url = self.account_url.clone().or_else(|| {
+            Some(format!(
+                "https://{}.blob.core.windows.net",
+                account.as_ref()?
+            ))
+        });
+        let credentials = self.credentials.clone().or_else(|| {
+            var("AZURE_STORAGE_SAS_TOKEN")
+                .map(AzureCredentials::Sas)
+                .or_else(|| var("AZURE_STORAGE_KEY").map(AzureCredentials::SharedKey))
+        });
+        Azure {
+            account,
+            account_url: account_url.map(|url| url.trim_end_matches('/').to_string()),
+            credentials,
+        }
+    }
+}
+
+impl Azure {
+    /// The https url of an `azure://container/blob` path
+    pub(crate) fn url(&self, path: &str) -> Option<String> {
+        let (container, blob) = path.strip_prefix("azure://")?.split_once('/')?;
+        let (blob, query) = match blob.split_once('?') {
+            Some((blob, query)) => (blob, Some(query.to_string())),
+            None => (blob, None),
+        };
+        let query = match (&self.credentials, query) {
+            (Some(AzureCredentials::Sas(sas)), Some(query)) => {
+                Some(format!("{query}&{}", sas.trim_start_matches('?')))
+            }
+            (Some(AzureCredentials::Sas(sas)), None) => {
+                Some(sas.trim_start_matches('?').to_string())
+            }
+            (_, query) => query,
+        };
+        let base = self.account_url.as_ref()?;
+        let url = format!("{base}/{container}/{}", encode_key(blob));
+        match query {
+            Some(query) => Some(format!("{url}?{query}")),
+            None => Some(url),
+        }
+    }
+
+    /// Adds the shared key `Authorization` for a request to `url`, if configured
+    pub(crate) fn sign(&self, method: &Method, url: &Url, headers: &mut HeaderMap) {
+        self.sign_at(method, url, headers, &DateTime::now());
+    }
+
+    fn sign_at(&self, method: &Method, url: &Url, headers: &mut HeaderMap, now: &DateTime) {
+        let (Some(AzureCredentials::SharedKey(key)), Some(account)) =
+            (&self.credentials, &self.account)
+        else {
+            return;
+        };
+        let key = match base64::decode(key.trim()) {
+            Ok(key) => key,
+            Err(err) => {
+                bevy::log::warn!("Invalid azure storage key: {err}");
+                return;
+            }
+        };
+        let _ = headers.insert("x-ms-date", now.to_http_date());
+        let _ = headers.insert("x-ms-version", API_VERSION);
+
+        let header = |name| headers.get(name).unwrap_or_default();
+        let mut ms_headers: Vec<_> = headers
+            .iter()
+            .filter(|(name, _)| name.starts_with("x-ms-"))
+            .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+            .collect();
+        ms_headers.sort();
+
+        // Emulator urls have the account in the path, and still get it prepended here
+        let mut resource = format!("/{account}{}", url.path());
+        let mut query = BTreeMap::<String, Vec<String>>::new();
+        for (name, value) in url.query_pairs() {
+            query
+                .entry(name.to_lowercase())
+                .or_default()
+                .push(value.into_owned());
+        }
+        for (name, mut values) in query {
+            values.sort();
+            resource += &format!("\n{name}:{}", values.join(","));
+        }
+
+        let string_to_sign = format!(
+            "{method}\n{}\n{}\n\n{}\n{}\n\n{}\n{}\n{}\n{}\n{}\n{}{}",
+            header("content-encoding"),
+            header("content-language"),
+            header("content-md5"),
+            header("content-type"),
+            header("if-modified-since"),
+            header("if-match"),
+            header("if-none-match"),
+            header("if-unmodified-since"),
+            header("range"),
+            ms_headers.concat(),
+            resource,
+        );
+        let mut mac = Hmac::<Sha256>::new_varkey(&key).expect("hmac accepts any key length");
+        mac.update(string_to_sign.as_bytes());
+        let signature = base64::encode(mac.finalize().into_bytes());
+        let _ = headers.insert("Authorization", format!("SharedKey {account}:{signature}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The well-known account of the Azurite emulator
+    fn devstoreaccount1() -> Azure {
+        AzureConfig {
+            account: Some("devstoreaccount1".to_string()),
+            account_url: None,
+            credentials: Some(AzureCredentials::SharedKey(
+                "Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw=="
+                    .to_string(),
+            )),
+        }
+        .resolve()
+    }
+
+    #[test]
+    fn signs_with_shared_key() {
+        let url = Url::parse(
+            "https://devstoreaccount1.blob.core.windows.net/textures/hero.png?timeout=30&comp=metadata",
+        )
+        .unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("Range", "bytes=0-99").unwrap();
+        devstoreaccount1().sign_at(
+            &Method::Get,
+            &url,
+            &mut headers,
+            &DateTime::from_unix(1369353600),
+        );
+        assert_eq!(
+            headers.get("x-ms-date"),
+            Some("Fri, 24 May 2013 00:00:00 GMT")
+        );
+        assert_eq!(headers.get("x-ms-version"), Some(API_VERSION));
+        assert_eq!(
+            headers.get("authorization"),
+            Some("SharedKey devstoreaccount1:+rp72rFBpHddOVPxiQno476K6ljpthEv1mdTcqzfycc=")
+        );
+    }
+
+    #[test]
+    fn appends_sas_to_urls() {
+        let azure = AzureConfig {
+            account: Some("mygame".to_string()),
+            account_url: None,
+            credentials: Some(AzureCredentials::Sas("?sv=2021-08-06&sig=abc".to_string())),
+        }
+        .resolve();
+        assert_eq!(
+            azure.url("azure://textures/hero.png").as_deref(),
+            Some("https://mygame.blob.core.windows.net/textures/hero.png?sv=2021-08-06&sig=abc")
+        );
+        assert_eq!(
+            azure.url("azure://textures/hero.png?snapshot=1").as_deref(),
+            Some("https://mygame.blob.core.windows.net/textures/hero.png?snapshot=1&sv=2021-08-06&sig=abc")
+        );
+        let mut headers = HeaderMap::new();
+        let url = Url::parse("https://mygame.blob.core.windows.net/textures/hero.png").unwrap();
+        azure.sign(&Method::Get, &url, &mut headers);
+        assert!(headers.is_empty());
+    }
+}
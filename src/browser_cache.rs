@@ -0,0 +1,131 @@
+use js_sys::Uint8Array;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{global_scope::GlobalScope, CacheConfig};
+
+/// Made up origin entries are stored under, since Cache Storage only takes http urls
+const ORIGIN: &str = "https://web-asset-cache.invalid";
+
+/// Header holding the metadata of an entry
+const META_HEADER: &str = "x-web-asset-cache";
+
+fn js_error(err: JsValue) -> String {
+    format!("{err:?}")
+}
+
+/// Cache entries kept in the browser's Cache Storage, as responses with their
+/// metadata in a header
+pub(crate) struct Store {
+    name: String,
+}
+
+impl Store {
+    pub(crate) fn new(config: &CacheConfig) -> Self {
+        Self {
+            name: config.name.clone(),
+        }
+    }
+
+    fn url(key: &str) -> String {
+        format!("{ORIGIN}/{key}")
+    }
+
+    async fn open(&self) -> Result<web_sys::Cache, String> {
+        let caches = GlobalScope::get()?.caches()?;
+        JsFuture::from(caches.open(&self.name))
+            .await
+            .map_err(js_error)?
+            .dyn_into()
+            .map_err(js_error)
+    }
+
+    async fn get(&self, url: &str) -> Option<web_sys::Response> {
+        let cache = self.open().await.ok()?;
+        JsFuture::from(cache.match_with_str(url))
+            .await
+            .ok()?
+            .dyn_into()
+            .ok()
+    }
+
+    async fn body(response: &web_sys::Response) -> Option<Vec<u8>> {
+        let data = JsFuture::from(response.array_buffer().ok()?).await.ok()?;
+        Some(Uint8Array::new(&data).to_vec())
+    }
+
+    async fn put(&self, url: &str, meta: Option<&str>, body: &[u8]) -> Result<(), String> {
+        let cache = self.open().await?;
+        let headers = web_sys::Headers::new().map_err(js_error)?;
+        if let Some(meta) = meta {
+            headers.set(META_HEADER, meta).map_err(js_error)?;
+        }
+        let mut init = web_sys::ResponseInit::new();
+        init.headers(&headers);
+        let mut body = body.to_vec();
+        let response = web_sys::Response::new_with_opt_u8_array_and_init(Some(&mut body), &init)
+            .map_err(js_error)?;
+        JsFuture::from(cache.put_with_str(url, &response))
+            .await
+            .map_err(js_error)?;
+        Ok(())
+    }
+
+    /// The metadata and body stored under `key`
+    pub(crate) async fn read(&self, key: &str) -> Option<(String, Vec<u8>)> {
+        let response = self.get(&Self::url(key)).await?;
+        let meta = response.headers().get(META_HEADER).ok()??;
+        Some((meta, Self::body(&response).await?))
+    }
+
+    pub(crate) async fn write(&self, key: &str, meta: &str, body: &[u8]) -> Result<(), String> {
+        self.put(&Self::url(key), Some(meta), body).await
+    }
+
+    pub(crate) async fn remove(&self, key: &str) -> Result<(), String> {
+        let cache = self.open().await?;
+        JsFuture::from(cache.delete_with_str(&Self::url(key)))
+            .await
+            .map_err(js_error)?;
+        Ok(())
+    }
+
+    /// Removes every entry, and the index
+    pub(crate) async fn clear(&self) -> Result<(), String> {
+        let caches = GlobalScope::get()?.caches()?;
+        JsFuture::from(caches.delete(&self.name))
+            .await
+            .map_err(js_error)?;
+        Ok(())
+    }
+
+    pub(crate) async fn read_index(&self) -> Option<String> {
+        let response = self.get(&Self::url("index")).await?;
+        String::from_utf8(Self::body(&response).await?).ok()
+    }
+
+    pub(crate) async fn write_index(&self, index: &str) -> Result<(), String> {
+        self.put(&Self::url("index"), None, index.as_bytes()).await
+    }
+
+    /// The metadata of every entry, for rebuilding a missing index
+    pub(crate) async fn metas(&self) -> Vec<String> {
+        let Ok(cache) = self.open().await else {
+            return Vec::new();
+        };
+        let Ok(requests) = JsFuture::from(cache.keys()).await else {
+            return Vec::new();
+        };
+        let mut metas = Vec::new();
+        for request in js_sys::Array::from(&requests).iter() {
+            let request: web_sys::Request = request.unchecked_into();
+            let Some(response) = self.get(&request.url()).await else {
+                continue;
+            };
+            if let Ok(Some(meta)) = response.headers().get(META_HEADER) {
+                metas.push(meta);
+            }
+        }
+        metas
+    }
+}
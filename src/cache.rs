@@ -0,0 +1,1020 @@
+use async_lock::{Mutex, MutexGuard};
+use bevy::{
+    ecs::system::SystemParam,
+    log::warn,
+    prelude::*,
+    tasks::IoTaskPool,
+    utils::{HashMap, HashSet},
+};
+use crossbeam_channel::Receiver;
+use serde_json::json;
+use std::{
+    fmt,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[cfg(all(target_arch = "wasm32", not(feature = "opfs")))]
+use crate::browser_cache::Store;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::disk_cache::Store;
+#[cfg(all(target_arch = "wasm32", feature = "opfs"))]
+use crate::opfs_cache::Store;
+use crate::{
+    clock::unix_time,
+    watch::{reload, Version},
+    HeaderMap, WebAssetIo,
+};
+
+/// Keeps downloaded assets between runs, see
+/// [`WebAssetPlugin::cache`](crate::WebAssetPlugin::cache).
+///
+/// Cached assets are revalidated with the server before they're used, unless their
+/// `Cache-Control` or `Expires` headers say they're still fresh. Responses sent with
+/// `Cache-Control: no-store` aren't cached.
+///
+/// Assets older than `max_age` are evicted, as are the least recently used ones once
+/// the cache grows past `max_size`. This happens when the cache is first used, and
+/// whenever an asset is added.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{CacheConfig, WebAssetPlugin};
+/// let mut app = App::new();
+/// app.add_plugin(WebAssetPlugin {
+///     cache: Some(CacheConfig::default()),
+///     ..default()
+/// });
+/// app.add_plugins(DefaultPlugins);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// Name of the directory assets are kept in, inside the platform's cache directory,
+    /// e.g. `~/.cache/<executable name>/` on Linux. In browsers, the name of the Cache
+    /// Storage cache, or with the `opfs` feature, of the directory in the Origin
+    /// Private File System.
+    pub name: String,
+    /// The directory assets are kept in, instead of one in the platform's cache
+    /// directory. If it can't be created, e.g. in a read-only install location, a
+    /// temporary directory is used.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub directory: Option<std::path::PathBuf>,
+    /// Total size of the cached assets in bytes, `None` means unlimited. Defaults to
+    /// 512 MiB.
+    pub max_size: Option<u64>,
+    /// How long assets are kept after they were downloaded, `None`, the default,
+    /// means until they're evicted to make room
+    pub max_age: Option<Duration>,
+    /// How stale assets are loaded, unless overridden in `strategies`
+    pub strategy: CacheStrategy,
+    /// Strategies for urls matching patterns, where `*` matches anything, e.g.
+    /// `"https://cdn.example.com/ui/*"`. The first matching pattern applies.
+    pub strategies: Vec<(String, CacheStrategy)>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            name: "web_asset_cache".to_string(),
+            #[cfg(not(target_arch = "wasm32"))]
+            directory: None,
+            max_size: Some(512 * 1024 * 1024),
+            max_age: None,
+            strategy: default(),
+            strategies: default(),
+        }
+    }
+}
+
+/// How a cached asset that's no longer fresh is loaded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheStrategy {
+    /// Asks the server whether it changed first, downloading it again if it did
+    #[default]
+    Revalidate,
+    /// Loads the cached copy right away, and asks the server whether it changed in
+    /// the background, reloading the asset if it did. Good for assets where an
+    /// outdated version is fine for a moment.
+    StaleWhileRevalidate,
+}
+
+/// A downloaded asset, along with what's needed to cache it
+pub(crate) struct Fetched {
+    pub(crate) body: Vec<u8>,
+    /// `ETag` or `Last-Modified` of the response
+    pub(crate) validator: Option<String>,
+    pub(crate) headers: HeaderMap,
+}
+
+/// An asset in the cache
+pub(crate) struct CacheEntry {
+    pub(crate) url: String,
+    /// After response transforms, as it's loaded
+    pub(crate) body: Vec<u8>,
+    /// `ETag` or `Last-Modified`, telling versions apart
+    pub(crate) validator: Option<String>,
+    /// `ETag`, weak ones included, sent as `If-None-Match` to revalidate
+    pub(crate) etag: Option<String>,
+    /// `Last-Modified`, sent as `If-Modified-Since` along with the `ETag`, for
+    /// servers that only compare dates
+    pub(crate) last_modified: Option<String>,
+    /// Seconds since the unix epoch until which the entry is used without revalidating
+    pub(crate) fresh_until: Option<u64>,
+    /// Seconds since the unix epoch when the entry was downloaded
+    pub(crate) stored_at: u64,
+}
+
+/// The `Cache-Control` directives of a response, lowercase
+fn cache_control(headers: &HeaderMap) -> impl Iterator<Item = String> + '_ {
+    headers
+        .get_all("cache-control")
+        .flat_map(|value| value.split(','))
+        .map(|directive| directive.trim().to_ascii_lowercase())
+}
+
+impl CacheEntry {
+    /// The entry for a response from `url`, `None` if it may not be stored
+    pub(crate) fn new(url: &str, fetched: &Fetched) -> Option<Self> {
+        if cache_control(&fetched.headers).any(|directive| directive == "no-store") {
+            return None;
+        }
+        let now = unix_time();
+        let mut max_age = None;
+        for directive in cache_control(&fetched.headers) {
+            if directive == "no-cache" {
+                max_age = Some(0);
+                break;
+            }
+            if let Some(age) = directive.strip_prefix("max-age=") {
+                max_age = age.trim_matches('"').parse::<u64>().ok();
+            }
+        }
+        let fresh_until = match max_age {
+            Some(age) => Some(now + age),
+            None => fetched
+                .headers
+                .get("expires")
+                .and_then(crate::clock::DateTime::parse_http_date),
+        };
+        Some(Self {
+            url: url.to_string(),
+            body: fetched.body.clone(),
+            validator: fetched.validator.clone(),
+            etag: fetched.headers.get("etag").map(str::to_string),
+            last_modified: fetched.headers.get("last-modified").map(str::to_string),
+            fresh_until,
+            stored_at: now,
+        })
+    }
+
+    /// Adds the headers that make the server answer `304 Not Modified` if the entry
+    /// is still current
+    pub(crate) fn add_conditional_headers(&self, headers: &mut HeaderMap) {
+        if self.etag.is_none() && self.last_modified.is_none() {
+            // Stored before both were kept
+            if let Some(validator) = &self.validator {
+                Version::Validator(validator.clone()).add_conditional_headers(headers);
+            }
+            return;
+        }
+        if let Some(etag) = &self.etag {
+            let _ = headers.insert("If-None-Match", etag.clone());
+        }
+        if let Some(last_modified) = &self.last_modified {
+            let _ = headers.insert("If-Modified-Since", last_modified.clone());
+        }
+    }
+
+    /// Whether the entry can be used without asking the server
+    pub(crate) fn is_fresh(&self) -> bool {
+        self.fresh_until.is_some_and(|until| unix_time() < until)
+    }
+
+    /// Everything but the body, as stored next to it
+    fn meta(&self) -> String {
+        json!({
+            "url": self.url,
+            "validator": self.validator,
+            "etag": self.etag,
+            "last_modified": self.last_modified,
+            "fresh_until": self.fresh_until,
+            "stored_at": self.stored_at,
+            "size": self.body.len(),
+        })
+        .to_string()
+    }
+
+    /// The entry for `url` described by `meta`, if it's not another url with the same key
+    fn from_meta(url: &str, meta: &str, body: Vec<u8>) -> Option<Self> {
+        let meta: serde_json::Value = serde_json::from_str(meta).ok()?;
+        if meta["url"].as_str()? != url || meta["size"].as_u64()? != body.len() as u64 {
+            return None;
+        }
+        Some(Self {
+            url: url.to_string(),
+            body,
+            validator: meta["validator"].as_str().map(str::to_string),
+            etag: meta["etag"].as_str().map(str::to_string),
+            last_modified: meta["last_modified"].as_str().map(str::to_string),
+            fresh_until: meta["fresh_until"].as_u64(),
+            stored_at: meta["stored_at"].as_u64().unwrap_or_default(),
+        })
+    }
+}
+
+/// The name an entry is stored under, since urls aren't valid file names
+fn key(url: &str) -> String {
+    crate::integrity::sha256(url.as_bytes())
+}
+
+/// What eviction needs to know about an entry
+struct IndexEntry {
+    size: u64,
+    stored_at: u64,
+    /// Seconds since the unix epoch when the entry was last loaded
+    accessed: u64,
+}
+
+/// Every entry in the cache by url, loaded on first use
+#[derive(Default)]
+struct Index {
+    entries: HashMap<String, IndexEntry>,
+    loaded: bool,
+}
+
+impl Index {
+    fn parse(json: &str) -> Option<HashMap<String, IndexEntry>> {
+        let json: serde_json::Value = serde_json::from_str(json).ok()?;
+        let entries = json
+            .as_object()?
+            .iter()
+            .filter_map(|(url, entry)| {
+                let entry = IndexEntry {
+                    size: entry["size"].as_u64()?,
+                    stored_at: entry["stored_at"].as_u64()?,
+                    accessed: entry["accessed"].as_u64()?,
+                };
+                Some((url.clone(), entry))
+            })
+            .collect();
+        Some(entries)
+    }
+
+    /// The index of entries described by `metas`, for caches without an index
+    fn rebuild(metas: &[String]) -> HashMap<String, IndexEntry> {
+        metas
+            .iter()
+            .filter_map(|meta| {
+                let meta: serde_json::Value = serde_json::from_str(meta).ok()?;
+                let stored_at = meta["stored_at"].as_u64()?;
+                let entry = IndexEntry {
+                    size: meta["size"].as_u64()?,
+                    stored_at,
+                    accessed: stored_at,
+                };
+                Some((meta["url"].as_str()?.to_string(), entry))
+            })
+            .collect()
+    }
+
+    fn to_json(&self) -> String {
+        let entries: serde_json::Map<_, _> = self
+            .entries
+            .iter()
+            .map(|(url, entry)| {
+                let entry = json!({
+                    "size": entry.size,
+                    "stored_at": entry.stored_at,
+                    "accessed": entry.accessed,
+                });
+                (url.clone(), entry)
+            })
+            .collect();
+        serde_json::Value::Object(entries).to_string()
+    }
+}
+
+/// Downloaded assets kept between runs, in a directory on native and in Cache Storage
+/// in browsers
+pub(crate) struct Cache {
+    store: Store,
+    max_size: Option<u64>,
+    max_age: Option<Duration>,
+    strategy: CacheStrategy,
+    strategies: Vec<(String, CacheStrategy)>,
+    index: Mutex<Index>,
+    stats: WebAssetCacheStats,
+}
+
+impl Cache {
+    pub(crate) fn new(config: &CacheConfig, stats: WebAssetCacheStats) -> Self {
+        Self {
+            stats,
+            store: Store::new(config),
+            max_size: config.max_size,
+            max_age: config.max_age,
+            strategy: config.strategy,
+            strategies: config.strategies.clone(),
+            index: default(),
+        }
+    }
+
+    /// How stale copies of `url` are loaded
+    pub(crate) fn strategy(&self, url: &str) -> CacheStrategy {
+        self.strategies
+            .iter()
+            .find(|(pattern, _)| crate::fallback::matches(pattern, url))
+            .map_or(self.strategy, |&(_, strategy)| strategy)
+    }
+
+    /// The index, read from the store the first time, which also evicts whatever
+    /// expired since the last run
+    async fn index(&self) -> MutexGuard<'_, Index> {
+        let mut index = self.index.lock().await;
+        if !index.loaded {
+            index.entries = match self.store.read_index().await {
+                Some(json) => Index::parse(&json).unwrap_or_default(),
+                None => Index::rebuild(&self.store.metas().await),
+            };
+            index.loaded = true;
+            self.evict(&mut index).await;
+        }
+        index
+    }
+
+    /// The cached copy of `url`, if any
+    pub(crate) async fn get(&self, url: &str) -> Option<CacheEntry> {
+        // Evicts expired entries on first use, but isn't held while reading the body
+        drop(self.index().await);
+        let (meta, body) = self.store.read(&key(url)).await?;
+        let entry = CacheEntry::from_meta(url, &meta, body)?;
+        let now = unix_time();
+        let mut index = self.index().await;
+        // Entries the index lost track of are adopted
+        let indexed = index.entries.entry(url.to_string()).or_insert(IndexEntry {
+            size: entry.body.len() as u64,
+            stored_at: entry.stored_at,
+            accessed: now,
+        });
+        // Saved with the next change, a few lost accesses don't matter
+        indexed.accessed = now;
+        Some(entry)
+    }
+
+    /// Removes the cached copy of `url`
+    pub(crate) async fn remove(&self, url: &str) {
+        let mut index = self.index().await;
+        index.entries.remove(url);
+        if let Err(err) = self.store.remove(&key(url)).await {
+            warn!("Failed to remove {url} from the cache: {err}");
+        }
+        self.save_index(&index).await;
+    }
+
+    /// Removes everything
+    pub(crate) async fn clear(&self) {
+        let mut index = self.index().await;
+        index.entries.clear();
+        if let Err(err) = self.store.clear().await {
+            warn!("Failed to clear the cache: {err}");
+        }
+        self.stats.set_contents(&index);
+    }
+
+    /// Stores `entry`, replacing any older version, and evicts entries if the cache
+    /// grew too large. Failing to cache isn't fatal, so errors are only logged.
+    pub(crate) async fn put(&self, entry: &CacheEntry) {
+        let size = entry.body.len() as u64;
+        if self.max_size.is_some_and(|max_size| size > max_size) {
+            return;
+        }
+        let mut index = self.index().await;
+        let key = key(&entry.url);
+        if let Err(err) = self.store.write(&key, &entry.meta(), &entry.body).await {
+            warn!("Failed to cache {}: {err}", entry.url);
+            return;
+        }
+        let indexed = IndexEntry {
+            size,
+            stored_at: entry.stored_at,
+            accessed: entry.stored_at,
+        };
+        index.entries.insert(entry.url.clone(), indexed);
+        self.evict(&mut index).await;
+    }
+
+    /// Removes entries older than `max_age`, then the least recently used ones until
+    /// the cache fits in `max_size`, and saves the index
+    async fn evict(&self, index: &mut Index) {
+        let now = unix_time();
+        let mut evicted = Vec::new();
+        if let Some(max_age) = self.max_age {
+            evicted.extend(
+                index
+                    .entries
+                    .iter()
+                    .filter(|(_, entry)| now.saturating_sub(entry.stored_at) > max_age.as_secs())
+                    .map(|(url, _)| url.clone()),
+            );
+        }
+        if let Some(max_size) = self.max_size {
+            let mut by_access: Vec<_> = index
+                .entries
+                .iter()
+                .filter(|(url, _)| !evicted.contains(url))
+                .map(|(url, entry)| (entry.accessed, entry.size, url))
+                .collect();
+            by_access.sort();
+            let mut total: u64 = by_access.iter().map(|(_, size, _)| size).sum();
+            for (_, size, url) in by_access {
+                if total <= max_size {
+                    break;
+                }
+                total -= size;
+                evicted.push(url.clone());
+            }
+        }
+        for url in evicted {
+            index.entries.remove(&url);
+            if let Err(err) = self.store.remove(&key(&url)).await {
+                warn!("Failed to evict {url} from the cache: {err}");
+            }
+        }
+        self.save_index(index).await;
+    }
+
+    async fn save_index(&self, index: &Index) {
+        if let Err(err) = self.store.write_index(&index.to_json()).await {
+            warn!("Failed to save the cache index: {err}");
+        }
+        self.stats.set_contents(index);
+    }
+}
+
+/// How well the [cache](crate::WebAssetPlugin::cache) works, e.g. for a debug overlay.
+///
+/// The plugin inserts this as a resource, updated as assets load. Loads from the
+/// [memory cache](crate::WebAssetPlugin::memory_cache_size) count as hits too, otherwise
+/// everything stays at zero without a cache.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetCacheStats;
+/// fn log_cache_stats(stats: Res<WebAssetCacheStats>) {
+///     info!(
+///         "{} hits, {} misses, {} of {} bytes from the cache",
+///         stats.hits(),
+///         stats.misses(),
+///         stats.bytes_from_cache(),
+///         stats.bytes_from_cache() + stats.bytes_from_network(),
+///     );
+/// }
+/// ```
+#[derive(Resource, Clone, Default)]
+pub struct WebAssetCacheStats(Arc<CacheCounters>);
+
+#[derive(Default)]
+struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    bytes_from_cache: AtomicU64,
+    bytes_from_network: AtomicU64,
+    entries: AtomicU64,
+    size: AtomicU64,
+}
+
+impl WebAssetCacheStats {
+    /// Number of loads served from the cache, including ones the server confirmed
+    /// were still current
+    pub fn hits(&self) -> u64 {
+        self.0.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of loads downloaded because the cache had no current copy
+    pub fn misses(&self) -> u64 {
+        self.0.misses.load(Ordering::Relaxed)
+    }
+
+    /// Bytes loaded from the cache
+    pub fn bytes_from_cache(&self) -> u64 {
+        self.0.bytes_from_cache.load(Ordering::Relaxed)
+    }
+
+    /// Bytes downloaded by cache misses
+    pub fn bytes_from_network(&self) -> u64 {
+        self.0.bytes_from_network.load(Ordering::Relaxed)
+    }
+
+    /// Number of assets in the cache
+    pub fn entries(&self) -> u64 {
+        self.0.entries.load(Ordering::Relaxed)
+    }
+
+    /// Total size of the assets in the cache, in bytes
+    pub fn size(&self) -> u64 {
+        self.0.size.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn hit(&self, bytes: usize) {
+        self.0.hits.fetch_add(1, Ordering::Relaxed);
+        self.0
+            .bytes_from_cache
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn miss(&self, bytes: usize) {
+        self.0.misses.fetch_add(1, Ordering::Relaxed);
+        self.0
+            .bytes_from_network
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    fn set_contents(&self, index: &Index) {
+        let size = index.entries.values().map(|entry| entry.size).sum();
+        self.0
+            .entries
+            .store(index.entries.len() as u64, Ordering::Relaxed);
+        self.0.size.store(size, Ordering::Relaxed);
+    }
+}
+
+impl fmt::Debug for WebAssetCacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebAssetCacheStats")
+            .field("hits", &self.hits())
+            .field("misses", &self.misses())
+            .field("bytes_from_cache", &self.bytes_from_cache())
+            .field("bytes_from_network", &self.bytes_from_network())
+            .field("entries", &self.entries())
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+/// Removes downloaded assets from the [cache](crate::WebAssetPlugin::cache), e.g. for a
+/// "clear downloaded content" button.
+///
+/// Assets that are already loaded stay loaded, as do archives kept in memory. The next
+/// load downloads them again. Assets kept by the
+/// [memory cache](crate::WebAssetPlugin::memory_cache_size) are removed too.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetCache;
+/// fn clear_downloads(cache: WebAssetCache) {
+///     cache.purge("https://example.com/level1/map.glb");
+///     // Or everything
+///     cache.purge_all();
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct WebAssetCache<'w> {
+    asset_server: Res<'w, AssetServer>,
+}
+
+impl<'w> WebAssetCache<'w> {
+    /// Removes the cached copy of the remote asset at `path` in the background
+    pub fn purge(&self, path: impl Into<PathBuf>) {
+        let asset_server = self.asset_server.clone();
+        let path = path.into();
+        IoTaskPool::get()
+            .spawn(async move {
+                let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+                    warn!(
+                        "Can't purge {}, the asset io isn't a WebAssetIo",
+                        path.display()
+                    );
+                    return;
+                };
+                let resolved = asset_io.resolve_path(&path);
+                let url = resolved.as_deref().unwrap_or(&path).to_string_lossy();
+                // Archive entries are cached as part of their archive
+                let url = crate::archive::split(&url).map_or(&*url, |(url, _)| url);
+                if let Some(memory) = &asset_io.memory_cache {
+                    memory.remove(url);
+                }
+                if let Some(cache) = &asset_io.cache {
+                    cache.remove(url).await;
+                }
+            })
+            .detach();
+    }
+
+    /// Removes every cached asset in the background
+    pub fn purge_all(&self) {
+        let asset_server = self.asset_server.clone();
+        IoTaskPool::get()
+            .spawn(async move {
+                let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+                    warn!("Can't purge the cache, the asset io isn't a WebAssetIo");
+                    return;
+                };
+                if let Some(memory) = &asset_io.memory_cache {
+                    memory.clear();
+                }
+                if let Some(cache) = &asset_io.cache {
+                    cache.clear().await;
+                }
+            })
+            .detach();
+    }
+}
+
+/// Receives the urls of stale cache entries that were loaded by
+/// [`WebAssetIo`](crate::WebAssetIo)
+#[derive(Resource)]
+pub(crate) struct StaleReceiver(pub(crate) Receiver<String>);
+
+/// Revalidates stale cache entries in the background, reloading the assets that changed
+pub(crate) fn revalidate_stale(asset_server: Res<AssetServer>, receiver: Res<StaleReceiver>) {
+    let stale: HashSet<_> = receiver.0.try_iter().collect();
+    for url in stale {
+        let asset_server = asset_server.clone();
+        IoTaskPool::get()
+            .spawn(async move {
+                let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+                    warn!("Can't revalidate {url}, the asset io isn't a WebAssetIo");
+                    return;
+                };
+                match asset_io.revalidate(&url).await {
+                    Ok(true) => reload(&asset_server, asset_io.changed(&url)),
+                    Ok(false) => {}
+                    Err(err) => warn!("Failed to revalidate {url}: {err}"),
+                }
+            })
+            .detach();
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use bevy::{asset::AssetIo, ecs::system::SystemState};
+    use futures_lite::future;
+    use std::{path::Path, time::Instant};
+
+    use super::*;
+    use crate::{
+        test_util::{MockResponse, MockServer},
+        WebAssetPlugin,
+    };
+
+    fn fetched(headers: &[(&str, &str)]) -> Fetched {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(*name, *value).unwrap();
+        }
+        Fetched {
+            body: b"body".to_vec(),
+            validator: map.get("etag").map(str::to_string),
+            headers: map,
+        }
+    }
+
+    fn entry(url: &str, size: usize) -> CacheEntry {
+        CacheEntry::new(
+            url,
+            &Fetched {
+                body: vec![0; size],
+                ..fetched(&[])
+            },
+        )
+        .unwrap()
+    }
+
+    /// A cache in a directory of its own, removed when dropped
+    struct TempCache {
+        cache: Cache,
+        directory: PathBuf,
+    }
+
+    impl TempCache {
+        fn new(max_size: Option<u64>, max_age: Option<Duration>) -> Self {
+            let directory =
+                std::env::temp_dir().join(format!("bevy_web_asset_{}", fastrand::u64(..)));
+            let config = CacheConfig {
+                directory: Some(directory.clone()),
+                max_size,
+                max_age,
+                ..default()
+            };
+            Self {
+                cache: Cache::new(&config, default()),
+                directory,
+            }
+        }
+
+        fn put(&self, entry: CacheEntry) {
+            future::block_on(self.cache.put(&entry));
+        }
+
+        fn has(&self, url: &str) -> bool {
+            future::block_on(self.cache.get(url)).is_some()
+        }
+
+        /// Makes `url` look like it was last loaded `ago` seconds ago
+        fn accessed(&self, url: &str, ago: u64) {
+            let mut index = future::block_on(self.cache.index());
+            index.entries.get_mut(url).unwrap().accessed = unix_time() - ago;
+        }
+    }
+
+    impl Drop for TempCache {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.directory);
+        }
+    }
+
+    #[test]
+    fn computes_freshness() {
+        let now = unix_time();
+        fn fresh_until(headers: &[(&str, &str)]) -> Option<u64> {
+            CacheEntry::new("url", &fetched(headers))
+                .unwrap()
+                .fresh_until
+        }
+
+        assert!(CacheEntry::new("url", &fetched(&[("Cache-Control", "no-store")])).is_none());
+        assert!(
+            CacheEntry::new("url", &fetched(&[("Cache-Control", "max-age=5, No-Store")])).is_none()
+        );
+        assert_eq!(fresh_until(&[]), None);
+        let max_age = fresh_until(&[("Cache-Control", "public, max-age=\"60\"")]).unwrap();
+        assert!((now + 60..now + 62).contains(&max_age));
+        // `no-cache` means revalidating every time, whatever else is there
+        let no_cache = fresh_until(&[("Cache-Control", "no-cache, max-age=60")]).unwrap();
+        assert!(no_cache < now + 2);
+        // `max-age` takes precedence over `Expires`
+        let expires = ("Expires", "Fri, 24 May 2013 00:00:00 GMT");
+        assert_eq!(fresh_until(&[expires]), Some(1369353600));
+        let max_age = fresh_until(&[expires, ("Cache-Control", "max-age=60")]).unwrap();
+        assert!(max_age >= now + 60);
+        assert_eq!(fresh_until(&[("Expires", "0")]), None);
+
+        let fresh = CacheEntry::new("url", &fetched(&[("Cache-Control", "max-age=60")]));
+        assert!(fresh.unwrap().is_fresh());
+        assert!(!CacheEntry::new("url", &fetched(&[expires]))
+            .unwrap()
+            .is_fresh());
+    }
+
+    #[test]
+    fn adds_conditional_headers() {
+        let conditional = |entry: CacheEntry| {
+            let mut headers = HeaderMap::new();
+            entry.add_conditional_headers(&mut headers);
+            (
+                headers.get("if-none-match").map(str::to_string),
+                headers.get("if-modified-since").map(str::to_string),
+            )
+        };
+        let date = "Wed, 21 Oct 2015 07:28:00 GMT";
+        let both = CacheEntry::new(
+            "mock://a",
+            &fetched(&[("ETag", "W/\"1\""), ("Last-Modified", date)]),
+        );
+        assert_eq!(
+            conditional(both.unwrap()),
+            (Some("W/\"1\"".to_string()), Some(date.to_string()))
+        );
+        let dated = CacheEntry::new("mock://a", &fetched(&[("Last-Modified", date)]));
+        assert_eq!(conditional(dated.unwrap()), (None, Some(date.to_string())));
+        assert_eq!(conditional(entry("mock://a", 1)), (None, None));
+
+        // Entries of earlier versions only kept the validator
+        let old = CacheEntry {
+            validator: Some("\"1\"".to_string()),
+            ..entry("mock://a", 1)
+        };
+        assert_eq!(conditional(old), (Some("\"1\"".to_string()), None));
+    }
+
+    #[test]
+    fn stores_entries_with_their_meta() {
+        let temp = TempCache::new(None, None);
+        temp.put(
+            CacheEntry::new(
+                "https://example.com/hero.png",
+                &fetched(&[("ETag", "\"v1\""), ("Cache-Control", "max-age=60")]),
+            )
+            .unwrap(),
+        );
+        let cached = future::block_on(temp.cache.get("https://example.com/hero.png")).unwrap();
+        assert_eq!(cached.body, b"body");
+        assert_eq!(cached.etag.as_deref(), Some("\"v1\""));
+        assert!(cached.is_fresh());
+        assert!(!temp.has("https://example.com/other.png"));
+    }
+
+    #[test]
+    fn evicts_entries_older_than_max_age() {
+        let temp = TempCache::new(None, Some(Duration::from_secs(60)));
+        temp.put(CacheEntry {
+            stored_at: unix_time() - 120,
+            ..entry("old", 1)
+        });
+        temp.put(entry("new", 1));
+        assert!(!temp.has("old"));
+        assert!(temp.has("new"));
+    }
+
+    #[test]
+    fn evicts_least_recently_used_beyond_max_size() {
+        let temp = TempCache::new(Some(10), None);
+        temp.put(entry("a", 4));
+        temp.put(entry("b", 4));
+        temp.accessed("a", 10);
+        temp.accessed("b", 20);
+        // `b` was used longest ago, so it goes to make room
+        temp.put(entry("c", 4));
+        assert!(temp.has("a") && temp.has("c"));
+        assert!(!temp.has("b"));
+        // Too large for the cache at all
+        temp.put(entry("huge", 11));
+        assert!(!temp.has("huge"));
+        assert!(temp.has("a") && temp.has("c"));
+    }
+
+    #[test]
+    fn evicts_expired_entries_of_earlier_runs() {
+        let temp = TempCache::new(None, None);
+        temp.put(CacheEntry {
+            stored_at: unix_time() - 120,
+            ..entry("old", 1)
+        });
+        temp.put(entry("new", 1));
+        // The next run, with a shorter max age, and without an index
+        std::fs::remove_file(temp.directory.join("index.json")).ok();
+        let config = CacheConfig {
+            directory: Some(temp.directory.clone()),
+            max_age: Some(Duration::from_secs(60)),
+            ..default()
+        };
+        let cache = Cache::new(&config, default());
+        assert!(future::block_on(cache.get("old")).is_none());
+        assert!(future::block_on(cache.get("new")).is_some());
+    }
+
+    #[test]
+    fn removes_entries() {
+        let temp = TempCache::new(None, None);
+        temp.put(entry("a", 1));
+        temp.put(entry("b", 2));
+        temp.put(entry("c", 3));
+        future::block_on(temp.cache.remove("a"));
+        assert!(!temp.has("a") && temp.has("b"));
+        future::block_on(temp.cache.clear());
+        assert!(!temp.has("b") && !temp.has("c"));
+    }
+
+    #[test]
+    fn purges_from_every_cache() {
+        let temp = TempCache::new(None, None);
+        let server = MockServer::start();
+        server.mock("/hero.png", MockResponse::ok("png"));
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(WebAssetPlugin {
+            cache: Some(CacheConfig {
+                directory: Some(temp.directory.clone()),
+                ..default()
+            }),
+            memory_cache_size: Some(1024),
+            ..server.plugin()
+        });
+        let asset_server = app.world.resource::<AssetServer>().clone();
+        let asset_io = asset_server
+            .asset_io()
+            .downcast_ref::<WebAssetIo>()
+            .unwrap();
+        future::block_on(asset_io.load_path(Path::new("mock://hero.png"))).unwrap();
+        let url = server.url("hero.png");
+        let cache = asset_io.cache.as_ref().unwrap();
+        let memory = asset_io.memory_cache.as_ref().unwrap();
+        assert!(future::block_on(cache.get(&url)).is_some());
+        assert!(memory.get(&url).is_some());
+
+        let mut state = SystemState::<WebAssetCache>::new(&mut app.world);
+        state.get(&app.world).purge("mock://hero.png");
+        let started = Instant::now();
+        while future::block_on(cache.get(&url)).is_some() {
+            assert!(started.elapsed() < Duration::from_secs(5), "not purged");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(memory.get(&url).is_none());
+    }
+
+    #[test]
+    fn counts_contents() {
+        let temp = TempCache::new(Some(10), None);
+        let stats = temp.cache.stats.clone();
+        temp.put(entry("a", 4));
+        temp.put(entry("b", 4));
+        assert_eq!((stats.entries(), stats.size()), (2, 8));
+        temp.put(entry("c", 4));
+        assert_eq!((stats.entries(), stats.size()), (2, 8));
+        future::block_on(temp.cache.remove("c"));
+        assert_eq!((stats.entries(), stats.size()), (1, 4));
+        future::block_on(temp.cache.clear());
+        assert_eq!((stats.entries(), stats.size()), (0, 0));
+    }
+
+    #[test]
+    fn counts_hits_and_misses() {
+        let temp = TempCache::new(None, None);
+        let server = MockServer::start();
+        server.mock("/hero.png", MockResponse::ok("png").etag("\"1\""));
+        server.mock(
+            "/fresh.png",
+            MockResponse::ok("fresh").header("Cache-Control", "max-age=60"),
+        );
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(WebAssetPlugin {
+            cache: Some(CacheConfig {
+                directory: Some(temp.directory.clone()),
+                ..default()
+            }),
+            ..server.plugin()
+        });
+        let stats = app.world.resource::<WebAssetCacheStats>().clone();
+        let asset_server = app.world.resource::<AssetServer>();
+        let load =
+            |path: &str| future::block_on(asset_server.asset_io().load_path(Path::new(path)));
+
+        load("mock://hero.png").unwrap();
+        assert_eq!((stats.hits(), stats.misses()), (0, 1));
+        // Revalidated, and still current
+        load("mock://hero.png").unwrap();
+        load("mock://fresh.png").unwrap();
+        load("mock://fresh.png").unwrap();
+        assert_eq!((stats.hits(), stats.misses()), (2, 2));
+        assert_eq!(stats.bytes_from_network(), 8);
+        assert_eq!(stats.bytes_from_cache(), 8);
+        assert_eq!((stats.entries(), stats.size()), (2, 8));
+    }
+
+    #[test]
+    fn picks_strategy_by_pattern() {
+        let config = CacheConfig {
+            strategies: vec![(
+                "https://cdn.example.com/ui/*".to_string(),
+                CacheStrategy::StaleWhileRevalidate,
+            )],
+            ..default()
+        };
+        let cache = Cache::new(&config, default());
+        assert_eq!(
+            cache.strategy("https://cdn.example.com/ui/button.png"),
+            CacheStrategy::StaleWhileRevalidate
+        );
+        assert_eq!(
+            cache.strategy("https://cdn.example.com/levels/1.ron"),
+            CacheStrategy::Revalidate
+        );
+    }
+
+    #[test]
+    fn loads_stale_copy_and_revalidates_in_background() {
+        let temp = TempCache::new(None, None);
+        let server = MockServer::start();
+        server.mock("/hero.png", MockResponse::ok("v1").etag("\"1\""));
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(WebAssetPlugin {
+            cache: Some(CacheConfig {
+                directory: Some(temp.directory.clone()),
+                strategy: CacheStrategy::StaleWhileRevalidate,
+                ..default()
+            }),
+            ..server.plugin()
+        });
+        let asset_server = app.world.resource::<AssetServer>().clone();
+        let load = || {
+            future::block_on(
+                asset_server
+                    .asset_io()
+                    .load_path(Path::new("mock://hero.png")),
+            )
+        };
+        assert_eq!(load().unwrap(), b"v1");
+
+        server.mock("/hero.png", MockResponse::ok("v2").etag("\"2\""));
+        // The stale copy right away, without waiting for the server
+        assert_eq!(load().unwrap(), b"v1");
+        assert_eq!(server.requests_to("/hero.png").len(), 1);
+
+        // Revalidated by `revalidate_stale` in the background
+        let started = Instant::now();
+        while server.requests_to("/hero.png").len() < 2 || load().unwrap() != b"v2" {
+            assert!(
+                started.elapsed() < Duration::from_secs(5),
+                "not revalidated"
+            );
+            app.update();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let requests = server.requests_to("/hero.png");
+        assert_eq!(requests[1].headers.get("if-none-match"), Some("\"1\""));
+    }
+}
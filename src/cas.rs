@@ -0,0 +1,39 @@
+/// How `cas://<sha256>` paths, referencing assets by the hex encoded sha256 of their
+/// content, are loaded. The hash can be followed by an extension for Bevy to pick the
+/// asset loader by, e.g. `cas://9f86d0....png`.
+///
+/// Every path with the same hash is stored in the cache and downloaded only once, however
+/// many urls or names the asset has, checked against the hash, and never revalidated.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{CasConfig, WebAssetPlugin};
+/// App::new().add_plugin(WebAssetPlugin {
+///     cas: CasConfig {
+///         base_url: Some("https://cdn.example.com/blobs".to_string()),
+///     },
+///     ..default()
+/// });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CasConfig {
+    /// Where blobs are downloaded from, as `<base_url>/<sha256>`. `None`, the default,
+    /// fails to load `cas://` paths.
+    pub base_url: Option<String>,
+}
+
+impl CasConfig {
+    /// The url the blob of a `cas://` path is downloaded from
+    pub(crate) fn url(&self, path: &str) -> Option<String> {
+        let base_url = self.base_url.as_deref()?.trim_end_matches('/');
+        Some(format!("{base_url}/{}", digest(path)?))
+    }
+}
+
+/// The lowercase hex encoded sha256 of a `cas://<sha256>` path, without its extension
+pub(crate) fn digest(path: &str) -> Option<String> {
+    let content = path.strip_prefix("cas://")?;
+    let digest = content.split(['.', '/', '?', '#']).next()?;
+    let valid = digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit());
+    valid.then(|| digest.to_ascii_lowercase())
+}
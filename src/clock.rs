@@ -0,0 +1,114 @@
+/// Seconds since the unix epoch
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or_default()
+}
+
+/// Seconds since the unix epoch
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn unix_time() -> u64 {
+    // `SystemTime` isn't available in browsers
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// A point in time in UTC, broken down for formatting
+pub(crate) struct DateTime {
+    pub(crate) year: i64,
+    pub(crate) month: u32,
+    pub(crate) day: u32,
+    pub(crate) hour: u32,
+    pub(crate) minute: u32,
+    pub(crate) second: u32,
+    /// Days since sunday
+    pub(crate) weekday: u32,
+}
+
+impl DateTime {
+    pub(crate) fn from_unix(time: u64) -> Self {
+        let days = (time / 86400) as i64;
+        let seconds = (time % 86400) as u32;
+
+        // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: seconds / 3600,
+            minute: seconds / 60 % 60,
+            second: seconds % 60,
+            weekday: (days + 4).rem_euclid(7) as u32,
+        }
+    }
+
+    /// Parses `Sat, 01 Apr 2023 12:00:00 GMT` into seconds since the unix epoch
+    pub(crate) fn parse_http_date(value: &str) -> Option<u64> {
+        let (_weekday, date) = value.trim().split_once(", ")?;
+        let mut parts = date.split(' ');
+        let day: i64 = parts.next()?.parse().ok()?;
+        let month = parts.next()?;
+        let month = MONTHS.iter().position(|&name| name == month)? as i64 + 1;
+        let year: i64 = parts.next()?.parse().ok()?;
+        let mut time = parts
+            .next()?
+            .split(':')
+            .map(|part| part.parse::<i64>().ok());
+        let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+        if parts.next() != Some("GMT") || !(1..=31).contains(&day) {
+            return None;
+        }
+
+        // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let mp = (month + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146097 + doe - 719468;
+        u64::try_from(days * 86400 + hour * 3600 + minute * 60 + second).ok()
+    }
+
+    pub(crate) fn now() -> Self {
+        Self::from_unix(unix_time())
+    }
+
+    /// `20230401T120000Z`, as used by AWS
+    pub(crate) fn to_iso8601_basic(&self) -> String {
+        format!(
+            "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+
+    /// `Sat, 01 Apr 2023 12:00:00 GMT`, as used in http headers
+    pub(crate) fn to_http_date(&self) -> String {
+        const DAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+        format!(
+            "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+            DAYS[self.weekday as usize],
+            self.day,
+            MONTHS[self.month as usize - 1],
+            self.year,
+            self.hour,
+            self.minute,
+            self.second
+        )
+    }
+}
@@ -0,0 +1,297 @@
+use bevy::utils::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::{WebAssetError, WebAssetIo};
+
+/// Content types accepted for every extension, since they say nothing about the content
+const GENERIC: &[&str] = &["application/octet-stream", "binary/octet-stream"];
+
+/// Content types that are never assets themselves, but error or login pages
+const PAGES: &[&str] = &["text/html", "application/xhtml+xml"];
+
+/// The content types that make sense for each file extension. A trailing `*`
+/// matches any suffix, e.g. `image/*`.
+const EXPECTED: &[(&[&str], &[&str])] = &[
+    (
+        &[
+            "png", "jpg", "jpeg", "gif", "webp", "bmp", "tga", "dds", "ktx2", "basis", "hdr",
+            "exr", "qoi", "pnm", "ico", "tif", "tiff",
+        ],
+        &["image/*"],
+    ),
+    (
+        &["ogg", "oga", "wav", "mp3", "flac"],
+        &["audio/*", "application/ogg"],
+    ),
+    (
+        &["ttf", "otf", "woff", "woff2"],
+        &[
+            "font/*",
+            "application/font-*",
+            "application/x-font-*",
+            "application/vnd.ms-opentype",
+        ],
+    ),
+    (
+        &["gltf"],
+        &["model/gltf+json", "application/json", "text/plain"],
+    ),
+    (&["glb"], &["model/gltf-binary"]),
+    (&["json"], &["application/json", "text/json", "text/plain"]),
+    (
+        &["zip"],
+        &["application/zip", "application/x-zip-compressed"],
+    ),
+    (&["tar"], &["application/x-tar"]),
+    (
+        &["tgz", "gz"],
+        &[
+            "application/gzip",
+            "application/x-gzip",
+            "application/x-gtar",
+        ],
+    ),
+    (
+        &[
+            "txt", "ron", "toml", "yaml", "yml", "csv", "wgsl", "glsl", "vert", "frag",
+        ],
+        &["text/*"],
+    ),
+];
+
+/// The extensions of Bevy's asset loaders for common content types
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+    ("image/bmp", "bmp"),
+    ("image/ktx2", "ktx2"),
+    ("image/vnd.ms-dds", "dds"),
+    ("image/x-exr", "exr"),
+    ("image/vnd.radiance", "hdr"),
+    ("audio/ogg", "ogg"),
+    ("application/ogg", "ogg"),
+    ("audio/wav", "wav"),
+    ("audio/x-wav", "wav"),
+    ("audio/mpeg", "mp3"),
+    ("audio/flac", "flac"),
+    ("font/ttf", "ttf"),
+    ("font/otf", "otf"),
+    ("model/gltf+json", "gltf"),
+    ("model/gltf-binary", "glb"),
+    ("text/wgsl", "wgsl"),
+];
+
+/// The default of [`WebAssetPlugin::content_type_extensions`](crate::WebAssetPlugin::content_type_extensions)
+pub(crate) fn default_extensions() -> HashMap<String, String> {
+    EXTENSIONS
+        .iter()
+        .map(|&(content_type, extension)| (content_type.to_string(), extension.to_string()))
+        .collect()
+}
+
+/// The media type of `content_type` without parameters, lowercase
+fn essence(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+fn matches(content_type: &str, pattern: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => content_type.starts_with(prefix),
+        None => content_type == pattern,
+    }
+}
+
+/// The lowercase extension of the file at `url`, ignoring its query
+fn extension(url: &str) -> String {
+    let path = url::Url::parse(url).map_or(url.to_string(), |url| url.path().to_string());
+    path.rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+/// The content type uploads of the file at `url` are sent with
+pub(crate) fn of(url: &str) -> &'static str {
+    let extension = extension(url);
+    match extension.as_str() {
+        "json" => "application/json",
+        "txt" | "ron" | "toml" | "yaml" | "yml" | "csv" => "text/plain",
+        "zip" => "application/zip",
+        _ => EXTENSIONS
+            .iter()
+            .find(|&&(_, known)| known == extension)
+            .map_or("application/octet-stream", |&(content_type, _)| {
+                content_type
+            }),
+    }
+}
+
+/// Whether a response with `content_type` could be the file at `url`.
+///
+/// Only known extensions are checked, anything else just can't be an html page.
+pub(crate) fn is_expected(url: &str, content_type: &str) -> bool {
+    let content_type = essence(content_type);
+    if content_type.is_empty() || GENERIC.contains(&content_type.as_str()) {
+        return true;
+    }
+    let extension = extension(url);
+    match EXPECTED
+        .iter()
+        .find(|(extensions, _)| extensions.contains(&extension.as_str()))
+    {
+        Some((_, expected)) => expected
+            .iter()
+            .any(|&pattern| matches(&content_type, pattern)),
+        None => {
+            !PAGES.contains(&content_type.as_str()) || matches!(extension.as_str(), "html" | "htm")
+        }
+    }
+}
+
+impl WebAssetIo {
+    /// `path` with an extension Bevy can pick the asset loader by. Paths without one,
+    /// e.g. `https://example.com/assets/12345`, get the extension of the `Content-Type`
+    /// found with a `HEAD` request, in a query parameter that isn't sent to the server:
+    /// `https://example.com/assets/12345?.png`.
+    ///
+    /// The extensions of content types are set in
+    /// [`WebAssetPlugin::content_type_extensions`](crate::WebAssetPlugin::content_type_extensions).
+    pub async fn loader_path(&self, path: impl AsRef<Path>) -> Result<PathBuf, WebAssetError> {
+        let path = path.as_ref();
+        let uri = path.to_string_lossy();
+        let name = uri.split(['?', '#']).next().unwrap_or_default();
+        if name
+            .rsplit('/')
+            .next()
+            .is_some_and(|name| name.contains('.'))
+        {
+            return Ok(path.to_owned());
+        }
+        let metadata = self.remote_metadata(path).await?;
+        let Some(content_type) = metadata.content_type else {
+            return Err(WebAssetError::Decode {
+                url: uri.into_owned(),
+                message: "no Content-Type to pick the asset loader by".to_string(),
+            });
+        };
+        let Some(extension) = self.content_type_extensions.get(&essence(&content_type)) else {
+            return Err(WebAssetError::ContentType {
+                url: uri.into_owned(),
+                content_type,
+            });
+        };
+        let separator = if uri.contains('?') { '&' } else { '?' };
+        Ok(PathBuf::from(format!("{uri}{separator}.{extension}")))
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use bevy::prelude::*;
+    use futures_lite::future;
+
+    use super::*;
+    use crate::{
+        test_util::{MockResponse, MockServer},
+        WebAssetPlugin,
+    };
+
+    #[test]
+    fn expects_content_types_by_extension() {
+        assert!(is_expected("https://cdn/hero.png", "image/png"));
+        assert!(is_expected(
+            "https://cdn/hero.PNG?v=2",
+            "Image/PNG; charset=binary"
+        ));
+        assert!(is_expected(
+            "https://cdn/hero.png",
+            "application/octet-stream"
+        ));
+        assert!(is_expected("https://cdn/hero.png", ""));
+        assert!(!is_expected("https://cdn/hero.png", "text/html"));
+        assert!(!is_expected("https://cdn/hero.png", "audio/ogg"));
+        assert!(is_expected("https://cdn/theme.ogg", "application/ogg"));
+        assert!(is_expected(
+            "https://cdn/font.woff2",
+            "application/font-woff2"
+        ));
+        assert!(is_expected("https://cdn/scene.gltf", "model/gltf+json"));
+        assert!(!is_expected("https://cdn/scene.glb", "application/json"));
+        assert!(is_expected(
+            "https://cdn/level.ron",
+            "text/plain; charset=utf-8"
+        ));
+
+        // Unknown extensions only can't be pages
+        assert!(is_expected(
+            "https://cdn/level.custom",
+            "application/x-custom"
+        ));
+        assert!(!is_expected("https://cdn/level.custom", "text/html"));
+        assert!(!is_expected(
+            "https://cdn/assets/12345",
+            "application/xhtml+xml"
+        ));
+        assert!(is_expected("https://cdn/index.html", "text/html"));
+    }
+
+    #[test]
+    fn picks_loader_extension_by_content_type() {
+        let server = MockServer::start();
+        server.mock(
+            "/assets/12345",
+            MockResponse::ok("png").header("Content-Type", "Image/PNG; charset=binary"),
+        );
+        server.mock(
+            "/assets/wasm",
+            MockResponse::ok("wasm").header("Content-Type", "application/wasm"),
+        );
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(WebAssetPlugin {
+            content_type_extensions: [("application/wasm".to_string(), "wasm".to_string())]
+                .into_iter()
+                .chain(default_extensions())
+                .collect(),
+            ..server.plugin()
+        });
+        let asset_server = app.world.resource::<AssetServer>();
+        let asset_io: &WebAssetIo = asset_server.asset_io().downcast_ref().unwrap();
+        let loader_path = |path: &str| future::block_on(asset_io.loader_path(path));
+
+        assert_eq!(
+            loader_path("mock://assets/12345").unwrap(),
+            Path::new("mock://assets/12345?.png")
+        );
+        assert_eq!(
+            loader_path("mock://assets/12345?v=2").unwrap(),
+            Path::new("mock://assets/12345?v=2&.png")
+        );
+        assert_eq!(
+            loader_path("mock://assets/wasm").unwrap(),
+            Path::new("mock://assets/wasm?.wasm")
+        );
+        // Paths with an extension are left alone, without asking the server
+        assert_eq!(
+            loader_path("mock://assets/hero.png?v=2").unwrap(),
+            Path::new("mock://assets/hero.png?v=2")
+        );
+        assert!(server.requests_to("/assets/hero.png").is_empty());
+
+        server.mock(
+            "/assets/wasm",
+            MockResponse::ok("wasm").header("Content-Type", "application/x-unknown"),
+        );
+        assert!(matches!(
+            loader_path("mock://assets/wasm"),
+            Err(WebAssetError::ContentType { .. })
+        ));
+    }
+}
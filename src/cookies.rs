@@ -0,0 +1,347 @@
+use bevy::prelude::*;
+use std::sync::{Arc, RwLock};
+use url::Url;
+
+use crate::{
+    clock::{unix_time, DateTime},
+    HeaderMap,
+};
+
+/// Domains under which anyone can register a site, so none of them may set cookies
+/// for all of them. Single label domains like `com` are rejected anyway, this covers
+/// the common multi label ones rather than all of the Public Suffix List.
+const PUBLIC_SUFFIXES: &[&str] = &[
+    "ac.uk",
+    "co.uk",
+    "gov.uk",
+    "org.uk",
+    "com.au",
+    "net.au",
+    "org.au",
+    "com.br",
+    "com.cn",
+    "co.in",
+    "co.jp",
+    "ne.jp",
+    "or.jp",
+    "co.kr",
+    "com.mx",
+    "co.nz",
+    "com.tr",
+    "co.za",
+    "appspot.com",
+    "azurewebsites.net",
+    "blob.core.windows.net",
+    "cloudfront.net",
+    "firebaseapp.com",
+    "github.io",
+    "gitlab.io",
+    "herokuapp.com",
+    "netlify.app",
+    "pages.dev",
+    "s3.amazonaws.com",
+    "vercel.app",
+    "web.app",
+    "workers.dev",
+];
+
+/// A cookie set by an asset host
+#[derive(Debug, Clone)]
+struct Cookie {
+    name: String,
+    value: String,
+    /// Lowercase host, or domain if `host_only` isn't set
+    domain: String,
+    /// Whether the cookie is only sent to `domain` itself, rather than its subdomains too
+    host_only: bool,
+    path: String,
+    secure: bool,
+    /// Seconds since the unix epoch, `None` for session cookies
+    expires: Option<u64>,
+}
+
+impl Cookie {
+    /// Parses a `Set-Cookie` header sent by `url`
+    fn parse(url: &Url, header: &str, now: u64) -> Option<Self> {
+        let mut parts = header.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        let host = url.host_str()?.to_ascii_lowercase();
+        let has_domain = matches!(url.host(), Some(url::Host::Domain(_)));
+        if name.is_empty() {
+            return None;
+        }
+        let mut cookie = Self {
+            name: name.to_string(),
+            value: value.trim().to_string(),
+            domain: host.clone(),
+            host_only: true,
+            path: default_path(url),
+            secure: false,
+            expires: None,
+        };
+        let mut max_age = None;
+        for attribute in parts {
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "domain" if !value.is_empty() => {
+                    let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                    // Hosts can't set cookies for other sites
+                    if !domain_matches(&host, &domain) {
+                        return None;
+                    }
+                    // Nor for all sites under a public suffix, or other ip addresses. A
+                    // public suffix that is the host itself keeps the cookie to the host.
+                    if has_domain && !is_public_suffix(&domain) {
+                        cookie.domain = domain;
+                        cookie.host_only = false;
+                    } else if domain != host {
+                        return None;
+                    }
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "secure" => cookie.secure = true,
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "expires" => cookie.expires = DateTime::parse_http_date(value),
+                _ => {}
+            }
+        }
+        // Max-Age wins over Expires
+        if let Some(max_age) = max_age {
+            cookie.expires = Some(now.saturating_add_signed(max_age));
+        }
+        Some(cookie)
+    }
+
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str().map(str::to_ascii_lowercase) else {
+            return false;
+        };
+        let domain = match self.host_only {
+            true => host == self.domain,
+            false => domain_matches(&host, &self.domain),
+        };
+        let path = url.path();
+        let path = path == self.path
+            || path.starts_with(&self.path)
+                && (self.path.ends_with('/') || path[self.path.len()..].starts_with('/'));
+        domain && path && (!self.secure || url.scheme() == "https")
+    }
+}
+
+/// Whether `host` is `domain` or one of its subdomains
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|subdomain| subdomain.ends_with('.'))
+}
+
+/// Whether sites can be registered directly under `domain`, see [`PUBLIC_SUFFIXES`]
+fn is_public_suffix(domain: &str) -> bool {
+    !domain.contains('.') || PUBLIC_SUFFIXES.contains(&domain)
+}
+
+/// The directory of the path of `url`, which cookies without a `Path` are sent to
+fn default_path(url: &Url) -> String {
+    match url.path().rsplit_once('/') {
+        Some((directory, _)) if !directory.is_empty() => directory.to_string(),
+        _ => "/".to_string(),
+    }
+}
+
+/// Cookies set by asset hosts, sent back with later requests to them. Native only,
+/// browsers keep cookies themselves, which `FetchOptions::credentials` sends on wasm.
+///
+/// Set in [`WebAssetPlugin::cookies`](crate::WebAssetPlugin::cookies), and inserted as a
+/// resource, shared by all requests.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{WebAssetCookies, WebAssetPlugin};
+/// App::new().add_plugin(WebAssetPlugin {
+///     cookies: Some(WebAssetCookies::default()),
+///     ..default()
+/// });
+///
+/// fn log_out(cookies: Res<WebAssetCookies>) {
+///     cookies.clear();
+/// }
+/// ```
+#[derive(Resource, Clone, Default)]
+pub struct WebAssetCookies(Arc<RwLock<Vec<Cookie>>>);
+
+impl WebAssetCookies {
+    /// Stores the cookie of a `Set-Cookie` header, as if `url` sent it, e.g. a session
+    /// cookie from logging in elsewhere
+    pub fn set(&self, url: &str, set_cookie: &str) {
+        if let Ok(url) = Url::parse(url) {
+            self.store(&url, [set_cookie]);
+        }
+    }
+
+    /// Forgets every cookie
+    pub fn clear(&self) {
+        self.0.write().unwrap().clear();
+    }
+
+    /// Stores the cookies of the `Set-Cookie` headers of a response from `url`
+    pub(crate) fn store_response(&self, url: &Url, headers: &HeaderMap) {
+        self.store(url, headers.get_all("set-cookie"));
+    }
+
+    fn store<'a>(&self, url: &Url, set_cookies: impl IntoIterator<Item = &'a str>) {
+        let now = unix_time();
+        let mut cookies = self.0.write().unwrap();
+        for cookie in set_cookies
+            .into_iter()
+            .filter_map(|header| Cookie::parse(url, header, now))
+        {
+            cookies.retain(|old| {
+                (&old.name, &old.domain, &old.path) != (&cookie.name, &cookie.domain, &cookie.path)
+            });
+            // Servers delete cookies by expiring them
+            if !cookie.is_expired(now) {
+                cookies.push(cookie);
+            }
+        }
+    }
+
+    /// The `Cookie` header for a request to `url`, if any cookies apply
+    pub(crate) fn header(&self, url: &Url) -> Option<String> {
+        let now = unix_time();
+        let cookies = self.0.read().unwrap();
+        let mut matching: Vec<_> = cookies
+            .iter()
+            .filter(|cookie| !cookie.is_expired(now) && cookie.matches(url))
+            .collect();
+        if matching.is_empty() {
+            return None;
+        }
+        // More specific paths first, as browsers do
+        matching.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+        let pairs: Vec<_> = matching
+            .iter()
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect();
+        Some(pairs.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn parse(url: &str, header: &str) -> Option<Cookie> {
+        Cookie::parse(&Url::parse(url).unwrap(), header, NOW)
+    }
+
+    fn matches(cookie: &Cookie, url: &str) -> bool {
+        cookie.matches(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn parses_set_cookie_headers() {
+        let cookie = parse(
+            "https://Assets.Example.com/packs/hero.png",
+            " session = abc ; Secure; Max-Age=60; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+        )
+        .unwrap();
+        assert_eq!((&*cookie.name, &*cookie.value), ("session", "abc"));
+        assert_eq!(cookie.domain, "assets.example.com");
+        assert!(cookie.host_only && cookie.secure);
+        assert_eq!(cookie.path, "/packs");
+        // Max-Age wins over Expires
+        assert_eq!(cookie.expires, Some(NOW + 60));
+
+        let cookie = parse(
+            "https://a.example.com/",
+            "id=1; Domain=.Example.com; Path=/x",
+        )
+        .unwrap();
+        assert_eq!(cookie.domain, "example.com");
+        assert!(!cookie.host_only);
+        assert_eq!(cookie.path, "/x");
+
+        let cookie = parse(
+            "https://a.example.com/hero.png",
+            "id=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+        );
+        assert_eq!(cookie.unwrap().expires, Some(1_445_412_480));
+        assert_eq!(
+            parse("https://a.example.com/", "id=1; Path=relative")
+                .unwrap()
+                .path,
+            "/"
+        );
+        assert!(parse("https://a.example.com/", "=1").is_none());
+        assert!(parse("https://a.example.com/", "no value").is_none());
+    }
+
+    #[test]
+    fn rejects_cookies_for_other_sites() {
+        assert!(parse("https://a.example.com/", "id=1; Domain=other.com").is_none());
+        assert!(parse("https://a.example.com/", "id=1; Domain=b.example.com").is_none());
+        assert!(parse("https://example.com/", "id=1; Domain=ample.com").is_none());
+        // Public suffixes
+        assert!(parse("https://a.example.com/", "id=1; Domain=com").is_none());
+        assert!(parse("https://game.co.uk/", "id=1; Domain=co.uk").is_none());
+        assert!(parse("https://me.github.io/", "id=1; Domain=github.io").is_none());
+        assert!(parse("https://10.0.0.1/", "id=1; Domain=0.0.1").is_none());
+
+        // Unless they're the host itself, which keeps the cookie to the host
+        let cookie = parse("http://localhost/", "id=1; Domain=localhost").unwrap();
+        assert!(cookie.host_only);
+        let cookie = parse("https://10.0.0.1/", "id=1; Domain=10.0.0.1").unwrap();
+        assert!(cookie.host_only);
+        assert!(parse("https://game.co.uk/", "id=1; Domain=game.co.uk").is_some());
+    }
+
+    #[test]
+    fn matches_urls() {
+        let host_only = parse("https://example.com/", "id=1").unwrap();
+        assert!(matches(&host_only, "https://EXAMPLE.com/a.png"));
+        assert!(!matches(&host_only, "https://a.example.com/a.png"));
+
+        let shared = parse(
+            "https://example.com/",
+            "id=1; Domain=example.com; Path=/packs",
+        )
+        .unwrap();
+        assert!(matches(&shared, "https://a.example.com/packs"));
+        assert!(matches(&shared, "https://a.example.com/packs/a.png"));
+        assert!(!matches(&shared, "https://a.example.com/packsmore"));
+        assert!(!matches(&shared, "https://a.example.com/"));
+        assert!(!matches(&shared, "https://badexample.com/packs"));
+
+        let secure = parse("https://example.com/", "id=1; Secure").unwrap();
+        assert!(matches(&secure, "https://example.com/"));
+        assert!(!matches(&secure, "http://example.com/"));
+    }
+
+    #[test]
+    fn forgets_expired_cookies() {
+        let cookies = WebAssetCookies::default();
+        let url = Url::parse("https://example.com/").unwrap();
+        cookies.set("https://example.com/", "a=1");
+        cookies.set("https://example.com/", "b=2; Max-Age=3600");
+        cookies.set(
+            "https://example.com/",
+            "c=3; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+        );
+        assert_eq!(cookies.header(&url).as_deref(), Some("a=1; b=2"));
+
+        // Servers delete cookies by expiring them
+        cookies.set("https://example.com/", "a=1; Max-Age=0");
+        assert_eq!(cookies.header(&url).as_deref(), Some("b=2"));
+        cookies.clear();
+        assert_eq!(cookies.header(&url), None);
+    }
+}
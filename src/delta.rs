@@ -0,0 +1,149 @@
+/// Applies a bsdiff `patch` to `old`, in the format of the
+/// [`bsdiff`](https://crates.io/crates/bsdiff) crate: without a header or compression,
+/// just each control triple of add, copy and seek lengths followed by its bytes.
+/// Servers can still compress patches with `Content-Encoding`.
+///
+/// Fails instead of growing the result past `max_len`.
+pub(crate) fn apply(old: &[u8], mut patch: &[u8], max_len: u64) -> Result<Vec<u8>, String> {
+    let mut new = Vec::new();
+    let mut old_pos: i64 = 0;
+    while !patch.is_empty() {
+        let control = take(&mut patch, 24)?;
+        let add = length(offtin(&control[0..8]))?;
+        let copy = length(offtin(&control[8..16]))?;
+        let seek = offtin(&control[16..24]);
+        if (new.len() as u64)
+            .saturating_add(add as u64)
+            .saturating_add(copy as u64)
+            > max_len
+        {
+            return Err(format!("patched file is larger than {max_len} bytes"));
+        }
+
+        // The diff block holds the differences to the old bytes at the same position
+        let diff = take(&mut patch, add)?;
+        let start = usize::try_from(old_pos)
+            .ok()
+            .filter(|&start| start.checked_add(add).is_some_and(|end| end <= old.len()))
+            .ok_or("patch reads outside the old file")?;
+        new.extend(
+            diff.iter()
+                .zip(&old[start..start + add])
+                .map(|(diff, old)| diff.wrapping_add(*old)),
+        );
+        new.extend_from_slice(take(&mut patch, copy)?);
+        old_pos = old_pos
+            .checked_add(add as i64)
+            .and_then(|pos| pos.checked_add(seek))
+            .ok_or("patch seeks outside the old file")?;
+    }
+    Ok(new)
+}
+
+/// Splits the next `len` bytes off of `patch`
+fn take<'a>(patch: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+    if patch.len() < len {
+        return Err("patch is truncated".to_string());
+    }
+    let (taken, rest) = patch.split_at(len);
+    *patch = rest;
+    Ok(taken)
+}
+
+/// Reads a bsdiff integer, little endian with the sign in the highest bit
+fn offtin(bytes: &[u8]) -> i64 {
+    let mut le = [0; 8];
+    le.copy_from_slice(bytes);
+    let value = u64::from_le_bytes(le);
+    let magnitude = (value & !(1 << 63)) as i64;
+    match value >> 63 {
+        0 => magnitude,
+        _ => -magnitude,
+    }
+}
+
+fn length(value: i64) -> Result<usize, String> {
+    usize::try_from(value).map_err(|_| "patch has a negative length".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Turns `hello world` into `hello WORLD, jello`: keeps `hello `, inserts
+    /// `WORLD, `, seeks back to the start and changes `hello` into `jello`
+    const OLD: &[u8] = b"hello world";
+    const PATCH: &[&[u8]] = &[
+        // add 6, copy 7, seek -6
+        &[6, 0, 0, 0, 0, 0, 0, 0],
+        &[7, 0, 0, 0, 0, 0, 0, 0],
+        &[6, 0, 0, 0, 0, 0, 0, 0x80],
+        &[0, 0, 0, 0, 0, 0],
+        b"WORLD, ",
+        // add 5, copy 0, seek 0
+        &[5, 0, 0, 0, 0, 0, 0, 0],
+        &[0, 0, 0, 0, 0, 0, 0, 0],
+        &[0, 0, 0, 0, 0, 0, 0, 0],
+        &[2, 0, 0, 0, 0],
+    ];
+
+    #[test]
+    fn applies_patch() {
+        let new = apply(OLD, &PATCH.concat(), 1024).unwrap();
+        assert_eq!(new, b"hello WORLD, jello");
+    }
+
+    #[test]
+    fn adds_diff_bytes_wrapping() {
+        let mut patch = vec![1, 0, 0, 0, 0, 0, 0, 0];
+        patch.extend([0; 16]);
+        patch.push(0x02);
+        assert_eq!(apply(&[0xff], &patch, 1024).unwrap(), [0x01]);
+    }
+
+    #[test]
+    fn applies_empty_patch() {
+        assert_eq!(apply(OLD, &[], 0).unwrap(), b"");
+    }
+
+    #[test]
+    fn rejects_results_over_max_len() {
+        assert!(apply(OLD, &PATCH.concat(), 17).is_err());
+        assert!(apply(OLD, &PATCH.concat(), 18).is_ok());
+    }
+
+    #[test]
+    fn rejects_truncated_patch() {
+        let patch = PATCH.concat();
+        assert_eq!(
+            apply(OLD, &patch[..patch.len() - 1], 1024),
+            Err("patch is truncated".to_string())
+        );
+        assert!(apply(OLD, &patch[..10], 1024).is_err());
+    }
+
+    #[test]
+    fn rejects_reads_outside_old_file() {
+        let mut patch = PATCH.concat();
+        // Seek back 7 instead of 6, before the start
+        patch[16] = 7;
+        assert_eq!(
+            apply(OLD, &patch, 1024),
+            Err("patch reads outside the old file".to_string())
+        );
+        // Add more than the old file has
+        let mut patch = vec![12, 0, 0, 0, 0, 0, 0, 0];
+        patch.extend([0; 16 + 12]);
+        assert!(apply(OLD, &patch, 1024).is_err());
+    }
+
+    #[test]
+    fn rejects_negative_lengths() {
+        let mut patch = PATCH.concat();
+        patch[7] = 0x80;
+        assert_eq!(
+            apply(OLD, &patch, 1024),
+            Err("patch has a negative length".to_string())
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics},
+    prelude::*,
+};
+
+use crate::WebAssetNetStats;
+
+/// Adds diagnostics of web asset downloads, shown by Bevy's `LogDiagnosticsPlugin`
+/// alongside the frame time. Add it after [`WebAssetPlugin`](crate::WebAssetPlugin).
+///
+/// ```no_run
+/// # use bevy::{diagnostic::LogDiagnosticsPlugin, prelude::*};
+/// # use bevy_web_asset::{WebAssetDiagnosticsPlugin, WebAssetPlugin};
+/// App::new()
+///     .add_plugin(WebAssetPlugin::default())
+///     .add_plugin(WebAssetDiagnosticsPlugin)
+///     .add_plugin(LogDiagnosticsPlugin::default());
+/// ```
+#[derive(Default)]
+pub struct WebAssetDiagnosticsPlugin;
+
+impl Plugin for WebAssetDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        // In case Bevy's `DiagnosticsPlugin` isn't added
+        app.init_resource::<Diagnostics>()
+            .add_startup_system(Self::setup_system)
+            .add_system(Self::diagnostic_system);
+    }
+}
+
+impl WebAssetDiagnosticsPlugin {
+    /// Requests being sent or received
+    pub const ACTIVE_DOWNLOADS: DiagnosticId =
+        DiagnosticId::from_u128(78678962313198733994635466968671382459);
+    /// Requests waiting for a free request slot, see
+    /// [`WebAssetPlugin::max_concurrent_requests`](crate::WebAssetPlugin::max_concurrent_requests)
+    pub const QUEUED_DOWNLOADS: DiagnosticId =
+        DiagnosticId::from_u128(237745720877759561394596481197419421244);
+    /// Bytes received per second, over all requests
+    pub const BYTES_PER_SECOND: DiagnosticId =
+        DiagnosticId::from_u128(174462262235226111527748759430345587516);
+    /// Remote assets that failed to load since the app started
+    pub const FAILURES: DiagnosticId =
+        DiagnosticId::from_u128(66930850487822656980827294778285207805);
+
+    fn setup_system(mut diagnostics: ResMut<Diagnostics>) {
+        diagnostics.add(Diagnostic::new(
+            Self::ACTIVE_DOWNLOADS,
+            "web_asset_active_downloads",
+            20,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::QUEUED_DOWNLOADS,
+            "web_asset_queued_downloads",
+            20,
+        ));
+        diagnostics.add(
+            Diagnostic::new(Self::BYTES_PER_SECOND, "web_asset_bytes_per_second", 20)
+                .with_suffix("B/s"),
+        );
+        diagnostics.add(
+            Diagnostic::new(Self::FAILURES, "web_asset_failures", 1).with_smoothing_factor(0.0),
+        );
+    }
+
+    fn diagnostic_system(
+        mut diagnostics: ResMut<Diagnostics>,
+        stats: Option<Res<WebAssetNetStats>>,
+        time: Res<Time>,
+        mut last_bytes: Local<u64>,
+    ) {
+        let Some(stats) = stats else {
+            return;
+        };
+        diagnostics.add_measurement(Self::ACTIVE_DOWNLOADS, || stats.active_transfers() as f64);
+        diagnostics.add_measurement(Self::QUEUED_DOWNLOADS, || stats.queued_transfers() as f64);
+        diagnostics.add_measurement(Self::FAILURES, || stats.failures() as f64);
+
+        let bytes = stats.bytes_downloaded();
+        let received = bytes - std::mem::replace(&mut *last_bytes, bytes);
+        let delta_seconds = time.raw_delta_seconds_f64();
+        if delta_seconds > 0.0 {
+            diagnostics.add_measurement(Self::BYTES_PER_SECOND, || received as f64 / delta_seconds);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use async_std::fs;
+use bevy::log::warn;
+use futures_lite::StreamExt;
+use std::{
+    env, io,
+    path::{Path, PathBuf},
+};
+
+use crate::CacheConfig;
+
+/// Name of the file the cache index is kept in
+const INDEX: &str = "index.json";
+
+/// Cache entries kept in a directory, each as a body file named by its key and a
+/// `.json` file with its metadata next to it
+pub(crate) struct Store {
+    dir: PathBuf,
+}
+
+impl Store {
+    pub(crate) fn new(config: &CacheConfig) -> Self {
+        let dir = config.directory.clone().unwrap_or_else(|| {
+            let app = env::current_exe()
+                .ok()
+                .and_then(|exe| Some(PathBuf::from(exe.file_stem()?)))
+                .unwrap_or_default();
+            platform_cache_dir()
+                .unwrap_or_else(env::temp_dir)
+                .join(app)
+                .join(&config.name)
+        });
+        // Games installed to read-only locations still get a cache
+        if let Err(err) = check_writable(&dir) {
+            let fallback = env::temp_dir().join(&config.name);
+            warn!(
+                "Can't write to the cache directory {}, using {} instead: {err}",
+                dir.display(),
+                fallback.display()
+            );
+            return Self { dir: fallback };
+        }
+        Self { dir }
+    }
+
+    /// The metadata and body stored under `key`
+    pub(crate) async fn read(&self, key: &str) -> Option<(String, Vec<u8>)> {
+        let meta = fs::read_to_string(self.dir.join(format!("{key}.json")))
+            .await
+            .ok()?;
+        let body = fs::read(self.dir.join(key)).await.ok()?;
+        Some((meta, body))
+    }
+
+    pub(crate) async fn write(&self, key: &str, meta: &str, body: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir).await?;
+        self.replace(key, body).await?;
+        self.replace(&format!("{key}.json"), meta.as_bytes()).await
+    }
+
+    /// Writes `name` next to the file and renames it, so a crash never leaves half a file
+    async fn replace(&self, name: &str, contents: &[u8]) -> io::Result<()> {
+        let partial = self.dir.join(format!("{name}.partial"));
+        fs::write(&partial, contents).await?;
+        fs::rename(&partial, self.dir.join(name)).await
+    }
+
+    pub(crate) async fn remove(&self, key: &str) -> io::Result<()> {
+        for path in [self.dir.join(format!("{key}.json")), self.dir.join(key)] {
+            match fs::remove_file(&path).await {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes every entry, and the index
+    pub(crate) async fn clear(&self) -> io::Result<()> {
+        match fs::remove_dir_all(&self.dir).await {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) async fn read_index(&self) -> Option<String> {
+        fs::read_to_string(self.dir.join(INDEX)).await.ok()
+    }
+
+    pub(crate) async fn write_index(&self, index: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.dir).await?;
+        self.replace(INDEX, index.as_bytes()).await
+    }
+
+    /// The metadata of every entry, for rebuilding a missing index
+    pub(crate) async fn metas(&self) -> Vec<String> {
+        let Ok(mut entries) = fs::read_dir(&self.dir).await else {
+            return Vec::new();
+        };
+        let mut metas = Vec::new();
+        while let Some(Ok(entry)) = entries.next().await {
+            let path: PathBuf = entry.path().into();
+            if !is_meta(&path) {
+                continue;
+            }
+            if let Ok(meta) = fs::read_to_string(&path).await {
+                metas.push(meta);
+            }
+        }
+        metas
+    }
+}
+
+/// Creates `dir` if needed, and checks that files can be written to it
+fn check_writable(dir: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let probe = dir.join(".write_check");
+    std::fs::write(&probe, [])?;
+    std::fs::remove_file(probe)
+}
+
+/// Where the platform keeps caches of applications, e.g. `~/.cache` on Linux
+fn platform_cache_dir() -> Option<PathBuf> {
+    let from_env = |name| {
+        env::var_os(name)
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+    };
+    if cfg!(target_os = "windows") {
+        from_env("LOCALAPPDATA")
+    } else if cfg!(target_os = "macos") {
+        Some(from_env("HOME")?.join("Library/Caches"))
+    } else {
+        from_env("XDG_CACHE_HOME").or_else(|| Some(from_env("HOME")?.join(".cache")))
+    }
+}
+
+fn is_meta(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "json")
+        && path.file_name().is_some_and(|name| name != INDEX)
+}
@@ -0,0 +1,202 @@
+use async_std::net::{TcpStream, ToSocketAddrs};
+use bevy::utils::HashMap;
+use std::{
+    io,
+    net::{IpAddr, SocketAddr},
+};
+use surf::{http, Url};
+
+/// Which IP version the default native client connects with first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IpPreference {
+    /// In the order the system resolver returns the addresses
+    #[default]
+    System,
+    /// IPv4 addresses first, falling back to IPv6 ones
+    PreferIpv4,
+    /// IPv6 addresses first, falling back to IPv4 ones
+    PreferIpv6,
+    /// IPv4 addresses only
+    Ipv4Only,
+    /// IPv6 addresses only
+    Ipv6Only,
+}
+
+/// How the default native client finds the addresses of hosts, set with
+/// [`WebAssetPlugin::dns`](crate::WebAssetPlugin::dns), e.g. for split-horizon DNS
+/// setups and test servers on the local network. Native only.
+///
+/// Requests to hosts this changes anything for connect by themselves, with a new
+/// connection for every request, like proxied ones. https connections are still
+/// verified against the host name.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{DnsConfig, IpPreference, WebAssetPlugin};
+/// let mut dns = DnsConfig {
+///     ip_preference: IpPreference::PreferIpv4,
+///     ..default()
+/// };
+/// dns.hosts
+///     .insert("assets.example.com".to_string(), vec!["192.168.1.20".parse().unwrap()]);
+/// App::new().add_plugin(WebAssetPlugin { dns, ..default() });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DnsConfig {
+    /// Addresses used for host names instead of asking the resolver, by host name
+    pub hosts: HashMap<String, Vec<IpAddr>>,
+    /// Which addresses are tried first, applying to every host if it's not
+    /// [`IpPreference::System`]
+    pub ip_preference: IpPreference,
+}
+
+impl DnsConfig {
+    /// Whether requests to `url` are resolved differently from the system resolver
+    pub(crate) fn applies_to(&self, url: &Url) -> bool {
+        self.ip_preference != IpPreference::System
+            || url
+                .host_str()
+                .is_some_and(|host| self.hosts.contains_key(&host.to_ascii_lowercase()))
+    }
+
+    /// The addresses of `host`, in the order they're tried
+    async fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        let mut addresses: Vec<_> = match self.hosts.get(&host.to_ascii_lowercase()) {
+            Some(ips) => ips.iter().map(|&ip| SocketAddr::new(ip, port)).collect(),
+            None => (host, port).to_socket_addrs().await?.collect(),
+        };
+        match self.ip_preference {
+            IpPreference::System => {}
+            // Stable, so the resolver's order stays within each version
+            IpPreference::PreferIpv4 => addresses.sort_by_key(SocketAddr::is_ipv6),
+            IpPreference::PreferIpv6 => addresses.sort_by_key(SocketAddr::is_ipv4),
+            IpPreference::Ipv4Only => addresses.retain(SocketAddr::is_ipv4),
+            IpPreference::Ipv6Only => addresses.retain(SocketAddr::is_ipv6),
+        }
+        Ok(addresses)
+    }
+
+    /// Connects to `host:port`, trying its addresses in order
+    pub(crate) async fn connect(&self, host: &str, port: u16) -> io::Result<TcpStream> {
+        let mut last_err = None;
+        for address in self.resolve(host, port).await? {
+            match TcpStream::connect(address).await {
+                Ok(stream) => return Ok(stream),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no usable address for {host}"),
+            )
+        }))
+    }
+}
+
+/// Sends `request` over a connection of its own, resolved with `dns`, using `tls` to
+/// talk to https servers
+pub(crate) async fn send(
+    dns: &DnsConfig,
+    request: http::Request,
+    tls: &async_tls::TlsConnector,
+) -> http::Result<http::Response> {
+    let url = request.url().clone();
+    let invalid = |message| io::Error::new(io::ErrorKind::InvalidInput, message);
+    let host = url.host_str().ok_or_else(|| invalid("url has no host"))?;
+    // Ipv6 literals come in brackets
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| invalid("url has no port"))?;
+    let stream = dns.connect(host, port).await?;
+    if url.scheme() == "https" {
+        let stream = tls.connect(host, stream).await?;
+        async_h1::connect(stream, request).await
+    } else {
+        async_h1::connect(stream, request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_lite::future;
+
+    use super::*;
+
+    fn config(ip_preference: IpPreference) -> DnsConfig {
+        let mut dns = DnsConfig {
+            ip_preference,
+            ..Default::default()
+        };
+        let ips = ["::1", "10.0.0.1", "::2", "10.0.0.2"];
+        dns.hosts.insert(
+            "assets.example.com".to_string(),
+            ips.iter().map(|ip| ip.parse().unwrap()).collect(),
+        );
+        dns
+    }
+
+    fn resolve(dns: &DnsConfig, host: &str) -> Vec<String> {
+        let addresses = future::block_on(dns.resolve(host, 443)).unwrap();
+        addresses
+            .iter()
+            .map(|address| address.ip().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn applies_to_configured_hosts() {
+        let url = |url| Url::parse(url).unwrap();
+        let dns = config(IpPreference::System);
+        assert!(dns.applies_to(&url("https://Assets.Example.com/hero.png")));
+        assert!(!dns.applies_to(&url("https://cdn.example.com/hero.png")));
+        assert!(!DnsConfig::default().applies_to(&url("https://assets.example.com")));
+        let dns = DnsConfig {
+            ip_preference: IpPreference::Ipv4Only,
+            ..Default::default()
+        };
+        assert!(dns.applies_to(&url("https://cdn.example.com/hero.png")));
+    }
+
+    #[test]
+    fn orders_addresses_by_preference() {
+        let ordered = |preference| resolve(&config(preference), "ASSETS.example.com");
+        assert_eq!(
+            ordered(IpPreference::System),
+            ["::1", "10.0.0.1", "::2", "10.0.0.2"]
+        );
+        assert_eq!(
+            ordered(IpPreference::PreferIpv4),
+            ["10.0.0.1", "10.0.0.2", "::1", "::2"]
+        );
+        assert_eq!(
+            ordered(IpPreference::PreferIpv6),
+            ["::1", "::2", "10.0.0.1", "10.0.0.2"]
+        );
+        assert_eq!(ordered(IpPreference::Ipv4Only), ["10.0.0.1", "10.0.0.2"]);
+        assert_eq!(ordered(IpPreference::Ipv6Only), ["::1", "::2"]);
+        // Hosts that aren't configured go to the resolver
+        assert_eq!(
+            resolve(&config(IpPreference::Ipv4Only), "127.0.0.1"),
+            ["127.0.0.1"]
+        );
+    }
+
+    #[test]
+    fn connects_to_configured_addresses() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut dns = DnsConfig::default();
+        dns.hosts
+            .insert("game.local".to_string(), vec!["127.0.0.1".parse().unwrap()]);
+        assert!(future::block_on(dns.connect("game.local", port)).is_ok());
+
+        let dns = DnsConfig {
+            ip_preference: IpPreference::Ipv6Only,
+            ..dns
+        };
+        let err = future::block_on(dns.connect("game.local", port)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,71 @@
+use async_std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{web_asset_io::is_remote, WebAssetError, WebAssetIo};
+
+impl WebAssetIo {
+    /// Downloads the remote asset at `path` to the file `destination`, writing the body
+    /// to disk as it arrives instead of keeping it in memory, and returns its size.
+    ///
+    /// For packs of hundreds of megabytes, which the game then reads from or memory-maps
+    /// itself, since asset loaders only get whole bodies in memory. Interrupted downloads
+    /// resume like any other, and `destination` only shows up once the download
+    /// finished. Bodies are requested uncompressed, and aren't cached or transformed.
+    /// Native only.
+    pub async fn download_to(
+        &self,
+        path: impl AsRef<Path>,
+        destination: impl AsRef<Path>,
+    ) -> Result<u64, WebAssetError> {
+        let path = path.as_ref();
+        let resolved = self.resolve_path(path);
+        let path = resolved.as_deref().unwrap_or(path);
+        let uri = path.to_string_lossy();
+        let invalid = |message: &str| WebAssetError::Transport {
+            url: uri.to_string(),
+            message: message.to_string(),
+        };
+        if !is_remote(path) || uri.starts_with("ipfs://") || self.scheme_handler(path).is_some() {
+            return Err(invalid(
+                "only http, s3, gs and azure paths can be downloaded",
+            ));
+        }
+        let destination = destination.as_ref();
+        let mut partial = destination.as_os_str().to_owned();
+        partial.push(".partial");
+        let partial = PathBuf::from(partial);
+
+        let (mut options, _) = self.asset_options(&uri, 0).await?;
+        // Saved as is, so there's nothing to decompress
+        let _ = options.headers.insert("Accept-Encoding", "identity");
+        options.download_to = Some(partial.clone());
+        let (uri, mut download) = match self.send_with_retries(&uri, &options).await {
+            Ok(sent) => sent,
+            Err(err) => {
+                let _ = fs::remove_file(&partial).await;
+                return Err(err);
+            }
+        };
+        let io_error = |err: std::io::Error| WebAssetError::Transport {
+            url: uri.clone(),
+            message: format!("can't write {}: {err}", destination.display()),
+        };
+        if download
+            .encoding
+            .as_deref()
+            .is_some_and(|encoding| !encoding.eq_ignore_ascii_case("identity"))
+        {
+            drop(download);
+            let _ = fs::remove_file(&partial).await;
+            return Err(WebAssetError::Decode {
+                url: uri.clone(),
+                message: "the server compressed a download to disk".to_string(),
+            });
+        }
+        download.finish_file().map_err(io_error)?;
+        let size = download.received();
+        drop(download);
+        fs::rename(&partial, destination).await.map_err(io_error)?;
+        Ok(size)
+    }
+}
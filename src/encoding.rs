@@ -0,0 +1,35 @@
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use std::io::Read;
+
+/// The `Accept-Encoding` sent unless one is configured explicitly
+pub(crate) const ACCEPT_ENCODING: &str = "gzip, deflate";
+
+/// Undoes the `Content-Encoding` of a response body.
+///
+/// `encoding` lists the codings in the order they were applied, so they are
+/// removed back to front. Decompression stops once the body grows past `max_len`.
+pub(crate) fn decode(encoding: &str, mut body: Vec<u8>, max_len: u64) -> Result<Vec<u8>, String> {
+    for coding in encoding.rsplit(',') {
+        body = match coding.trim().to_ascii_lowercase().as_str() {
+            "" | "identity" => body,
+            "gzip" | "x-gzip" => read_all(GzDecoder::new(&body[..]), max_len)?,
+            "deflate" => {
+                // Supposed to be zlib wrapped, but some servers send raw deflate
+                read_all(ZlibDecoder::new(&body[..]), max_len)
+                    .or_else(|_| read_all(DeflateDecoder::new(&body[..]), max_len))?
+            }
+            coding => return Err(format!("unsupported content encoding {coding}")),
+        };
+    }
+    Ok(body)
+}
+
+fn read_all(decoder: impl Read, max_len: u64) -> Result<Vec<u8>, String> {
+    let mut decoded = Vec::new();
+    // One byte more than allowed, so the caller can tell the body was too large
+    decoder
+        .take(max_len.saturating_add(1))
+        .read_to_end(&mut decoded)
+        .map_err(|err| format!("failed to decompress body: {err}"))?;
+    Ok(decoded)
+}
@@ -0,0 +1,280 @@
+use bevy::{asset::AssetIoError, prelude::*, utils::HashMap};
+use crossbeam_channel::Receiver;
+use std::{io, path::Path};
+use thiserror::Error;
+
+use crate::{HeaderMap, StatusClass};
+
+/// Maximum number of bytes of an error response body kept in [`WebAssetError::Status`]
+pub(crate) const BODY_SNIPPET_LEN: usize = 256;
+
+/// Why loading a web asset failed.
+///
+/// Requests that fail with `404 Not Found` or `410 Gone` are reported to bevy as
+/// [`AssetIoError::NotFound`] unless [`WebAssetPlugin::status_errors`](crate::WebAssetPlugin::status_errors)
+/// says otherwise, all other failures are wrapped in [`AssetIoError::Io`], and can be
+/// recovered with [`WebAssetError::from_asset_io_error`].
+#[derive(Debug, Clone, Error)]
+pub enum WebAssetError {
+    /// The request never produced a response (dns failure, connection reset, etc.)
+    #[error("request to {url} failed: {message}")]
+    Transport {
+        /// The requested url
+        url: String,
+        /// Description of the underlying error
+        message: String,
+    },
+    /// The server responded with a non-success status code
+    #[error("request to {url} failed with status {status}: {body}")]
+    Status {
+        /// The requested url
+        url: String,
+        /// The http status code
+        status: u16,
+        /// The beginning of the response body, useful for error pages
+        body: String,
+        /// The response headers, e.g. the new `Cache-Control` of a `304 Not Modified`
+        headers: HeaderMap,
+    },
+    /// One of the configured [`Timeouts`](crate::Timeouts) was exceeded
+    #[error("request to {url} timed out")]
+    Timeout {
+        /// The requested url
+        url: String,
+    },
+    /// The response body couldn't be decoded, e.g. an unsupported `Content-Encoding`
+    #[error("failed to decode response from {url}: {message}")]
+    Decode {
+        /// The requested url
+        url: String,
+        /// Description of the underlying error
+        message: String,
+    },
+    /// The response was larger than allowed by
+    /// [`WebAssetPlugin::max_download_size`](crate::WebAssetPlugin::max_download_size)
+    #[error("response from {url} is larger than the limit of {limit} bytes")]
+    TooLarge {
+        /// The requested url
+        url: String,
+        /// The maximum size in bytes
+        limit: u64,
+    },
+    /// The `Content-Type` of the response doesn't match the extension of the url,
+    /// e.g. an html error page sent instead of an image. Only checked if
+    /// [`WebAssetPlugin::validate_content_type`](crate::WebAssetPlugin::validate_content_type)
+    /// is set.
+    #[error("response from {url} has unexpected content type {content_type}")]
+    ContentType {
+        /// The requested url
+        url: String,
+        /// The `Content-Type` sent by the server
+        content_type: String,
+    },
+    /// The body doesn't match its published sha256, so it was corrupted or tampered with
+    #[error("integrity check of {url} failed, expected sha256 {expected} but got {actual}")]
+    Integrity {
+        /// The requested url
+        url: String,
+        /// The hex encoded sha256 that was published
+        expected: String,
+        /// The hex encoded sha256 of the body
+        actual: String,
+    },
+    /// The host isn't allowed by
+    /// [`WebAssetPlugin::allowed_hosts`](crate::WebAssetPlugin::allowed_hosts) or
+    /// [`WebAssetPlugin::blocked_hosts`](crate::WebAssetPlugin::blocked_hosts), so no
+    /// request was sent
+    #[error("requests to {url} are blocked")]
+    Blocked {
+        /// The requested url
+        url: String,
+    },
+    /// The asset isn't cached, and nothing is downloaded while
+    /// [offline](crate::WebAssetOffline)
+    #[error("{url} can't be downloaded while offline")]
+    Offline {
+        /// The requested url
+        url: String,
+    },
+    /// The download was cancelled, see [`WebAssetIo::cancel`](crate::WebAssetIo::cancel)
+    #[error("downloading {url} was cancelled")]
+    Cancelled {
+        /// The requested url
+        url: String,
+    },
+}
+
+impl WebAssetError {
+    /// The url the failed request was made to
+    pub fn url(&self) -> &str {
+        match self {
+            WebAssetError::Transport { url, .. }
+            | WebAssetError::Status { url, .. }
+            | WebAssetError::Timeout { url }
+            | WebAssetError::Decode { url, .. }
+            | WebAssetError::TooLarge { url, .. }
+            | WebAssetError::ContentType { url, .. }
+            | WebAssetError::Integrity { url, .. }
+            | WebAssetError::Blocked { url }
+            | WebAssetError::Offline { url }
+            | WebAssetError::Cancelled { url } => url,
+        }
+    }
+
+    /// The http status code, if the server responded
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            WebAssetError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// The closest matching [`io::ErrorKind`]
+    pub fn io_kind(&self) -> io::ErrorKind {
+        match self {
+            WebAssetError::Transport { .. } => io::ErrorKind::ConnectionAborted,
+            WebAssetError::Blocked { .. } => io::ErrorKind::PermissionDenied,
+            WebAssetError::Timeout { .. } => io::ErrorKind::TimedOut,
+            WebAssetError::Offline { .. } => io::ErrorKind::NotConnected,
+            WebAssetError::Cancelled { .. } => io::ErrorKind::Interrupted,
+            WebAssetError::Decode { .. }
+            | WebAssetError::TooLarge { .. }
+            | WebAssetError::ContentType { .. }
+            | WebAssetError::Integrity { .. } => io::ErrorKind::InvalidData,
+            WebAssetError::Status { status, .. } => match status {
+                401 | 403 => io::ErrorKind::PermissionDenied,
+                404 | 410 => io::ErrorKind::NotFound,
+                400 | 405 | 414 => io::ErrorKind::InvalidInput,
+                408 | 504 => io::ErrorKind::TimedOut,
+                _ => io::ErrorKind::Other,
+            },
+        }
+    }
+
+    /// Whether trying again later could reasonably succeed
+    pub fn is_transient(&self) -> bool {
+        match self {
+            WebAssetError::Transport { .. } | WebAssetError::Timeout { .. } => true,
+            WebAssetError::Status { status, .. } => {
+                // `501 Not Implemented` won't be implemented by the next attempt
+                matches!(status, 408 | 429 | 500..=599) && *status != 501
+            }
+            WebAssetError::Decode { .. }
+            | WebAssetError::TooLarge { .. }
+            | WebAssetError::ContentType { .. }
+            | WebAssetError::Integrity { .. }
+            | WebAssetError::Blocked { .. }
+            | WebAssetError::Offline { .. }
+            | WebAssetError::Cancelled { .. } => false,
+        }
+    }
+
+    /// Extracts the [`WebAssetError`] wrapped by an [`AssetIoError::Io`]
+    pub fn from_asset_io_error(error: &AssetIoError) -> Option<&WebAssetError> {
+        match error {
+            AssetIoError::Io(error) => error.get_ref()?.downcast_ref(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn into_asset_io_error(
+        self,
+        path: &Path,
+        status_errors: &StatusErrors,
+    ) -> AssetIoError {
+        let kind = self.status().and_then(|status| status_errors.kind(status));
+        match kind.unwrap_or_else(|| self.io_kind()) {
+            io::ErrorKind::NotFound => AssetIoError::NotFound(path.to_path_buf()),
+            kind => AssetIoError::Io(io::Error::new(kind, self)),
+        }
+    }
+}
+
+/// Which [`io::ErrorKind`] responses with an error status are reported to bevy with,
+/// overriding [`WebAssetError::io_kind`]. [`io::ErrorKind::NotFound`] becomes an
+/// [`AssetIoError::NotFound`], any other kind an [`AssetIoError::Io`] wrapping the
+/// [`WebAssetError`].
+///
+/// E.g. for a CDN answering `403 Forbidden` for files that don't exist, and to tell
+/// removed content apart from a typo in the path:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{StatusErrors, WebAssetPlugin};
+/// # use std::io;
+/// let mut status_errors = StatusErrors::default();
+/// status_errors.codes.insert(403, io::ErrorKind::NotFound);
+/// status_errors.codes.insert(410, io::ErrorKind::Other);
+/// App::new().add_plugin(WebAssetPlugin {
+///     status_errors,
+///     ..default()
+/// });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StatusErrors {
+    /// Kinds by status code, taking precedence over `classes`
+    pub codes: HashMap<u16, io::ErrorKind>,
+    /// Kinds by class of status codes, e.g. [`StatusClass::ServerError`] for every `5xx`
+    pub classes: HashMap<StatusClass, io::ErrorKind>,
+}
+
+impl StatusErrors {
+    /// The kind configured for `status`, if any
+    pub(crate) fn kind(&self, status: u16) -> Option<io::ErrorKind> {
+        self.codes.get(&status).copied().or_else(|| {
+            let class = StatusClass::of(status)?;
+            self.classes.get(&class).copied()
+        })
+    }
+}
+
+/// Sent when downloading a web asset failed, e.g. to show a "check your connection"
+/// message or a retry button.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetFailed;
+/// fn show_errors(mut failures: EventReader<WebAssetFailed>) {
+///     for failure in failures.iter() {
+///         if failure.error.is_transient() {
+///             warn!("Couldn't download {}, check your connection", failure.url);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WebAssetFailed {
+    /// The url that failed to download
+    pub url: String,
+    /// The http status code, if the server responded
+    pub status: Option<u16>,
+    /// Why the download failed
+    pub error: WebAssetError,
+}
+
+impl From<&WebAssetError> for WebAssetFailed {
+    fn from(error: &WebAssetError) -> Self {
+        Self {
+            url: error.url().to_string(),
+            status: error.status(),
+            error: error.clone(),
+        }
+    }
+}
+
+/// Receives the failures reported by [`WebAssetIo`](crate::WebAssetIo)
+#[derive(Resource)]
+pub(crate) struct FailureReceiver(pub(crate) Receiver<WebAssetFailed>);
+
+pub(crate) fn send_failure_events(
+    receiver: Res<FailureReceiver>,
+    mut events: EventWriter<WebAssetFailed>,
+) {
+    events.send_batch(receiver.0.try_iter());
+}
+
+/// Turns the start of an error response body into something printable
+pub(crate) fn body_snippet(bytes: &[u8]) -> String {
+    let bytes = &bytes[..bytes.len().min(BODY_SNIPPET_LEN)];
+    String::from_utf8_lossy(bytes).into_owned()
+}
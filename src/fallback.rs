@@ -0,0 +1,161 @@
+use bevy::utils::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Local assets loaded in place of remote ones that failed to download, e.g. a
+/// magenta placeholder texture.
+///
+/// Set on [`WebAssetPlugin::fallbacks`](crate::WebAssetPlugin::fallbacks). Url
+/// patterns are tried in the order they were added, then the extension of the
+/// asset path. Fallbacks are loaded from the default asset io, so they're
+/// relative to the assets folder.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{FallbackAssets, WebAssetPlugin};
+/// let mut app = App::new();
+/// app.add_plugin(WebAssetPlugin {
+///     fallbacks: FallbackAssets::default()
+///         .with_pattern("https://cdn.example.com/avatars/*", "avatar.png")
+///         .with_extension("png", "missing.png")
+///         .with_extension("ogg", "silence.ogg"),
+///     ..default()
+/// });
+/// app.add_plugins(DefaultPlugins);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FallbackAssets {
+    patterns: Vec<(String, PathBuf)>,
+    extensions: HashMap<String, PathBuf>,
+}
+
+impl FallbackAssets {
+    /// Loads `fallback` instead of any remote asset with the extension `extension`,
+    /// e.g. `"png"`
+    pub fn with_extension(
+        mut self,
+        extension: impl Into<String>,
+        fallback: impl Into<PathBuf>,
+    ) -> Self {
+        let extension = extension
+            .into()
+            .trim_start_matches('.')
+            .to_ascii_lowercase();
+        self.extensions.insert(extension, fallback.into());
+        self
+    }
+
+    /// Loads `fallback` instead of remote assets matching `pattern`, where `*`
+    /// matches anything, e.g. `"https://cdn.example.com/avatars/*"`
+    pub fn with_pattern(
+        mut self,
+        pattern: impl Into<String>,
+        fallback: impl Into<PathBuf>,
+    ) -> Self {
+        self.patterns.push((pattern.into(), fallback.into()));
+        self
+    }
+
+    /// Whether no fallbacks are configured
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty() && self.extensions.is_empty()
+    }
+
+    /// The fallback for the asset path `path`, loaded from `url`
+    pub(crate) fn get(&self, path: &str, url: &str) -> Option<&Path> {
+        let by_pattern = self
+            .patterns
+            .iter()
+            .find(|(pattern, _)| matches(pattern, path) || matches(pattern, url));
+        if let Some((_, fallback)) = by_pattern {
+            return Some(fallback);
+        }
+        self.extensions.get(&extension(path)?).map(PathBuf::as_path)
+    }
+}
+
+/// The lowercase extension of the file `path` points to, ignoring queries
+fn extension(path: &str) -> Option<String> {
+    let path = path.split(['?', '#']).next()?;
+    let (_, extension) = path.rsplit('/').next()?.rsplit_once('.')?;
+    Some(extension.to_ascii_lowercase())
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any run of characters
+pub(crate) fn matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<_> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No wildcards
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_wildcards() {
+        assert!(matches("https://cdn/a.png", "https://cdn/a.png"));
+        assert!(!matches("https://cdn/a.png", "https://cdn/a.png?v=2"));
+        assert!(matches(
+            "https://cdn/avatars/*",
+            "https://cdn/avatars/42.png"
+        ));
+        assert!(matches("https://cdn/avatars/*", "https://cdn/avatars/"));
+        assert!(!matches(
+            "https://cdn/avatars/*",
+            "https://cdn/items/42.png"
+        ));
+        assert!(matches("*.png", "https://cdn/a.png"));
+        assert!(matches(
+            "https://*/textures/*.png",
+            "https://cdn/textures/a/b.png"
+        ));
+        assert!(!matches(
+            "https://*/textures/*.png",
+            "https://cdn/models/a.png"
+        ));
+        assert!(matches("*", ""));
+        // The parts can't overlap
+        assert!(!matches("ab*b", "ab"));
+        assert!(matches("ab*b", "abb"));
+    }
+
+    #[test]
+    fn picks_fallbacks() {
+        let fallbacks = FallbackAssets::default()
+            .with_pattern("https://cdn.example.com/avatars/*", "avatar.png")
+            .with_pattern("*/boss.png", "boss.png")
+            .with_extension(".PNG", "missing.png");
+        let get = |path, url| fallbacks.get(path, url).and_then(Path::to_str);
+
+        let avatar = "https://cdn.example.com/avatars/42.png";
+        assert_eq!(get(avatar, avatar), Some("avatar.png"));
+        // Patterns match the url the path resolved to as well
+        assert_eq!(get("avatar://42.png", avatar), Some("avatar.png"));
+        // In the order they were added
+        assert_eq!(
+            get("https://cdn.example.com/avatars/boss.png", ""),
+            Some("avatar.png")
+        );
+        assert_eq!(get("https://cdn/boss.png", ""), Some("boss.png"));
+        assert_eq!(get("https://cdn/a.Png?v=2#x", ""), Some("missing.png"));
+        assert_eq!(get("https://cdn/a.ogg", ""), None);
+        assert_eq!(get("https://cdn.png/a", ""), None);
+
+        assert!(FallbackAssets::default().is_empty());
+        assert!(!fallbacks.is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+use bevy::utils::BoxedFuture;
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    http_client::{BufferedBody, HttpClient, HttpError, HttpRequest, HttpResponse, ResponseBody},
+    retry::sleep,
+    throttle::Throttle,
+    HeaderMap,
+};
+
+/// Simulated network trouble, for testing loading screens and error handling
+/// without a flaky connection at hand. Meant for development builds only, set with
+/// [`WebAssetPlugin::faults`](crate::WebAssetPlugin::faults).
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{FaultInjection, WebAssetPlugin};
+/// # use std::time::Duration;
+/// App::new().add_plugin(WebAssetPlugin {
+///     faults: Some(FaultInjection {
+///         latency: Duration::from_millis(300),
+///         jitter: Duration::from_millis(200),
+///         max_bandwidth: Some(256 * 1024),
+///         failure_rate: 0.1,
+///         ..default()
+///     }),
+///     ..default()
+/// });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjection {
+    /// Added before every response arrives, counting towards the connect timeout
+    pub latency: Duration,
+    /// Up to this much more latency, picked at random for every request
+    pub jitter: Duration,
+    /// Bytes per second received by all requests together
+    pub max_bandwidth: Option<u64>,
+    /// Fraction of requests, between `0.0` and `1.0`, that fail
+    pub failure_rate: f32,
+    /// The status failed requests get, e.g. `503`. `None`, the default, fails them
+    /// like a dropped connection, without a response.
+    pub failure_status: Option<u16>,
+    /// Urls to disturb, where `*` matches any run of characters. Empty, the default,
+    /// disturbs every request.
+    pub urls: Vec<String>,
+}
+
+impl FaultInjection {
+    fn applies_to(&self, url: &str) -> bool {
+        self.urls.is_empty()
+            || self
+                .urls
+                .iter()
+                .any(|pattern| crate::fallback::matches(pattern, url))
+    }
+}
+
+/// Sends requests with `inner`, disturbed by `faults`
+pub(crate) struct FaultyClient {
+    inner: Arc<dyn HttpClient>,
+    faults: FaultInjection,
+    throttle: Option<Arc<Throttle>>,
+}
+
+impl FaultyClient {
+    pub(crate) fn new(inner: Arc<dyn HttpClient>, faults: FaultInjection) -> Self {
+        Self {
+            throttle: faults
+                .max_bandwidth
+                .map(|limit| Arc::new(Throttle::new(limit))),
+            inner,
+            faults,
+        }
+    }
+}
+
+impl HttpClient for FaultyClient {
+    fn send(&self, request: HttpRequest) -> BoxedFuture<'_, Result<HttpResponse, HttpError>> {
+        Box::pin(async move {
+            let faults = &self.faults;
+            if !faults.applies_to(&request.url) {
+                return self.inner.send(request).await;
+            }
+            let delay = faults.latency + faults.jitter.mul_f32(fastrand::f32());
+            if !delay.is_zero() {
+                sleep(delay).await;
+            }
+            if fastrand::f32() < faults.failure_rate {
+                return match faults.failure_status {
+                    Some(status) => Ok(HttpResponse {
+                        status,
+                        headers: HeaderMap::new(),
+                        body: Box::new(BufferedBody::new(b"simulated failure".to_vec())),
+                    }),
+                    None => Err("simulated connection failure".into()),
+                };
+            }
+            let mut response = self.inner.send(request).await?;
+            if let Some(throttle) = &self.throttle {
+                response.body = Box::new(ThrottledBody {
+                    inner: response.body,
+                    throttle: throttle.clone(),
+                });
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// Delays every chunk of `inner` to fit the simulated bandwidth
+struct ThrottledBody {
+    inner: Box<dyn ResponseBody>,
+    throttle: Arc<Throttle>,
+}
+
+impl ResponseBody for ThrottledBody {
+    fn next_chunk(&mut self) -> BoxedFuture<'_, Result<Option<Vec<u8>>, HttpError>> {
+        Box::pin(async move {
+            let chunk = self.inner.next_chunk().await?;
+            if let Some(chunk) = &chunk {
+                let delay = self.throttle.delay(chunk.len());
+                if !delay.is_zero() {
+                    sleep(delay).await;
+                }
+            }
+            Ok(chunk)
+        })
+    }
+}
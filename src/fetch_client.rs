@@ -0,0 +1,198 @@
+use bevy::utils::BoxedFuture;
+use js_sys::Uint8Array;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{
+    global_scope::GlobalScope,
+    http_client::{HttpClient, HttpError, HttpRequest, HttpResponse, ResponseBody},
+    HeaderMap,
+};
+
+fn js_error(err: JsValue) -> HttpError {
+    format!("{err:?}").into()
+}
+
+/// The default [`HttpClient`] on wasm, using the browser's `fetch`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FetchClient {
+    options: FetchOptions,
+}
+
+impl FetchClient {
+    /// Sends requests with `options`, e.g. to include cookies with cross-origin requests
+    pub fn with_options(mut self, options: FetchOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+/// Options of the browser's `fetch` for every request, left to the browser's
+/// defaults when `None`
+///
+/// ```ignore
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{FetchOptions, WebAssetPlugin};
+/// App::new().add_plugin(WebAssetPlugin {
+///     // The asset host authorizes with session cookies
+///     fetch: FetchOptions {
+///         credentials: Some(web_sys::RequestCredentials::Include),
+///         ..default()
+///     },
+///     ..default()
+/// });
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FetchOptions {
+    /// Whether cookies and other credentials are sent, e.g. `Include` to send them
+    /// to other origins too
+    pub credentials: Option<web_sys::RequestCredentials>,
+    /// How the request uses the browser's HTTP cache, e.g. `NoStore` to bypass it
+    pub cache: Option<web_sys::RequestCache>,
+    /// Whether cross-origin requests are allowed, e.g. `Cors` or `SameOrigin`
+    pub mode: Option<web_sys::RequestMode>,
+    /// How much of the page's url is sent in the `Referer` header
+    pub referrer_policy: Option<web_sys::ReferrerPolicy>,
+}
+
+impl FetchOptions {
+    fn apply(&self, init: &mut web_sys::RequestInit) {
+        if let Some(credentials) = self.credentials {
+            init.credentials(credentials);
+        }
+        if let Some(cache) = self.cache {
+            init.cache(cache);
+        }
+        if let Some(mode) = self.mode {
+            init.mode(mode);
+        }
+        if let Some(referrer_policy) = self.referrer_policy {
+            init.referrer_policy(referrer_policy);
+        }
+    }
+}
+
+/// Aborts the fetch once dropped, so loads that are given up on, e.g. after a timeout,
+/// stop using bandwidth. Aborting a finished fetch does nothing.
+struct AbortOnDrop(Option<web_sys::AbortController>);
+
+impl AbortOnDrop {
+    fn new() -> Self {
+        Self(web_sys::AbortController::new().ok())
+    }
+
+    fn signal(&self) -> Option<web_sys::AbortSignal> {
+        self.0.as_ref().map(|controller| controller.signal())
+    }
+}
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        if let Some(controller) = &self.0 {
+            controller.abort();
+        }
+    }
+}
+
+/// The body of a fetch response, read chunk by chunk from its `ReadableStream`, so
+/// progress is reported as it arrives and the response is never buffered twice
+struct FetchBody {
+    kind: FetchBodyKind,
+    _abort: AbortOnDrop,
+}
+
+enum FetchBodyKind {
+    Stream(web_sys::ReadableStreamDefaultReader),
+    /// Browsers without streaming support, read in one go through `array_buffer()`
+    Buffered(Option<web_sys::Response>),
+}
+
+impl FetchBody {
+    fn new(response: web_sys::Response, abort: AbortOnDrop) -> Self {
+        let kind = match response.body() {
+            Some(stream) => FetchBodyKind::Stream(stream.get_reader().unchecked_into()),
+            None => FetchBodyKind::Buffered(Some(response)),
+        };
+        Self {
+            kind,
+            _abort: abort,
+        }
+    }
+}
+
+impl ResponseBody for FetchBody {
+    fn next_chunk(&mut self) -> BoxedFuture<'_, Result<Option<Vec<u8>>, HttpError>> {
+        Box::pin(async move {
+            match &mut self.kind {
+                FetchBodyKind::Stream(reader) => {
+                    let result = JsFuture::from(reader.read()).await.map_err(js_error)?;
+                    let done = js_sys::Reflect::get(&result, &"done".into()).map_err(js_error)?;
+                    if done.is_truthy() {
+                        return Ok(None);
+                    }
+                    let value = js_sys::Reflect::get(&result, &"value".into()).map_err(js_error)?;
+                    let chunk: Uint8Array = value
+                        .dyn_into()
+                        .map_err(|_| "response stream sent something other than bytes")?;
+                    Ok(Some(chunk.to_vec()))
+                }
+                FetchBodyKind::Buffered(response) => {
+                    let Some(response) = response.take() else {
+                        return Ok(None);
+                    };
+                    let data = JsFuture::from(response.array_buffer().map_err(js_error)?)
+                        .await
+                        .map_err(js_error)?;
+                    Ok(Some(Uint8Array::new(&data).to_vec()))
+                }
+            }
+        })
+    }
+}
+
+impl HttpClient for FetchClient {
+    fn send(&self, request: HttpRequest) -> BoxedFuture<'_, Result<HttpResponse, HttpError>> {
+        Box::pin(async move {
+            let mut init = web_sys::RequestInit::new();
+            init.method(request.method.as_str());
+            self.options.apply(&mut init);
+            let abort = AbortOnDrop::new();
+            init.signal(abort.signal().as_ref());
+            if let Some(body) = &request.body {
+                init.body(Some(&Uint8Array::from(body.as_slice())));
+            }
+
+            let js_request =
+                web_sys::Request::new_with_str_and_init(&request.url, &init).map_err(js_error)?;
+            for (name, value) in request.headers.iter() {
+                js_request.headers().append(name, value).map_err(js_error)?;
+            }
+
+            let global = GlobalScope::get()?;
+            let response: web_sys::Response =
+                JsFuture::from(global.fetch_with_request(&js_request))
+                    .await
+                    .map_err(js_error)?
+                    .dyn_into()
+                    .map_err(js_error)?;
+
+            let mut headers = HeaderMap::new();
+            if let Ok(Some(entries)) = js_sys::try_iter(&response.headers()) {
+                for entry in entries.flatten() {
+                    let entry: js_sys::Array = entry.unchecked_into();
+                    if let (Some(name), Some(value)) =
+                        (entry.get(0).as_string(), entry.get(1).as_string())
+                    {
+                        let _ = headers.append(name, value);
+                    }
+                }
+            }
+
+            Ok(HttpResponse {
+                status: response.status(),
+                headers,
+                body: Box::new(FetchBody::new(response, abort)),
+            })
+        })
+    }
+}
@@ -0,0 +1,223 @@
+use std::{env, fmt, sync::Arc};
+
+use crate::{s3::encode_key, AuthProvider};
+
+/// How `gs://bucket/object` paths are loaded from Google Cloud Storage
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{GcsConfig, ServiceAccountAuth, WebAssetPlugin};
+/// # use std::sync::Arc;
+/// let key = std::fs::read_to_string("service-account.json").unwrap();
+/// App::new().add_plugin(WebAssetPlugin {
+///     gcs: GcsConfig {
+///         auth: Some(Arc::new(ServiceAccountAuth::from_json(&key).unwrap())),
+///         ..default()
+///     },
+///     ..default()
+/// });
+/// ```
+#[derive(Clone, Default)]
+pub struct GcsConfig {
+    /// The storage endpoint, `STORAGE_EMULATOR_HOST` or
+    /// `https://storage.googleapis.com` if not set
+    pub endpoint: Option<String>,
+    /// Provides OAuth tokens for private buckets. If not set, the service account in
+    /// `GOOGLE_APPLICATION_CREDENTIALS` is used on native, and requests are
+    /// sent unauthenticated otherwise.
+    pub auth: Option<Arc<dyn AuthProvider>>,
+}
+
+impl fmt::Debug for GcsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GcsConfig")
+            .field("endpoint", &self.endpoint)
+            .field("auth", &self.auth.is_some())
+            .finish()
+    }
+}
+
+/// [`GcsConfig`] with the environment applied
+#[derive(Clone)]
+pub(crate) struct Gcs {
+    endpoint: String,
+    pub(crate) auth: Option<Arc<dyn AuthProvider>>,
+}
+
+impl GcsConfig {
+    pub(crate) fn resolve(&self) -> Gcs {
+        let endpoint = self
+            .endpoint
+            .clone()
+            .or_else(|| env::var("STORAGE_EMULATOR_HOST").ok())
+            .unwrap_or_else(|| "https://storage.googleapis.com".to_string());
+        #[cfg(not(target_arch = "wasm32"))]
+        let auth = self.auth.clone().or_else(|| {
+            let auth = ServiceAccountAuth::from_env()?
+                .map_err(|err| bevy::log::warn!("Ignoring GOOGLE_APPLICATION_CREDENTIALS: {err}"))
+                .ok()?;
+            Some(Arc::new(auth) as Arc<dyn AuthProvider>)
+        });
+        #[cfg(target_arch = "wasm32")]
+        let auth = self.auth.clone();
+        Gcs {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            auth,
+        }
+    }
+}
+
+impl Gcs {
+    /// The https url of a `gs://bucket/object` path
+    pub(crate) fn url(&self, path: &str) -> Option<String> {
+        let (bucket, object) = path.strip_prefix("gs://")?.split_once('/')?;
+        Some(format!("{}/{bucket}/{}", self.endpoint, encode_key(object)))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use service_account::ServiceAccountAuth;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod service_account {
+    use bevy::utils::BoxedFuture;
+    use ring::{
+        rand::SystemRandom,
+        signature::{RsaKeyPair, RSA_PKCS1_SHA256},
+    };
+    use std::{sync::Arc, time::Duration};
+
+    use crate::{
+        clock::unix_time,
+        http_client::{HttpClient, HttpRequest, Method},
+        AccessToken, AuthProvider, BearerTokenAuth, SurfClient,
+    };
+
+    const SCOPE: &str = "https://www.googleapis.com/auth/devstorage.read_only";
+
+    /// An [`AuthProvider`] for Google Cloud service accounts. Native only.
+    ///
+    /// Exchanges a JWT signed with the service account key for OAuth tokens,
+    /// refreshing them before they expire. Meant for [`GcsConfig::auth`](crate::GcsConfig::auth),
+    /// which is only asked about `gs://` paths, since tokens are sent to any host.
+    pub struct ServiceAccountAuth(BearerTokenAuth);
+
+    struct ServiceAccount {
+        client_email: String,
+        token_uri: String,
+        key: RsaKeyPair,
+    }
+
+    fn base64url(bytes: impl AsRef<[u8]>) -> String {
+        base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+    }
+
+    impl ServiceAccountAuth {
+        /// Reads a service account key file, as downloaded from the cloud console
+        pub fn from_json(json: &str) -> Result<Self, String> {
+            let json: serde_json::Value =
+                serde_json::from_str(json).map_err(|err| format!("invalid key file: {err}"))?;
+            let field = |name| {
+                json.get(name)
+                    .and_then(|value| value.as_str())
+                    .map(str::to_string)
+                    .ok_or_else(|| format!("key file has no {name}"))
+            };
+            let pem = field("private_key")?;
+            let der: String = pem
+                .lines()
+                .filter(|line| !line.starts_with("-----"))
+                .collect();
+            let der = base64::decode(der.trim()).map_err(|err| format!("invalid key: {err}"))?;
+            let account = Arc::new(ServiceAccount {
+                client_email: field("client_email")?,
+                token_uri: field("token_uri")
+                    .unwrap_or_else(|_| "https://oauth2.googleapis.com/token".to_string()),
+                key: RsaKeyPair::from_pkcs8(&der).map_err(|err| format!("invalid key: {err}"))?,
+            });
+            Ok(Self(BearerTokenAuth::new(["*"], move || {
+                let account = account.clone();
+                Box::pin(async move { account.fetch_token().await })
+            })))
+        }
+
+        /// Reads the key file at `GOOGLE_APPLICATION_CREDENTIALS`, if set
+        pub fn from_env() -> Option<Result<Self, String>> {
+            let path = std::env::var("GOOGLE_APPLICATION_CREDENTIALS").ok()?;
+            Some(
+                std::fs::read_to_string(&path)
+                    .map_err(|err| format!("failed to read {path}: {err}"))
+                    .and_then(|json| Self::from_json(&json)),
+            )
+        }
+    }
+
+    impl ServiceAccount {
+        fn assertion(&self) -> Result<String, String> {
+            let now = unix_time();
+            let header = base64url(r#"{"alg":"RS256","typ":"JWT"}"#);
+            let claims = serde_json::json!({
+                "iss": self.client_email,
+                "scope": SCOPE,
+                "aud": self.token_uri,
+                "iat": now,
+                "exp": now + 3600,
+            });
+            let message = format!("{header}.{}", base64url(claims.to_string()));
+            let mut signature = vec![0; self.key.public_modulus_len()];
+            self.key
+                .sign(
+                    &RSA_PKCS1_SHA256,
+                    &SystemRandom::new(),
+                    message.as_bytes(),
+                    &mut signature,
+                )
+                .map_err(|_| "failed to sign token request".to_string())?;
+            Ok(format!("{message}.{}", base64url(signature)))
+        }
+
+        async fn fetch_token(&self) -> Result<AccessToken, String> {
+            let body = format!(
+                "grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Ajwt-bearer&assertion={}",
+                self.assertion()?
+            );
+            let mut request = HttpRequest {
+                method: Method::Post,
+                body: Some(body.into_bytes()),
+                ..HttpRequest::get(&self.token_uri)
+            };
+            let _ = request
+                .headers
+                .insert("Content-Type", "application/x-www-form-urlencoded");
+            let response = SurfClient::default()
+                .send(request)
+                .await
+                .map_err(|err| err.to_string())?;
+            let status = response.status;
+            let body = response.bytes().await.map_err(|err| err.to_string())?;
+            if !(200..300).contains(&status) {
+                let body = String::from_utf8_lossy(&body);
+                return Err(format!("token request failed with status {status}: {body}"));
+            }
+            let json: serde_json::Value =
+                serde_json::from_slice(&body).map_err(|err| err.to_string())?;
+            Ok(AccessToken {
+                token: json["access_token"]
+                    .as_str()
+                    .ok_or("token response has no access_token")?
+                    .to_string(),
+                expires_in: json["expires_in"].as_u64().map(Duration::from_secs),
+            })
+        }
+    }
+
+    impl AuthProvider for ServiceAccountAuth {
+        fn authorization<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, Option<String>> {
+            self.0.authorization(url)
+        }
+
+        fn invalidate(&self, url: &str) {
+            self.0.invalidate(url)
+        }
+    }
+}
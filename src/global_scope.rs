@@ -0,0 +1,60 @@
+use wasm_bindgen::JsCast;
+use web_sys::{Window, WorkerGlobalScope};
+
+/// The global object of the thread the app runs on, a page's window or a Web Worker,
+/// e.g. when rendering to an offscreen canvas
+pub(crate) enum GlobalScope {
+    Window(Window),
+    Worker(WorkerGlobalScope),
+}
+
+impl GlobalScope {
+    pub(crate) fn get() -> Result<Self, String> {
+        if let Some(window) = web_sys::window() {
+            return Ok(Self::Window(window));
+        }
+        js_sys::global()
+            .dyn_into()
+            .map(Self::Worker)
+            .map_err(|_| "neither a window nor a worker".to_string())
+    }
+
+    pub(crate) fn fetch_with_request(&self, request: &web_sys::Request) -> js_sys::Promise {
+        match self {
+            Self::Window(window) => window.fetch_with_request(request),
+            Self::Worker(worker) => worker.fetch_with_request(request),
+        }
+    }
+
+    pub(crate) fn caches(&self) -> Result<web_sys::CacheStorage, String> {
+        match self {
+            Self::Window(window) => window.caches(),
+            Self::Worker(worker) => worker.caches(),
+        }
+        .map_err(|err| format!("{err:?}"))
+    }
+
+    /// Calls `callback` after `millis`
+    pub(crate) fn set_timeout(
+        &self,
+        callback: &js_sys::Function,
+        millis: i32,
+    ) -> Result<i32, wasm_bindgen::JsValue> {
+        match self {
+            Self::Window(window) => {
+                window.set_timeout_with_callback_and_timeout_and_arguments_0(callback, millis)
+            }
+            Self::Worker(worker) => {
+                worker.set_timeout_with_callback_and_timeout_and_arguments_0(callback, millis)
+            }
+        }
+    }
+
+    #[cfg(feature = "opfs")]
+    pub(crate) fn storage(&self) -> web_sys::StorageManager {
+        match self {
+            Self::Window(window) => window.navigator().storage(),
+            Self::Worker(worker) => worker.navigator().storage(),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use bevy::utils::HashMap;
+use bevy::{
+    asset::{Asset, LoadState},
+    prelude::*,
+};
+use std::{
+    fmt,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::WebAssetIo;
+
+/// A handle to a remote asset that cancels its download once it, its clones and every
+/// other `WebAssetHandle` of the same path were dropped before the asset finished
+/// loading, e.g. when a player skips a level before it was downloaded. Loaded with [`WebAssets::load`](crate::WebAssets::load).
+///
+/// Clone this rather than the [`Handle`] inside it, which doesn't keep the
+/// download going.
+pub struct WebAssetHandle<T: Asset>(Arc<Tracked<T>>);
+
+impl<T: Asset> Clone for WebAssetHandle<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+struct Tracked<T: Asset> {
+    handle: Handle<T>,
+    asset_server: AssetServer,
+    /// The path counted in [`LiveHandles`]
+    path: Option<PathBuf>,
+}
+
+/// How many [`WebAssetHandle`]s, not counting clones, are alive for each asset path,
+/// so dropping one doesn't cancel a download others still wait for
+#[derive(Default)]
+pub(crate) struct LiveHandles(Mutex<HashMap<PathBuf, usize>>);
+
+impl LiveHandles {
+    fn add(&self, path: &Path) {
+        *self
+            .0
+            .lock()
+            .unwrap()
+            .entry(path.to_path_buf())
+            .or_default() += 1;
+    }
+
+    /// Returns whether it was the last handle for `path`
+    fn remove(&self, path: &Path) -> bool {
+        let mut handles = self.0.lock().unwrap();
+        let Some(count) = handles.get_mut(path) else {
+            return false;
+        };
+        *count -= 1;
+        if *count > 0 {
+            return false;
+        }
+        handles.remove(path);
+        true
+    }
+}
+
+impl<T: Asset> WebAssetHandle<T> {
+    pub(crate) fn new(handle: Handle<T>, asset_server: AssetServer) -> Self {
+        let path = asset_server
+            .get_handle_path(&handle)
+            .map(|path| path.path().to_path_buf());
+        if let Some((asset_io, path)) = asset_server
+            .asset_io()
+            .downcast_ref::<WebAssetIo>()
+            .zip(path.as_ref())
+        {
+            asset_io.live_handles.add(path);
+        }
+        Self(Arc::new(Tracked {
+            handle,
+            asset_server,
+            path,
+        }))
+    }
+
+    /// The handle of the asset
+    pub fn handle(&self) -> &Handle<T> {
+        &self.0.handle
+    }
+}
+
+impl<T: Asset> fmt::Debug for WebAssetHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WebAssetHandle")
+            .field(&self.0.handle)
+            .finish()
+    }
+}
+
+impl<T: Asset> Deref for WebAssetHandle<T> {
+    type Target = Handle<T>;
+
+    fn deref(&self) -> &Handle<T> {
+        &self.0.handle
+    }
+}
+
+impl<T: Asset> Drop for Tracked<T> {
+    fn drop(&mut self) {
+        let asset_server = &self.asset_server;
+        let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+            return;
+        };
+        let Some(path) = &self.path else {
+            return;
+        };
+        if asset_io.live_handles.remove(path)
+            && asset_server.get_load_state(&self.handle) == LoadState::Loading
+        {
+            asset_io.cancel(path);
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use bevy::{
+        asset::{AssetLoader, LoadContext, LoadedAsset},
+        reflect::TypeUuid,
+        utils::BoxedFuture,
+    };
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::test_util::{MockResponse, MockServer};
+
+    #[derive(TypeUuid)]
+    #[uuid = "3f6c1a0e-7d38-4b7e-9a57-2f1e0d6c9b41"]
+    struct Text;
+
+    #[derive(Default)]
+    struct TextLoader;
+
+    impl AssetLoader for TextLoader {
+        fn load<'a>(
+            &'a self,
+            _bytes: &'a [u8],
+            load_context: &'a mut LoadContext,
+        ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+            Box::pin(async move {
+                load_context.set_default_asset(LoadedAsset::new(Text));
+                Ok(())
+            })
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["txt"]
+        }
+    }
+
+    #[test]
+    fn counts_handles_by_path() {
+        let handles = LiveHandles::default();
+        let path = Path::new("mock://level.txt");
+        handles.add(path);
+        handles.add(path);
+        assert!(!handles.remove(path));
+        assert!(handles.remove(path));
+        assert!(!handles.remove(path));
+    }
+
+    #[test]
+    fn keeps_loading_while_another_handle_is_alive() {
+        let server = MockServer::start();
+        server.mock(
+            "/level.txt",
+            MockResponse::ok("level").delay(Duration::from_millis(200)),
+        );
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(server.plugin())
+            .add_asset::<Text>()
+            .init_asset_loader::<TextLoader>();
+        let asset_server = app.world.resource::<AssetServer>().clone();
+        let load = || {
+            let handle = asset_server.load::<Text, _>("mock://level.txt");
+            WebAssetHandle::new(handle, asset_server.clone())
+        };
+        let (first, second) = (load(), load());
+        drop(first);
+
+        let started = Instant::now();
+        while asset_server.get_load_state(&*second) == LoadState::Loading {
+            assert!(started.elapsed() < Duration::from_secs(5), "still loading");
+            app.update();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(asset_server.get_load_state(&*second), LoadState::Loaded);
+        assert_eq!(server.requests_to("/level.txt").len(), 1);
+    }
+}
@@ -0,0 +1,255 @@
+use bevy::prelude::*;
+use std::sync::{Arc, RwLock};
+use thiserror::Error;
+
+use crate::HostPattern;
+
+/// Returned when trying to add a malformed header to a [`HeaderMap`]
+#[derive(Debug, Clone, Error)]
+pub enum InvalidHeader {
+    /// The name was empty or contained characters not allowed in header names
+    #[error("invalid header name {0:?}")]
+    Name(String),
+    /// The value contained line breaks or other control characters
+    #[error("invalid value for header {0:?}")]
+    Value(String),
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+fn validate(name: &str, value: &str) -> Result<String, InvalidHeader> {
+    if name.is_empty() || !name.chars().all(is_token_char) {
+        return Err(InvalidHeader::Name(name.to_string()));
+    }
+    if value.chars().any(|c| c.is_ascii_control() && c != '\t') {
+        return Err(InvalidHeader::Value(name.to_string()));
+    }
+    Ok(name.to_ascii_lowercase())
+}
+
+/// A list of http headers.
+///
+/// Names are case-insensitive, and a name may have several values, which are
+/// sent as repeated headers.
+///
+/// ```
+/// # use bevy_web_asset::HeaderMap;
+/// let mut headers = HeaderMap::new();
+/// headers.insert("Accept", "image/png")?;
+/// headers.append("accept", "image/webp")?;
+/// assert_eq!(headers.get_all("ACCEPT").count(), 2);
+/// assert!(headers.insert("Bad Name", "value").is_err());
+/// # Ok::<(), bevy_web_asset::InvalidHeader>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderMap {
+    /// Lowercase name and value, in insertion order
+    entries: Vec<(String, String)>,
+}
+
+impl HeaderMap {
+    /// An empty header map
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// Sets `name` to `value`, replacing all previous values
+    pub fn insert(
+        &mut self,
+        name: impl AsRef<str>,
+        value: impl Into<String>,
+    ) -> Result<&mut Self, InvalidHeader> {
+        let value = value.into();
+        let name = validate(name.as_ref(), &value)?;
+        self.entries.retain(|(existing, _)| *existing != name);
+        self.entries.push((name, value));
+        Ok(self)
+    }
+
+    /// Adds another value for `name`, keeping the previous ones
+    pub fn append(
+        &mut self,
+        name: impl AsRef<str>,
+        value: impl Into<String>,
+    ) -> Result<&mut Self, InvalidHeader> {
+        let value = value.into();
+        let name = validate(name.as_ref(), &value)?;
+        self.entries.push((name, value));
+        Ok(self)
+    }
+
+    /// Removes all values for `name`, returning whether there were any
+    pub fn remove(&mut self, name: impl AsRef<str>) -> bool {
+        let name = name.as_ref();
+        let len = self.entries.len();
+        self.entries
+            .retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+        self.entries.len() != len
+    }
+
+    /// The first value for `name`
+    pub fn get(&self, name: impl AsRef<str>) -> Option<&str> {
+        let name = name.as_ref();
+        self.entries
+            .iter()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// All values for `name`
+    pub fn get_all<'a>(&'a self, name: impl AsRef<str> + 'a) -> impl Iterator<Item = &'a str> {
+        self.entries
+            .iter()
+            .filter(move |(existing, _)| existing.eq_ignore_ascii_case(name.as_ref()))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether there is a value for `name`
+    pub fn contains(&self, name: impl AsRef<str>) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// All headers as lowercase name and value pairs
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Number of header values
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no headers at all
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Replaces the headers in `self` with the ones in `other` that have the same name,
+    /// and adds the rest
+    pub fn merge(&mut self, other: &HeaderMap) {
+        self.entries
+            .retain(|(name, _)| !other.entries.iter().any(|(other, _)| other == name));
+        self.entries.extend(other.entries.iter().cloned());
+    }
+}
+
+#[derive(Default)]
+struct HeaderSets {
+    global: HeaderMap,
+    hosts: Vec<(HostPattern, HeaderMap)>,
+}
+
+impl HeaderSets {
+    fn host_mut(&mut self, host: HostPattern) -> &mut HeaderMap {
+        let index = match self.hosts.iter().position(|(pattern, _)| *pattern == host) {
+            Some(index) => index,
+            None => {
+                self.hosts.push((host, default()));
+                self.hosts.len() - 1
+            }
+        };
+        &mut self.hosts[index].1
+    }
+}
+
+/// Extra http headers sent with web asset requests.
+///
+/// Headers can either be sent to every host, or only to hosts matching a
+/// [`HostPattern`], so api keys aren't leaked to third parties. Host specific
+/// headers replace global ones with the same name.
+///
+/// The plugin inserts this as a resource, and changes made to it at runtime
+/// apply to all subsequent requests.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{WebAssetHeaders, WebAssetPlugin};
+/// let headers = WebAssetHeaders::default();
+/// headers.insert("X-Game-Version", "1.2.0")?;
+/// headers.insert_for_host("cdn.mygame.com", "X-Api-Key", "secret")?;
+///
+/// App::new().add_plugin(WebAssetPlugin {
+///     headers,
+///     ..default()
+/// });
+/// # Ok::<(), bevy_web_asset::InvalidHeader>(())
+/// ```
+#[derive(Resource, Clone, Default)]
+pub struct WebAssetHeaders(Arc<RwLock<HeaderSets>>);
+
+impl WebAssetHeaders {
+    /// Sends `name: value` to every host, replacing any previous values
+    pub fn insert(
+        &self,
+        name: impl AsRef<str>,
+        value: impl Into<String>,
+    ) -> Result<(), InvalidHeader> {
+        self.0.write().unwrap().global.insert(name, value)?;
+        Ok(())
+    }
+
+    /// Sends `name: value` to every host, in addition to any previous values
+    pub fn append(
+        &self,
+        name: impl AsRef<str>,
+        value: impl Into<String>,
+    ) -> Result<(), InvalidHeader> {
+        self.0.write().unwrap().global.append(name, value)?;
+        Ok(())
+    }
+
+    /// Stops sending the global header `name`
+    pub fn remove(&self, name: impl AsRef<str>) {
+        self.0.write().unwrap().global.remove(name);
+    }
+
+    /// Sends `name: value` to hosts matching `host`, replacing any previous values
+    pub fn insert_for_host(
+        &self,
+        host: impl Into<HostPattern>,
+        name: impl AsRef<str>,
+        value: impl Into<String>,
+    ) -> Result<(), InvalidHeader> {
+        let mut sets = self.0.write().unwrap();
+        sets.host_mut(host.into()).insert(name, value)?;
+        Ok(())
+    }
+
+    /// Sends `name: value` to hosts matching `host`, in addition to any previous values
+    pub fn append_for_host(
+        &self,
+        host: impl Into<HostPattern>,
+        name: impl AsRef<str>,
+        value: impl Into<String>,
+    ) -> Result<(), InvalidHeader> {
+        let mut sets = self.0.write().unwrap();
+        sets.host_mut(host.into()).append(name, value)?;
+        Ok(())
+    }
+
+    /// Stops sending the header `name` to hosts matching `host`
+    pub fn remove_for_host(&self, host: impl Into<HostPattern>, name: impl AsRef<str>) {
+        let mut sets = self.0.write().unwrap();
+        sets.host_mut(host.into()).remove(name);
+    }
+
+    /// Replaces all headers sent to hosts matching `host`
+    pub fn set_for_host(&self, host: impl Into<HostPattern>, headers: HeaderMap) {
+        let mut sets = self.0.write().unwrap();
+        *sets.host_mut(host.into()) = headers;
+    }
+
+    /// The headers to send with a request to `url`
+    pub(crate) fn for_url(&self, url: &url::Url) -> HeaderMap {
+        let sets = self.0.read().unwrap();
+        let mut headers = sets.global.clone();
+        for (_, host_headers) in sets.hosts.iter().filter(|(host, _)| host.matches_url(url)) {
+            headers.merge(host_headers);
+        }
+        headers
+    }
+}
@@ -0,0 +1,129 @@
+use std::{fmt, str::FromStr};
+
+/// Matches the host part of urls.
+///
+/// - `"cdn.example.com"` only matches that exact host
+/// - `"*.example.com"` matches any subdomain of `example.com`, but not `example.com` itself
+/// - `"*"` matches every host
+///
+/// Matching is case-insensitive.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HostPattern(String);
+
+impl HostPattern {
+    /// Creates a pattern from a string such as `"*.example.com"`
+    pub fn new(pattern: impl AsRef<str>) -> Self {
+        Self(pattern.as_ref().trim().to_ascii_lowercase())
+    }
+
+    /// Whether `host` matches this pattern
+    pub fn matches(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        match self.0.strip_prefix('*') {
+            Some("") => true,
+            Some(suffix) if suffix.starts_with('.') => host.ends_with(suffix),
+            _ => host == self.0,
+        }
+    }
+
+    /// Whether the host of `url` matches this pattern
+    pub fn matches_url(&self, url: &url::Url) -> bool {
+        url.host_str().is_some_and(|host| self.matches(host))
+    }
+}
+
+/// Which hosts requests may be sent to
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HostFilter {
+    /// Every host is allowed if `None`
+    pub(crate) allowed: Option<Vec<HostPattern>>,
+    /// Takes precedence over `allowed`
+    pub(crate) blocked: Vec<HostPattern>,
+}
+
+impl HostFilter {
+    /// Whether requests to `url` may be sent
+    pub(crate) fn allows(&self, url: &str) -> bool {
+        if self.allowed.is_none() && self.blocked.is_empty() {
+            return true;
+        }
+        let Some(url) = url::Url::parse(url).ok().filter(|url| url.host().is_some()) else {
+            return false;
+        };
+        let allowed = self
+            .allowed
+            .as_ref()
+            .is_none_or(|allowed| allowed.iter().any(|host| host.matches_url(&url)));
+        allowed && !self.blocked.iter().any(|host| host.matches_url(&url))
+    }
+}
+
+impl FromStr for HostPattern {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(s))
+    }
+}
+
+impl From<&str> for HostPattern {
+    fn from(pattern: &str) -> Self {
+        Self::new(pattern)
+    }
+}
+
+impl fmt::Display for HostPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_hosts() {
+        let exact = HostPattern::new(" CDN.example.com ");
+        assert!(exact.matches("cdn.example.com"));
+        assert!(exact.matches("CDN.Example.COM"));
+        assert!(!exact.matches("a.cdn.example.com"));
+        assert!(!exact.matches("example.com"));
+
+        let subdomains = HostPattern::new("*.example.com");
+        assert!(subdomains.matches("cdn.example.com"));
+        assert!(subdomains.matches("a.b.example.com"));
+        assert!(!subdomains.matches("example.com"));
+        assert!(!subdomains.matches("badexample.com"));
+
+        assert!(HostPattern::new("*").matches("anything.at.all"));
+        // Only a leading `*.` is a wildcard
+        assert!(!HostPattern::new("*example.com").matches("badexample.com"));
+
+        let url = |url| url::Url::parse(url).unwrap();
+        assert!(subdomains.matches_url(&url("https://cdn.example.com:8443/a.png")));
+        assert!(!subdomains.matches_url(&url("file:///example.com/a.png")));
+        assert_eq!(subdomains.to_string(), "*.example.com");
+    }
+
+    #[test]
+    fn filters_hosts() {
+        let filter = |allowed: Option<&[&str]>, blocked: &[&str]| HostFilter {
+            allowed: allowed.map(|hosts| hosts.iter().map(|&host| host.into()).collect()),
+            blocked: blocked.iter().map(|&host| host.into()).collect(),
+        };
+        let url = "https://cdn.example.com/a.png";
+
+        assert!(HostFilter::default().allows(url));
+        assert!(HostFilter::default().allows("not a url"));
+        assert!(filter(Some(&["*.example.com"]), &[]).allows(url));
+        assert!(!filter(Some(&["other.com"]), &[]).allows(url));
+        assert!(!filter(None, &["cdn.example.com"]).allows(url));
+        // Blocking wins over allowing
+        assert!(!filter(Some(&["*.example.com"]), &["cdn.example.com"]).allows(url));
+        assert!(filter(Some(&["*.example.com"]), &["ads.example.com"]).allows(url));
+        // Urls without a host can't be checked
+        assert!(!filter(None, &["ads.example.com"]).allows("data:text/plain,hi"));
+        assert!(!filter(Some(&["*"]), &[]).allows("not a url"));
+    }
+}
@@ -0,0 +1,178 @@
+use bevy::utils::BoxedFuture;
+use std::{error::Error, fmt, sync::Arc};
+
+use crate::HeaderMap;
+
+/// Error type returned by [`HttpClient`] implementations
+pub type HttpError = Box<dyn Error + Send + Sync>;
+
+/// `Send` on native, and nothing on wasm, where browser types can't be sent between threads
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSend: Send {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send> MaybeSend for T {}
+
+/// `Send` on native, and nothing on wasm, where browser types can't be sent between threads
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSend {}
+#[cfg(target_arch = "wasm32")]
+impl<T> MaybeSend for T {}
+
+/// An http request method
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Method {
+    /// `GET`
+    Get,
+    /// `HEAD`
+    Head,
+    /// `POST`
+    Post,
+    /// `PUT`
+    Put,
+    /// `DELETE`
+    Delete,
+    /// Any other method, e.g. `PROPFIND`
+    Other(String),
+}
+
+impl Method {
+    /// The method as sent over the wire
+    pub fn as_str(&self) -> &str {
+        match self {
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Delete => "DELETE",
+            Method::Other(method) => method,
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A request to be sent by an [`HttpClient`]
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// The request method
+    pub method: Method,
+    /// The absolute url to send the request to
+    pub url: String,
+    /// Headers to send along with the request
+    pub headers: HeaderMap,
+    /// The request body, if any
+    pub body: Option<Vec<u8>>,
+}
+
+impl HttpRequest {
+    /// A `GET` request without any headers
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            method: Method::Get,
+            url: url.into(),
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+}
+
+/// The body of an [`HttpResponse`], read in chunks as it arrives
+pub trait ResponseBody: MaybeSend {
+    /// The next chunk of the body, or `None` once all of it has been read
+    fn next_chunk(&mut self) -> BoxedFuture<'_, Result<Option<Vec<u8>>, HttpError>>;
+}
+
+/// A [`ResponseBody`] that is already in memory
+pub struct BufferedBody(Option<Vec<u8>>);
+
+impl BufferedBody {
+    /// A body consisting of `bytes`
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self(Some(bytes))
+    }
+}
+
+impl ResponseBody for BufferedBody {
+    fn next_chunk(&mut self) -> BoxedFuture<'_, Result<Option<Vec<u8>>, HttpError>> {
+        Box::pin(async move { Ok(self.0.take()) })
+    }
+}
+
+/// The response returned by an [`HttpClient`]
+pub struct HttpResponse {
+    /// The http status code
+    pub status: u16,
+    /// The response headers
+    pub headers: HeaderMap,
+    /// The response body
+    pub body: Box<dyn ResponseBody>,
+}
+
+impl HttpResponse {
+    /// Whether the status code is in the `2xx` range
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// The value of the `Content-Length` header
+    pub fn content_length(&self) -> Option<u64> {
+        self.headers.get("content-length")?.trim().parse().ok()
+    }
+
+    /// Reads the whole body into memory
+    pub async fn bytes(mut self) -> Result<Vec<u8>, HttpError> {
+        let mut bytes = Vec::new();
+        while let Some(chunk) = self.body.next_chunk().await? {
+            bytes.extend(chunk);
+        }
+        Ok(bytes)
+    }
+
+    /// Reads the whole body into memory, failing once it's longer than `max_size`
+    pub(crate) async fn bytes_up_to(mut self, max_size: usize) -> Result<Vec<u8>, HttpError> {
+        let mut bytes = Vec::new();
+        while let Some(chunk) = self.body.next_chunk().await? {
+            bytes.extend(chunk);
+            if bytes.len() > max_size {
+                return Err(format!("response is larger than {max_size} bytes").into());
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+/// Sends the http requests made by [`WebAssetIo`](crate::WebAssetIo).
+///
+/// The default is [`SurfClient`](crate::SurfClient) on native and
+/// [`FetchClient`](crate::FetchClient) on wasm. Implement this to route
+/// requests through your own http stack, e.g. for proxies or testing.
+/// Retries, timeouts and headers configured on the plugin are handled by
+/// [`WebAssetIo`](crate::WebAssetIo), so clients only need to send the request.
+///
+/// The returned future should resolve as soon as the response headers are
+/// available; the body is read through [`HttpResponse::body`].
+///
+/// On native, bodies should be returned as sent by the server: `gzip` and
+/// `deflate` content encodings are decompressed by [`WebAssetIo`](crate::WebAssetIo).
+///
+/// [`SurfClient`](crate::SurfClient) speaks HTTP/1.1, reusing kept-alive
+/// connections, while browsers negotiate HTTP/2 and HTTP/3 for
+/// [`FetchClient`](crate::FetchClient) by themselves. To multiplex many small
+/// assets over a few HTTP/2 connections on native, implement this on top of an
+/// HTTP/2 capable client, e.g. `hyper`'s, and raise
+/// [`max_concurrent_requests`](crate::WebAssetPlugin::max_concurrent_requests) if
+/// it's set, since requests no longer need a connection each.
+pub trait HttpClient: Send + Sync + 'static {
+    /// Sends `request`, returning the response
+    fn send(&self, request: HttpRequest) -> BoxedFuture<'_, Result<HttpResponse, HttpError>>;
+}
+
+impl<T: HttpClient + ?Sized> HttpClient for Arc<T> {
+    fn send(&self, request: HttpRequest) -> BoxedFuture<'_, Result<HttpResponse, HttpError>> {
+        (**self).send(request)
+    }
+}
@@ -0,0 +1,168 @@
+use async_channel::{Receiver, Sender};
+use bevy::utils::HashMap;
+use std::sync::Mutex;
+
+use crate::WebAssetError;
+
+type FetchResult = Result<Vec<u8>, WebAssetError>;
+
+/// Keeps track of urls currently being downloaded, so concurrent loads of the
+/// same url share a single request.
+#[derive(Default)]
+pub(crate) struct InFlight {
+    requests: Mutex<HashMap<String, Request>>,
+}
+
+struct Request {
+    waiters: Vec<Sender<FetchResult>>,
+    /// Closed to cancel the request
+    cancel: Sender<()>,
+}
+
+pub(crate) enum Join<'a> {
+    /// Nobody is fetching the url yet; the caller should fetch it and
+    /// hand the result to [`Leader::finish`]
+    Lead(Leader<'a>),
+    /// Someone else is already fetching the url.
+    ///
+    /// If the receiver is closed without a result, the other request was
+    /// cancelled, and the caller should try joining again.
+    Wait(Receiver<FetchResult>),
+}
+
+impl InFlight {
+    pub(crate) fn join<'a>(&'a self, url: &'a str) -> Join<'a> {
+        let mut requests = self.requests.lock().unwrap();
+        match requests.get_mut(url) {
+            Some(request) => {
+                let (sender, receiver) = async_channel::bounded(1);
+                request.waiters.push(sender);
+                Join::Wait(receiver)
+            }
+            None => {
+                let (cancel, cancelled) = async_channel::bounded(1);
+                let waiters = Vec::new();
+                requests.insert(url.to_string(), Request { waiters, cancel });
+                Join::Lead(Leader {
+                    in_flight: self,
+                    url,
+                    cancelled,
+                    finished: false,
+                })
+            }
+        }
+    }
+
+    /// Cancels the request for `url`, if there is one, failing everyone waiting for it
+    pub(crate) fn cancel(&self, url: &str) -> bool {
+        match self.requests.lock().unwrap().get(url) {
+            Some(request) => request.cancel.close(),
+            None => false,
+        }
+    }
+
+    fn remove(&self, url: &str) -> Vec<Sender<FetchResult>> {
+        let request = self.requests.lock().unwrap().remove(url);
+        request.map(|request| request.waiters).unwrap_or_default()
+    }
+}
+
+/// The caller responsible for actually fetching an url.
+///
+/// Dropping it without calling [`Leader::finish`] lets waiters take over.
+pub(crate) struct Leader<'a> {
+    in_flight: &'a InFlight,
+    url: &'a str,
+    cancelled: Receiver<()>,
+    /// Whether the result was shared, so the request is gone already
+    finished: bool,
+}
+
+impl Leader<'_> {
+    /// Finishes once the request is cancelled
+    pub(crate) async fn cancelled(&self) {
+        // Nothing is ever sent, the channel is only closed
+        let _ = self.cancelled.recv().await;
+    }
+
+    /// Shares the result with everyone that joined in the meantime
+    pub(crate) fn finish(mut self, result: &FetchResult) {
+        for sender in self.in_flight.remove(self.url) {
+            let _ = sender.try_send(result.clone());
+        }
+        self.finished = true;
+    }
+}
+
+impl Drop for Leader<'_> {
+    fn drop(&mut self) {
+        // Don't remove a newer request for the same url
+        if !self.finished {
+            self.in_flight.remove(self.url);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lead<'a>(in_flight: &'a InFlight, url: &'a str) -> Leader<'a> {
+        match in_flight.join(url) {
+            Join::Lead(leader) => leader,
+            Join::Wait(_) => panic!("{url} is already being fetched"),
+        }
+    }
+
+    fn wait(in_flight: &InFlight, url: &str) -> Receiver<FetchResult> {
+        match in_flight.join(url) {
+            Join::Wait(receiver) => receiver,
+            Join::Lead(_) => panic!("{url} isn't being fetched"),
+        }
+    }
+
+    #[test]
+    fn shares_result_with_waiters() {
+        let in_flight = InFlight::default();
+        let leader = lead(&in_flight, "a");
+        let waiters = [wait(&in_flight, "a"), wait(&in_flight, "a")];
+        let other = lead(&in_flight, "b");
+        leader.finish(&Ok(b"body".to_vec()));
+        for waiter in waiters {
+            assert_eq!(waiter.try_recv().unwrap().unwrap(), b"body");
+        }
+        // Finished, so the next join fetches it again
+        drop(lead(&in_flight, "a"));
+        drop(other);
+    }
+
+    #[test]
+    fn finishing_keeps_newer_request() {
+        let in_flight = InFlight::default();
+        let first = lead(&in_flight, "a");
+        first.finish(&Ok(Vec::new()));
+        let second = lead(&in_flight, "a");
+        let waiter = wait(&in_flight, "a");
+        second.finish(&Ok(b"second".to_vec()));
+        assert_eq!(waiter.try_recv().unwrap().unwrap(), b"second");
+    }
+
+    #[test]
+    fn dropped_leader_lets_waiters_take_over() {
+        let in_flight = InFlight::default();
+        let leader = lead(&in_flight, "a");
+        let waiter = wait(&in_flight, "a");
+        drop(leader);
+        assert!(waiter.is_closed());
+        drop(lead(&in_flight, "a"));
+    }
+
+    #[test]
+    fn cancels_request() {
+        let in_flight = InFlight::default();
+        assert!(!in_flight.cancel("a"));
+        let leader = lead(&in_flight, "a");
+        assert!(in_flight.cancel("a"));
+        futures_lite::future::block_on(leader.cancelled());
+    }
+}
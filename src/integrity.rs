@@ -0,0 +1,412 @@
+use bevy::{prelude::*, utils::HashMap};
+use crossbeam_channel::Receiver;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::{
+    s3::hex,
+    web_asset_io::{decode_body, RequestOptions},
+    WebAssetError, WebAssetIo,
+};
+
+/// Returned when an [`IntegrityManifest`] can't be built
+#[derive(Debug, Error)]
+pub enum IntegrityManifestError {
+    /// The manifest isn't a JSON object of strings
+    #[error("invalid integrity manifest: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A digest is neither hex nor `sha256-<base64>`
+    #[error("invalid sha256 digest for {url}: {digest:?}")]
+    Digest {
+        /// The url the digest was given for
+        url: String,
+        /// The invalid digest
+        digest: String,
+    },
+}
+
+/// The expected sha256 of remote assets, by url.
+///
+/// Set on [`WebAssetPlugin::integrity`](crate::WebAssetPlugin::integrity), every
+/// listed url is checked when downloaded. Mismatches fail with
+/// [`WebAssetError::Integrity`] and send a [`WebAssetTampered`] event.
+///
+/// ```
+/// # use bevy_web_asset::IntegrityManifest;
+/// let manifest = IntegrityManifest::from_json(
+///     r#"{
+///         "https://mirror.example.com/level.glb": "sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=",
+///         "https://mirror.example.com/hero.png": "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+///     }"#,
+/// )?;
+/// assert_eq!(manifest.len(), 2);
+/// # Ok::<(), bevy_web_asset::IntegrityManifestError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityManifest {
+    /// Hex encoded digests
+    digests: HashMap<String, String>,
+}
+
+impl IntegrityManifest {
+    /// Parses a JSON object mapping urls to digests
+    pub fn from_json(json: &str) -> Result<Self, IntegrityManifestError> {
+        let entries: HashMap<String, String> = serde_json::from_str(json)?;
+        let mut manifest = Self::default();
+        for (url, digest) in entries {
+            manifest.insert(url, &digest)?;
+        }
+        Ok(manifest)
+    }
+
+    /// Expects `url` to have the sha256 `digest`, either hex encoded or as a
+    /// subresource integrity value, `sha256-<base64>`
+    pub fn insert(
+        &mut self,
+        url: impl Into<String>,
+        digest: &str,
+    ) -> Result<(), IntegrityManifestError> {
+        let url = url.into();
+        let Some(digest) = parse_digest(digest) else {
+            return Err(IntegrityManifestError::Digest {
+                url,
+                digest: digest.to_string(),
+            });
+        };
+        self.digests.insert(url, digest);
+        Ok(())
+    }
+
+    /// The number of urls listed
+    pub fn len(&self) -> usize {
+        self.digests.len()
+    }
+
+    /// Whether no urls are listed
+    pub fn is_empty(&self) -> bool {
+        self.digests.is_empty()
+    }
+}
+
+/// Sent when a download doesn't match its expected sha256, from an
+/// [`IntegrityManifest`] or a checksum sidecar
+#[derive(Debug, Clone)]
+pub struct WebAssetTampered {
+    /// The downloaded url
+    pub url: String,
+    /// The hex encoded sha256 that was expected
+    pub expected: String,
+    /// The hex encoded sha256 of the body
+    pub actual: String,
+}
+
+/// Receives the tampering detected by [`WebAssetIo`]
+#[derive(Resource)]
+pub(crate) struct TamperReceiver(pub(crate) Receiver<WebAssetTampered>);
+
+pub(crate) fn send_tamper_events(
+    receiver: Res<TamperReceiver>,
+    mut events: EventWriter<WebAssetTampered>,
+) {
+    events.send_batch(receiver.0.try_iter());
+}
+
+/// A hex encoded sha256, or `sha256-<base64>`, as hex
+pub(crate) fn parse_digest(digest: &str) -> Option<String> {
+    let digest = digest.trim();
+    if let Some(base64) = digest.strip_prefix("sha256-") {
+        let bytes = base64::decode(base64).ok()?;
+        return (bytes.len() == 32).then(|| hex(&bytes));
+    }
+    let valid = digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit());
+    valid.then(|| digest.to_ascii_lowercase())
+}
+
+/// Checksum sidecars are a digest and maybe a file name, anything larger is wrong
+const MAX_SIDECAR_SIZE: u64 = 4 * 1024;
+
+/// The url of a file published next to `path`, with `suffix` appended to its path
+/// before the query, e.g. `foo.glb.sha256?v=2` for `foo.glb?v=2`
+pub(crate) fn sidecar_url(path: &str, suffix: &str) -> String {
+    match url::Url::parse(path) {
+        Ok(mut url)
+            if !url.cannot_be_a_base() && (url.query().is_some() || url.fragment().is_some()) =>
+        {
+            let sidecar_path = format!("{}{suffix}", url.path());
+            url.set_path(&sidecar_path);
+            url.to_string()
+        }
+        _ => format!("{path}{suffix}"),
+    }
+}
+
+/// The digest in a checksum sidecar, either just the hex digest or the output
+/// of `sha256sum`
+fn parse_sidecar(bytes: &[u8]) -> Option<String> {
+    let digest = std::str::from_utf8(bytes).ok()?.split_whitespace().next()?;
+    parse_digest(digest).filter(|_| !digest.starts_with("sha256-"))
+}
+
+/// The hex encoded sha256 of `body`
+pub(crate) fn sha256(body: &[u8]) -> String {
+    hex(&Sha256::digest(body))
+}
+
+impl WebAssetIo {
+    /// Checks `body` of `asset`, downloaded from `path` at `url`, against its expected
+    /// sha256 from the integrity manifest, and then the checksum sidecar
+    pub(crate) async fn verify(
+        &self,
+        asset: &str,
+        path: &str,
+        url: &str,
+        body: &[u8],
+    ) -> Result<(), WebAssetError> {
+        let digests = &self.integrity.digests;
+        let listed = [asset, path, url].iter().find_map(|key| digests.get(*key));
+        // Content addressed paths are their own manifest
+        let addressed = crate::cas::digest(asset);
+        let mut result = match addressed.as_ref().or(listed) {
+            Some(expected) => check(path, expected, body),
+            None => Ok(()),
+        };
+        if result.is_ok() && self.checksum_sidecars {
+            result = self.verify_sidecar(path, body).await;
+        }
+        if let Err(WebAssetError::Integrity {
+            url,
+            expected,
+            actual,
+        }) = &result
+        {
+            warn!("{url} doesn't match its sha256, it may have been tampered with");
+            // The receiver only goes away with the app
+            let _ = self.tampered.send(WebAssetTampered {
+                url: url.clone(),
+                expected: expected.clone(),
+                actual: actual.clone(),
+            });
+        }
+        result
+    }
+
+    /// Checks `body`, downloaded from `path`, against the checksum published next to it
+    async fn verify_sidecar(&self, path: &str, body: &[u8]) -> Result<(), WebAssetError> {
+        let sidecar = sidecar_url(path, ".sha256");
+        let options = RequestOptions {
+            max_size: Some(MAX_SIDECAR_SIZE),
+            ..RequestOptions::default()
+        };
+        // Sent as is, sidecars aren't assets to be transformed
+        let (uri, download) = match self.send_with_retries(&sidecar, &options).await {
+            // Reported as missing, the asset itself was found
+            Err(err) if err.io_kind() == std::io::ErrorKind::NotFound => {
+                return Err(WebAssetError::Decode {
+                    url: path.to_string(),
+                    message: format!("no checksum published at {sidecar}"),
+                });
+            }
+            result => result?,
+        };
+        let bytes = decode_body(&uri, download, options.max_size)?;
+        let expected = parse_sidecar(&bytes).ok_or_else(|| WebAssetError::Decode {
+            url: sidecar.clone(),
+            message: "expected a hex encoded sha256 digest".to_string(),
+        })?;
+        check(path, &expected, body)
+    }
+}
+
+/// Checks that `body`, downloaded from `path`, has the hex encoded sha256 `expected`
+fn check(path: &str, expected: &str, body: &[u8]) -> Result<(), WebAssetError> {
+    let actual = sha256(body);
+    if actual != expected {
+        return Err(WebAssetError::Integrity {
+            url: path.to_string(),
+            expected: expected.to_string(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use bevy::asset::AssetIo;
+    use futures_lite::future;
+    use std::{path::Path, time::Duration};
+
+    use super::*;
+    use crate::{
+        test_util::{MockResponse, MockServer},
+        WebAssetPlugin,
+    };
+
+    #[test]
+    fn parses_digests() {
+        let hex = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(parse_digest(hex).as_deref(), Some(hex));
+        assert_eq!(
+            parse_digest(&format!(" {} ", hex.to_ascii_uppercase())).as_deref(),
+            Some(hex)
+        );
+        let sri = "sha256-ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=";
+        assert_eq!(parse_digest(sri).as_deref(), Some(hex));
+
+        assert_eq!(parse_digest(&hex[1..]), None);
+        assert_eq!(parse_digest(&format!("{}g", &hex[1..])), None);
+        // Not 32 bytes
+        assert_eq!(parse_digest("sha256-YWJj"), None);
+        assert_eq!(parse_digest("sha256-not base64"), None);
+        assert_eq!(
+            parse_digest("sha384-ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0="),
+            None
+        );
+    }
+
+    #[test]
+    fn builds_manifests() {
+        let manifest = IntegrityManifest::from_json(
+            r#"{"https://example.com/a.png": "sha256-ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0="}"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.len(), 1);
+        assert_eq!(
+            manifest.digests["https://example.com/a.png"],
+            sha256(b"abc")
+        );
+        assert!(matches!(
+            IntegrityManifest::from_json(r#"{"https://example.com/a.png": "abc"}"#),
+            Err(IntegrityManifestError::Digest { .. })
+        ));
+        assert!(matches!(
+            IntegrityManifest::from_json(r#"["abc"]"#),
+            Err(IntegrityManifestError::Json(_))
+        ));
+        assert!(IntegrityManifest::default().is_empty());
+    }
+
+    #[test]
+    fn verifies_downloads_against_manifest() {
+        let server = MockServer::start();
+        server.mock("/abc.txt", MockResponse::ok("abc"));
+        server.mock("/tampered.txt", MockResponse::ok("abd"));
+        server.mock("/unlisted.txt", MockResponse::ok("abd"));
+        let mut integrity = IntegrityManifest::default();
+        integrity
+            .insert(server.url("/abc.txt"), &sha256(b"abc"))
+            .unwrap();
+        integrity
+            .insert(server.url("/tampered.txt"), &sha256(b"abc"))
+            .unwrap();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(WebAssetPlugin {
+            integrity,
+            ..server.plugin()
+        });
+        let asset_server = app.world.resource::<AssetServer>();
+        let asset_io: &WebAssetIo = asset_server.asset_io().downcast_ref().unwrap();
+        let load = |path: &str| future::block_on(asset_io.load_path(Path::new(path)));
+
+        assert_eq!(load("mock://abc.txt").unwrap(), b"abc");
+        assert_eq!(load("mock://unlisted.txt").unwrap(), b"abd");
+        let err = load("mock://tampered.txt").unwrap_err();
+        assert!(matches!(
+            WebAssetError::from_asset_io_error(&err),
+            Some(WebAssetError::Integrity { .. })
+        ));
+
+        app.update();
+        let events = app.world.resource::<Events<WebAssetTampered>>();
+        let tampered: Vec<_> = events.get_reader().iter(events).cloned().collect();
+        assert_eq!(tampered.len(), 1);
+        assert_eq!(tampered[0].actual, sha256(b"abd"));
+        assert_eq!(tampered[0].expected, sha256(b"abc"));
+    }
+
+    #[test]
+    fn appends_sidecar_suffix_before_query() {
+        assert_eq!(
+            sidecar_url("https://example.com/foo.glb", ".sha256"),
+            "https://example.com/foo.glb.sha256"
+        );
+        assert_eq!(
+            sidecar_url("https://example.com/foo.glb?v=2#scene", ".sha256"),
+            "https://example.com/foo.glb.sha256?v=2#scene"
+        );
+        assert_eq!(
+            sidecar_url("mock://foo.glb", ".sha256"),
+            "mock://foo.glb.sha256"
+        );
+    }
+
+    #[test]
+    fn parses_sidecars() {
+        let digest = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(parse_sidecar(digest.as_bytes()).as_deref(), Some(digest));
+        let upper = format!("{}\n", digest.to_ascii_uppercase());
+        assert_eq!(parse_sidecar(upper.as_bytes()).as_deref(), Some(digest));
+        // The output of `sha256sum`
+        let sha256sum = format!("{digest}  foo.glb\n");
+        assert_eq!(parse_sidecar(sha256sum.as_bytes()).as_deref(), Some(digest));
+        let binary = format!("{digest} *foo.glb\n");
+        assert_eq!(parse_sidecar(binary.as_bytes()).as_deref(), Some(digest));
+
+        assert_eq!(parse_sidecar(b""), None);
+        assert_eq!(parse_sidecar(&digest.as_bytes()[1..]), None);
+        assert_eq!(parse_sidecar(&[0xff; 64]), None);
+        // Subresource integrity values belong in manifests
+        let sri = "sha256-ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=";
+        assert_eq!(parse_sidecar(sri.as_bytes()), None);
+    }
+
+    #[test]
+    fn verifies_downloads_against_sidecars() {
+        let server = MockServer::start();
+        server.mock("/abc.txt", MockResponse::ok("abc"));
+        server.mock(
+            "/abc.txt.sha256",
+            MockResponse::ok(format!("{}  abc.txt\n", sha256(b"abc"))),
+        );
+        server.mock("/tampered.txt", MockResponse::ok("abd"));
+        server.mock(
+            "/tampered.txt.sha256",
+            MockResponse::ok(format!("{}  tampered.txt\n", sha256(b"abc"))),
+        );
+        server.mock("/unpublished.txt", MockResponse::ok("abc"));
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(WebAssetPlugin {
+            checksum_sidecars: true,
+            watch_interval: Some(Duration::from_secs(3600)),
+            ..server.plugin()
+        });
+        let asset_server = app.world.resource::<AssetServer>();
+        let asset_io: &WebAssetIo = asset_server.asset_io().downcast_ref().unwrap();
+        let load = |path: &str| future::block_on(asset_io.load_path(Path::new(path)));
+        let error = |path: &str| {
+            let err = load(path).unwrap_err();
+            WebAssetError::from_asset_io_error(&err).cloned()
+        };
+        let version = |path: &str| {
+            asset_io
+                .watcher
+                .as_ref()
+                .unwrap()
+                .version(&server.url(path))
+        };
+
+        assert_eq!(load("mock://abc.txt").unwrap(), b"abc");
+        assert!(version("/abc.txt").is_some());
+
+        let err = error("mock://tampered.txt");
+        assert!(
+            matches!(err, Some(WebAssetError::Integrity { .. })),
+            "{err:?}"
+        );
+        // Isn't taken as the current version by the watcher
+        assert!(version("/tampered.txt").is_none());
+
+        let err = error("mock://unpublished.txt");
+        assert!(matches!(err, Some(WebAssetError::Decode { .. })), "{err:?}");
+    }
+}
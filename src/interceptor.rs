@@ -0,0 +1,35 @@
+use crate::HttpRequest;
+
+/// Changes requests right before they're sent, e.g. to add a signature computed
+/// from the url, switch the method or append query parameters.
+///
+/// Registered in
+/// [`WebAssetPlugin::request_interceptors`](crate::WebAssetPlugin::request_interceptors),
+/// and called in order for every attempt, so retries get fresh values. Requests
+/// to buckets are signed afterwards, so the signature covers any changes. Closures
+/// taking a `&mut HttpRequest` implement this.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{HttpRequest, WebAssetPlugin};
+/// # use std::sync::Arc;
+/// let mut plugin = WebAssetPlugin::default();
+/// plugin.request_interceptors.push(Arc::new(|request: &mut HttpRequest| {
+///     let signature = format!("{:x}", request.url.len()); // Compute a real signature here
+///     request.headers.insert("X-Signature", signature).unwrap();
+/// }));
+/// App::new().add_plugin(plugin);
+/// ```
+pub trait RequestInterceptor: Send + Sync + 'static {
+    /// Changes `request` before it's sent
+    fn intercept(&self, request: &mut HttpRequest);
+}
+
+impl<F> RequestInterceptor for F
+where
+    F: Fn(&mut HttpRequest) + Send + Sync + 'static,
+{
+    fn intercept(&self, request: &mut HttpRequest) {
+        self(request)
+    }
+}
@@ -0,0 +1,46 @@
+/// How `ipfs://<cid>/path` paths are loaded
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{IpfsConfig, WebAssetPlugin};
+/// App::new().add_plugin(WebAssetPlugin {
+///     ipfs: IpfsConfig {
+///         gateways: vec!["http://127.0.0.1:8080".to_string()],
+///     },
+///     ..default()
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct IpfsConfig {
+    /// Http gateways to load content from, e.g. `https://ipfs.io`.
+    ///
+    /// Tried in order, moving on to the next if a gateway fails.
+    pub gateways: Vec<String>,
+}
+
+impl Default for IpfsConfig {
+    fn default() -> Self {
+        Self {
+            gateways: vec![
+                "https://ipfs.io".to_string(),
+                "https://dweb.link".to_string(),
+            ],
+        }
+    }
+}
+
+impl IpfsConfig {
+    /// The gateway urls of an `ipfs://<cid>/path` path, in the order they're tried
+    pub(crate) fn gateway_urls(&self, path: &str) -> Option<Vec<String>> {
+        let content = path.strip_prefix("ipfs://")?.trim_start_matches('/');
+        if content.is_empty() {
+            return None;
+        }
+        let urls = self
+            .gateways
+            .iter()
+            .map(|gateway| format!("{}/ipfs/{content}", gateway.trim_end_matches('/')))
+            .collect();
+        Some(urls)
+    }
+}
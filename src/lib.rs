@@ -1,8 +1,147 @@
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+mod archive;
+mod asset_request;
+mod auth;
+mod azure;
+#[cfg(all(target_arch = "wasm32", not(feature = "opfs")))]
+mod browser_cache;
+mod cache;
+mod cas;
+mod clock;
+mod content_type;
+#[cfg(not(target_arch = "wasm32"))]
+mod cookies;
+mod delta;
+mod diagnostics;
+#[cfg(not(target_arch = "wasm32"))]
+mod disk_cache;
+#[cfg(not(target_arch = "wasm32"))]
+mod dns;
+#[cfg(not(target_arch = "wasm32"))]
+mod download;
+#[cfg(not(target_arch = "wasm32"))]
+mod encoding;
+mod error;
+mod fallback;
+mod faults;
+#[cfg(target_arch = "wasm32")]
+mod fetch_client;
+mod gcs;
+#[cfg(target_arch = "wasm32")]
+mod global_scope;
+mod handle;
+mod headers;
+mod host_pattern;
+mod http_client;
+mod in_flight;
+mod integrity;
+mod interceptor;
+mod ipfs;
+mod load_progress;
+mod manifest;
+mod memory_cache;
+mod metadata;
+mod net_stats;
+mod oauth;
+mod offline;
+#[cfg(all(target_arch = "wasm32", feature = "opfs"))]
+mod opfs_cache;
+mod overrides;
+mod pause;
+mod pinned;
+mod prefetch;
+mod preload;
+mod priority;
+mod progress;
+#[cfg(not(target_arch = "wasm32"))]
+mod proxy;
+mod renew;
+#[cfg(not(target_arch = "wasm32"))]
+mod replay;
+mod resume;
+mod retry;
+mod rewrite;
+mod s3;
+mod scheme;
+mod sidecar;
+mod signer;
+mod sse;
+#[cfg(not(target_arch = "wasm32"))]
+mod surf_client;
+mod sync;
+#[cfg(all(any(test, feature = "test-util"), not(target_arch = "wasm32")))]
+pub mod test_util;
+mod throttle;
+mod timeout;
+#[cfg(not(target_arch = "wasm32"))]
+mod tls;
+mod transform;
+mod upload;
+mod watch;
 mod web_asset_io;
+mod web_asset_path;
 mod web_asset_plugin;
+#[cfg(feature = "webdav")]
+mod webdav;
+mod websocket;
 
+pub use asset_request::AssetRequest;
+pub use auth::{AccessToken, AuthProvider, BearerTokenAuth, WebAssetAuth};
+pub use azure::{AzureConfig, AzureCredentials};
+pub use cache::{CacheConfig, CacheStrategy, WebAssetCache, WebAssetCacheStats};
+pub use cas::CasConfig;
+#[cfg(not(target_arch = "wasm32"))]
+pub use cookies::WebAssetCookies;
+pub use diagnostics::WebAssetDiagnosticsPlugin;
+#[cfg(not(target_arch = "wasm32"))]
+pub use dns::{DnsConfig, IpPreference};
+pub use error::{StatusErrors, WebAssetError, WebAssetFailed};
+pub use fallback::FallbackAssets;
+pub use faults::FaultInjection;
+#[cfg(target_arch = "wasm32")]
+pub use fetch_client::{FetchClient, FetchOptions};
+pub use gcs::GcsConfig;
+#[cfg(not(target_arch = "wasm32"))]
+pub use gcs::ServiceAccountAuth;
+pub use handle::WebAssetHandle;
+pub use headers::{HeaderMap, InvalidHeader, WebAssetHeaders};
+pub use host_pattern::HostPattern;
+pub use http_client::{
+    BufferedBody, HttpClient, HttpError, HttpRequest, HttpResponse, MaybeSend, Method, ResponseBody,
+};
+pub use integrity::{IntegrityManifest, IntegrityManifestError, WebAssetTampered};
+pub use interceptor::RequestInterceptor;
+pub use ipfs::IpfsConfig;
+pub use load_progress::{HandleProgress, WebAssetLoadProgress};
+pub use metadata::RemoteMetadata;
+pub use net_stats::{StatusClass, WebAssetNetStats};
+pub use oauth::{ClientCredentials, ClientCredentialsAuth};
+pub use offline::WebAssetOffline;
+pub use pause::WebAssetPaused;
+pub use prefetch::WebAssets;
+pub use preload::WebAssetsPreloaded;
+pub use priority::WebAssetPriorities;
+pub use progress::WebAssetProgress;
+#[cfg(not(target_arch = "wasm32"))]
+pub use proxy::{Proxy, ProxyConfig, ProxyKind};
+pub use renew::UrlRenewer;
+#[cfg(not(target_arch = "wasm32"))]
+pub use replay::ResponseRecording;
+pub use retry::RetryPolicy;
+pub use rewrite::UrlRewriter;
+pub use s3::{AwsCredentials, S3Config};
+pub use scheme::SchemeHandler;
+pub use signer::RequestSigner;
+#[cfg(not(target_arch = "wasm32"))]
+pub use surf_client::{PoolConfig, SurfClient};
+pub use sync::{SyncState, SyncStatus, WebAssetSync, WebAssetSyncPlugin};
+pub use timeout::Timeouts;
+#[cfg(not(target_arch = "wasm32"))]
+pub use tls::{Certificate, ClientCertificate, PrivateKey, TlsConfig, TlsConfigError, TlsVersion};
+pub use transform::ResponseTransform;
+pub use upload::WebAssetWriter;
 pub use web_asset_io::WebAssetIo;
-pub use web_asset_plugin::WebAssetPlugin;
+pub use web_asset_path::WebAssetPath;
+pub use web_asset_plugin::{WebAssetPlugin, WebAssetPluginBuilder};
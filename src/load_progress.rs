@@ -0,0 +1,322 @@
+use bevy::{asset::LoadState, prelude::*, utils::HashMap};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use crate::{archive, WebAssetIo};
+
+/// Bytes received against bytes expected over every pending download, so loading
+/// bars can fill in proportion to the data rather than the number of assets.
+///
+/// Downloads that finished stay counted until all of them did, the next download
+/// starting a new batch. A download's size is known once its response arrives, or
+/// earlier with [`WebAssetPlugin::size_requests`](crate::WebAssetPlugin::size_requests)
+/// if it's waiting for a request slot. Assets loaded from the cache count with
+/// their size once loaded.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetLoadProgress;
+/// fn loading_bar(progress: Res<WebAssetLoadProgress>) {
+///     if let Some(fraction) = progress.fraction() {
+///         info!(
+///             "{:.0}% of {} bytes",
+///             fraction * 100.0,
+///             progress.expected_bytes()
+///         );
+///     }
+/// }
+/// ```
+#[derive(Resource, Clone, Default)]
+pub struct WebAssetLoadProgress(Arc<Mutex<HashMap<String, Entry>>>);
+
+#[derive(Default)]
+struct Entry {
+    expected: Option<u64>,
+    received: u64,
+    finished: bool,
+}
+
+impl WebAssetLoadProgress {
+    /// Bytes expected over the downloads of this batch whose size is known
+    pub fn expected_bytes(&self) -> u64 {
+        let entries = self.0.lock().unwrap();
+        entries.values().filter_map(|entry| entry.expected).sum()
+    }
+
+    /// Bytes received over the downloads of this batch
+    pub fn received_bytes(&self) -> u64 {
+        let entries = self.0.lock().unwrap();
+        entries.values().map(|entry| entry.received).sum()
+    }
+
+    /// Downloads that haven't finished yet
+    pub fn pending(&self) -> usize {
+        let entries = self.0.lock().unwrap();
+        entries.values().filter(|entry| !entry.finished).count()
+    }
+
+    /// Pending downloads whose size isn't known yet
+    pub fn unknown_sizes(&self) -> usize {
+        let entries = self.0.lock().unwrap();
+        entries
+            .values()
+            .filter(|entry| !entry.finished && entry.expected.is_none())
+            .count()
+    }
+
+    /// How much of this batch has been downloaded, between `0.0` and `1.0`, `None`
+    /// while the size of a pending download isn't known. `1.0` once nothing is
+    /// pending.
+    pub fn fraction(&self) -> Option<f32> {
+        let entries = self.0.lock().unwrap();
+        let mut expected = 0;
+        let mut received = 0;
+        for entry in entries.values() {
+            match entry.expected {
+                Some(size) => expected += size,
+                None if entry.finished => {}
+                None => return None,
+            }
+            received += entry.received.min(entry.expected.unwrap_or_default());
+        }
+        match expected {
+            0 => Some(1.0),
+            expected => Some((received as f64 / expected as f64) as f32),
+        }
+    }
+
+    /// Counts the download of `url` as pending until the returned guard is dropped
+    pub(crate) fn track<'a>(&'a self, url: &'a str) -> Tracked<'a> {
+        let mut entries = self.0.lock().unwrap();
+        if entries.values().all(|entry| entry.finished) {
+            entries.clear();
+        }
+        entries.insert(url.to_string(), Entry::default());
+        Tracked {
+            progress: self,
+            url,
+            size: None,
+        }
+    }
+
+    /// Records the size of `url` before its download starts
+    pub(crate) fn expect(&self, url: &str, size: u64) {
+        if let Some(entry) = self.0.lock().unwrap().get_mut(url) {
+            entry.expected.get_or_insert(size);
+        }
+    }
+
+    /// Records how much of `url` has been received, out of `expected` bytes
+    pub(crate) fn receive(&self, url: &str, received: u64, expected: Option<u64>) {
+        if let Some(entry) = self.0.lock().unwrap().get_mut(url) {
+            entry.received = received;
+            entry.expected = expected.or(entry.expected);
+        }
+    }
+
+    /// How much of `url` has been downloaded, if it's in this batch and its size
+    /// is known
+    fn fraction_of(&self, url: &str) -> Option<f32> {
+        let entries = self.0.lock().unwrap();
+        let entry = entries.get(url)?;
+        match entry.expected? {
+            0 => Some(1.0),
+            expected => Some((entry.received.min(expected) as f64 / expected as f64) as f32),
+        }
+    }
+
+    /// Whether the size of `url` is known
+    pub(crate) fn knows_size(&self, url: &str) -> bool {
+        let entries = self.0.lock().unwrap();
+        entries
+            .get(url)
+            .is_some_and(|entry| entry.expected.is_some())
+    }
+}
+
+impl std::fmt::Debug for WebAssetLoadProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebAssetLoadProgress")
+            .field("expected_bytes", &self.expected_bytes())
+            .field("received_bytes", &self.received_bytes())
+            .field("pending", &self.pending())
+            .finish()
+    }
+}
+
+/// Units of progress each handle counts for in [`HandleProgress`]
+const UNITS_PER_HANDLE: u32 = 100;
+
+/// How far the loads of a set of handles got, see [`WebAssets::progress`](crate::WebAssets::progress).
+///
+/// Each handle counts for 100 units, which fill in as its download arrives, like
+/// the `Progress` of `iyes_progress` that `bevy_asset_loader` tracks loading states
+/// with:
+///
+/// ```ignore
+/// fn web_asset_progress(web_assets: WebAssets, level: Res<LevelAssets>) -> Progress {
+///     let progress = web_assets.progress(&level.handles);
+///     Progress {
+///         done: progress.done,
+///         total: progress.total,
+///     }
+/// }
+///
+/// app.add_system(web_asset_progress.track_progress().run_if(in_state(GameState::Loading)));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HandleProgress {
+    /// Units done, the total once every handle loaded
+    pub done: u32,
+    /// Units over all handles
+    pub total: u32,
+}
+
+impl HandleProgress {
+    /// How much has been loaded, between `0.0` and `1.0`
+    pub fn fraction(&self) -> f32 {
+        match self.total {
+            0 => 1.0,
+            total => self.done as f32 / total as f32,
+        }
+    }
+
+    /// Adds the progress of a handle whose load is in `state`, with `downloaded` of
+    /// it arrived if that's known
+    pub(crate) fn add(&mut self, state: LoadState, downloaded: Option<f32>) {
+        self.total += UNITS_PER_HANDLE;
+        self.done += match state {
+            LoadState::Loaded => UNITS_PER_HANDLE,
+            // The loader still has to run once the download finished
+            LoadState::Loading => {
+                (downloaded.unwrap_or_default() * (UNITS_PER_HANDLE - 1) as f32) as u32
+            }
+            // Failed loads never finish, so loading states don't continue without them
+            LoadState::NotLoaded | LoadState::Failed | LoadState::Unloaded => 0,
+        };
+    }
+}
+
+impl WebAssetIo {
+    /// How much of the remote asset at `path` has been downloaded, between `0.0` and
+    /// `1.0`, `None` if it isn't being downloaded or its size isn't known yet. Archive
+    /// entries report the progress of their archive.
+    pub fn download_fraction(&self, path: &Path) -> Option<f32> {
+        let resolved = self.resolve_path(path);
+        let path = resolved.as_deref().unwrap_or(path);
+        let uri = path.to_string_lossy();
+        let url = archive::split(&uri).map_or(&*uri, |(url, _)| url);
+        self.load_progress.fraction_of(url)
+    }
+}
+
+/// A pending download, finished when dropped
+pub(crate) struct Tracked<'a> {
+    progress: &'a WebAssetLoadProgress,
+    url: &'a str,
+    /// The size of the loaded asset, if it loaded
+    size: Option<u64>,
+}
+
+impl Tracked<'_> {
+    /// Sets the size of the loaded asset, which counts if nothing was downloaded
+    pub(crate) fn loaded(&mut self, size: usize) {
+        self.size = Some(size as u64);
+    }
+}
+
+impl Drop for Tracked<'_> {
+    fn drop(&mut self) {
+        let mut entries = self.progress.0.lock().unwrap();
+        if let Some(entry) = entries.get_mut(self.url) {
+            // Whatever was received is all there is, loaded from the cache otherwise
+            if entry.received == 0 {
+                entry.received = self.size.unwrap_or_default();
+            }
+            entry.expected = Some(entry.received);
+            entry.finished = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_bytes_over_the_batch() {
+        let progress = WebAssetLoadProgress::default();
+        let a = progress.track("https://cdn/a.png");
+        let b = progress.track("https://cdn/b.png");
+        assert_eq!(progress.pending(), 2);
+        assert_eq!(progress.unknown_sizes(), 2);
+        assert_eq!(progress.fraction(), None);
+
+        progress.expect("https://cdn/a.png", 100);
+        progress.receive("https://cdn/b.png", 50, Some(300));
+        assert_eq!(progress.unknown_sizes(), 0);
+        assert!(progress.knows_size("https://cdn/a.png"));
+        assert_eq!(progress.expected_bytes(), 400);
+        assert_eq!(progress.received_bytes(), 50);
+        assert_eq!(progress.fraction(), Some(0.125));
+        assert_eq!(
+            progress.fraction_of("https://cdn/b.png"),
+            Some(50.0 / 300.0)
+        );
+        // The size from the response wins over the one asked for in advance
+        progress.receive("https://cdn/a.png", 20, Some(200));
+        assert_eq!(progress.expected_bytes(), 500);
+
+        drop(a);
+        assert_eq!(progress.pending(), 1);
+        // Finished downloads count with what they received
+        assert_eq!(progress.expected_bytes(), 320);
+        drop(b);
+        assert_eq!(progress.pending(), 0);
+        assert_eq!(progress.fraction(), Some(1.0));
+    }
+
+    #[test]
+    fn starts_new_batches() {
+        let progress = WebAssetLoadProgress::default();
+        drop(progress.track("https://cdn/a.png"));
+        let mut cached = progress.track("https://cdn/cached.png");
+        cached.loaded(64);
+        drop(cached);
+        // Loaded from the cache, so all of it counts once loaded
+        assert_eq!(progress.received_bytes(), 64);
+        assert_eq!(progress.expected_bytes(), 64);
+
+        let _b = progress.track("https://cdn/b.png");
+        assert_eq!(progress.received_bytes(), 0);
+        assert_eq!(progress.pending(), 1);
+        assert_eq!(progress.fraction_of("https://cdn/cached.png"), None);
+        assert!(!progress.knows_size("https://cdn/b.png"));
+    }
+
+    #[test]
+    fn counts_units_per_handle() {
+        let mut progress = HandleProgress::default();
+        assert_eq!(progress.fraction(), 1.0);
+        progress.add(LoadState::Loaded, None);
+        progress.add(LoadState::Loading, Some(0.5));
+        progress.add(LoadState::Loading, None);
+        progress.add(LoadState::Failed, Some(1.0));
+        assert_eq!(
+            progress,
+            HandleProgress {
+                done: 149,
+                total: 400
+            }
+        );
+
+        // A finished download still waits for its loader
+        let mut progress = HandleProgress::default();
+        progress.add(LoadState::Loading, Some(1.0));
+        assert_eq!(progress.done, 99);
+        progress.add(LoadState::Loaded, None);
+        assert_eq!(progress.fraction(), 199.0 / 200.0);
+    }
+}
@@ -0,0 +1,35 @@
+use serde_json::Value;
+
+/// The names listed in a directory manifest, directories ending with `/`.
+///
+/// Manifests are either an array of names, or an object with an `entries` array:
+///
+/// ```json
+/// { "entries": ["forest.level", "desert.level", "bonus/"] }
+/// ```
+pub(crate) fn parse(bytes: &[u8]) -> Result<Vec<String>, String> {
+    let json: Value =
+        serde_json::from_slice(bytes).map_err(|err| format!("invalid manifest: {err}"))?;
+    let entries = match &json {
+        Value::Array(entries) => entries,
+        Value::Object(manifest) => manifest
+            .get("entries")
+            .and_then(Value::as_array)
+            .ok_or("manifest has no entries array")?,
+        _ => return Err("manifest must be an array or an object".to_string()),
+    };
+    entries
+        .iter()
+        .map(|entry| {
+            let name = entry.as_str().ok_or("manifest entries must be strings")?;
+            // Entries must stay inside the directory
+            let escapes = name.starts_with('/')
+                || name.contains("://")
+                || name.split('/').any(|segment| segment == "..");
+            if name.trim_matches('/').is_empty() || escapes {
+                return Err(format!("invalid manifest entry {name:?}"));
+            }
+            Ok(name.to_string())
+        })
+        .collect()
+}
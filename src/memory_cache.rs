@@ -0,0 +1,133 @@
+use bevy::utils::HashMap;
+use std::{collections::BTreeMap, sync::Mutex};
+
+/// Recently loaded remote assets, kept in memory up to a total size, so loading them
+/// again needs neither the disk nor the network
+pub(crate) struct MemoryCache {
+    max_size: u64,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    /// Bodies by url, with the tick they were last used at
+    bodies: HashMap<String, (Vec<u8>, u64)>,
+    /// Urls by the tick they were last used at, oldest first
+    by_use: BTreeMap<u64, String>,
+    size: u64,
+    tick: u64,
+}
+
+impl Entries {
+    fn touch(&mut self, url: &str) {
+        self.tick += 1;
+        if let Some((_, used)) = self.bodies.get_mut(url) {
+            self.by_use.remove(used);
+            *used = self.tick;
+            self.by_use.insert(self.tick, url.to_string());
+        }
+    }
+
+    fn remove(&mut self, url: &str) {
+        if let Some((body, used)) = self.bodies.remove(url) {
+            self.by_use.remove(&used);
+            self.size -= body.len() as u64;
+        }
+    }
+}
+
+impl MemoryCache {
+    pub(crate) fn new(max_size: u64) -> Self {
+        Self {
+            max_size,
+            entries: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn get(&self, url: &str) -> Option<Vec<u8>> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.touch(url);
+        entries.bodies.get(url).map(|(body, _)| body.clone())
+    }
+
+    /// Keeps `body`, dropping the least recently used bodies to make room
+    pub(crate) fn insert(&self, url: &str, body: &[u8]) {
+        let size = body.len() as u64;
+        if size > self.max_size {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        entries.remove(url);
+        while entries.size + size > self.max_size {
+            let Some((_, oldest)) = entries.by_use.pop_first() else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+        entries.tick += 1;
+        let tick = entries.tick;
+        entries
+            .bodies
+            .insert(url.to_string(), (body.to_vec(), tick));
+        entries.by_use.insert(tick, url.to_string());
+        entries.size += size;
+    }
+
+    pub(crate) fn remove(&self, url: &str) {
+        self.entries.lock().unwrap().remove(url);
+    }
+
+    pub(crate) fn clear(&self) {
+        *self.entries.lock().unwrap() = Entries::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn size(cache: &MemoryCache) -> u64 {
+        cache.entries.lock().unwrap().size
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = MemoryCache::new(10);
+        cache.insert("a", b"aaaa");
+        cache.insert("b", b"bbbb");
+        // Using `a` makes `b` the oldest
+        assert_eq!(cache.get("a").as_deref(), Some(&b"aaaa"[..]));
+        cache.insert("c", b"cccc");
+        assert_eq!(cache.get("b"), None);
+        assert!(cache.get("a").is_some() && cache.get("c").is_some());
+        assert_eq!(size(&cache), 8);
+
+        // Replacing a body doesn't count it twice
+        cache.insert("a", b"aaaaaa");
+        assert_eq!(size(&cache), 10);
+        assert!(cache.get("c").is_some());
+    }
+
+    #[test]
+    fn skips_bodies_larger_than_the_cache() {
+        let cache = MemoryCache::new(4);
+        cache.insert("a", b"aaaa");
+        cache.insert("big", b"bigger");
+        assert_eq!(cache.get("big"), None);
+        assert!(cache.get("a").is_some());
+    }
+
+    #[test]
+    fn removes_and_clears() {
+        let cache = MemoryCache::new(10);
+        cache.insert("a", b"aaaa");
+        cache.insert("b", b"bbbb");
+        cache.remove("a");
+        cache.remove("missing");
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(size(&cache), 4);
+        cache.clear();
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(size(&cache), 0);
+    }
+}
@@ -0,0 +1,108 @@
+use bevy::{
+    asset::{AssetIoError, FileType, Metadata},
+    prelude::default,
+};
+use std::{
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    clock::DateTime, web_asset_io::RequestOptions, HeaderMap, Method, WebAssetError, WebAssetIo,
+};
+
+/// What the server tells about a remote asset without sending it, see
+/// [`WebAssetIo::remote_metadata`].
+///
+/// Bevy's [`Metadata`] only knows whether a path is a file, so this is where the
+/// size of a remote asset can be found before downloading it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoteMetadata {
+    /// Size in bytes, from `Content-Length`
+    pub content_length: Option<u64>,
+    /// From `Last-Modified`
+    pub last_modified: Option<SystemTime>,
+    /// From `Content-Type`, e.g. `image/png`
+    pub content_type: Option<String>,
+}
+
+impl RemoteMetadata {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        Self {
+            content_length: headers
+                .get("content-length")
+                .and_then(|len| len.trim().parse().ok()),
+            last_modified: headers
+                .get("last-modified")
+                .and_then(DateTime::parse_http_date)
+                .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+            content_type: headers.get("content-type").map(str::to_string),
+        }
+    }
+}
+
+impl WebAssetIo {
+    /// Sends a `HEAD` request for the remote asset at `path`, e.g. to check its size
+    /// before loading it.
+    ///
+    /// ```no_run
+    /// # use bevy::{prelude::*, tasks::IoTaskPool};
+    /// # use bevy_web_asset::WebAssetIo;
+    /// fn check_size(asset_server: Res<AssetServer>) {
+    ///     let asset_server = asset_server.clone();
+    ///     IoTaskPool::get()
+    ///         .spawn(async move {
+    ///             let asset_io = asset_server.asset_io().downcast_ref::<WebAssetIo>().unwrap();
+    ///             match asset_io.remote_metadata("https://example.com/level.glb").await {
+    ///                 Ok(metadata) => info!("level.glb is {:?} bytes", metadata.content_length),
+    ///                 Err(err) => warn!("{err}"),
+    ///             }
+    ///         })
+    ///         .detach();
+    /// }
+    /// ```
+    pub async fn remote_metadata(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<RemoteMetadata, WebAssetError> {
+        let path = path.as_ref();
+        let resolved = self.resolve_path(path);
+        let path = resolved.as_deref().unwrap_or(path);
+        let uri = path.to_string_lossy();
+        if !crate::web_asset_io::is_remote(path) {
+            return Err(WebAssetError::Transport {
+                url: uri.into_owned(),
+                message: "not a remote path".to_string(),
+            });
+        }
+        self.head(&uri).await
+    }
+
+    async fn head(&self, uri: &str) -> Result<RemoteMetadata, WebAssetError> {
+        let mut options = RequestOptions {
+            method: Some(Method::Head),
+            ..default()
+        };
+        // The size of the asset itself, not of a compressed transfer
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = options.headers.insert("Accept-Encoding", "identity");
+        let (_, download) = self.send_with_retries(uri, &options).await?;
+        Ok(RemoteMetadata::from_headers(&download.headers))
+    }
+
+    /// Metadata of the remote file at `uri`, from a `HEAD` request
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn head_metadata(&self, uri: &str) -> Result<Metadata, AssetIoError> {
+        futures_lite::future::block_on(self.head(uri))
+            .map(|_| Metadata::new(FileType::File))
+            .map_err(|err| err.into_asset_io_error(Path::new(uri), &self.status_errors))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn head_metadata(&self, uri: &str) -> Result<Metadata, AssetIoError> {
+        Err(AssetIoError::Io(std::io::Error::new(
+            std::io::ErrorKind::WouldBlock,
+            format!("can't request metadata of {uri} without blocking the browser"),
+        )))
+    }
+}
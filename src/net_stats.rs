@@ -0,0 +1,191 @@
+use bevy::{prelude::*, utils::Duration};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// Counts of what [`WebAssetIo`](crate::WebAssetIo) sent over the network, e.g. for
+/// debug overlays. Shared with the requests themselves, so it's current whenever
+/// it's read.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetNetStats;
+/// fn log_net_stats(stats: Res<WebAssetNetStats>) {
+///     info!(
+///         "{} bytes in {} requests, {} active, {:?} latency",
+///         stats.bytes_downloaded(),
+///         stats.requests(),
+///         stats.active_transfers(),
+///         stats.average_latency(),
+///     );
+/// }
+/// ```
+#[derive(Resource, Clone, Default)]
+pub struct WebAssetNetStats(Arc<NetCounters>);
+
+#[derive(Default)]
+struct NetCounters {
+    /// Requests being sent or received
+    active: AtomicUsize,
+    /// Requests waiting for a request slot
+    queued: AtomicUsize,
+    /// Body bytes received, over all requests
+    bytes: AtomicU64,
+    /// Responses by the first digit of their status, `1xx` to `5xx`
+    responses: [AtomicU64; 5],
+    /// Requests that got no response
+    transport_errors: AtomicU64,
+    /// Time until the response arrived, summed over all responses
+    latency_micros: AtomicU64,
+    /// Loads that failed
+    failures: AtomicU64,
+}
+
+/// Which class of status codes to count with [`WebAssetNetStats::responses`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatusClass {
+    /// `1xx`
+    Informational,
+    /// `2xx`
+    Success,
+    /// `3xx`, including `304 Not Modified` responses to revalidations
+    Redirection,
+    /// `4xx`
+    ClientError,
+    /// `5xx`
+    ServerError,
+}
+
+impl StatusClass {
+    /// The class of `status`, `None` if it isn't between `100` and `599`
+    pub(crate) fn of(status: u16) -> Option<Self> {
+        match status / 100 {
+            1 => Some(StatusClass::Informational),
+            2 => Some(StatusClass::Success),
+            3 => Some(StatusClass::Redirection),
+            4 => Some(StatusClass::ClientError),
+            5 => Some(StatusClass::ServerError),
+            _ => None,
+        }
+    }
+}
+
+impl WebAssetNetStats {
+    /// Body bytes downloaded since the app started, including retries and
+    /// failed requests
+    pub fn bytes_downloaded(&self) -> u64 {
+        self.0.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Requests sent since the app started, whether they got a response or not
+    pub fn requests(&self) -> u64 {
+        let responses: u64 = self
+            .0
+            .responses
+            .iter()
+            .map(|count| count.load(Ordering::Relaxed))
+            .sum();
+        responses + self.transport_errors()
+    }
+
+    /// Responses with a status of `class`
+    pub fn responses(&self, class: StatusClass) -> u64 {
+        self.0.responses[class as usize].load(Ordering::Relaxed)
+    }
+
+    /// Requests that failed without a response, e.g. because the connection failed
+    /// or timed out
+    pub fn transport_errors(&self) -> u64 {
+        self.0.transport_errors.load(Ordering::Relaxed)
+    }
+
+    /// Average time from sending a request until its response arrived, `None`
+    /// until the first one did
+    pub fn average_latency(&self) -> Option<Duration> {
+        let responses = self.requests() - self.transport_errors();
+        (responses > 0).then(|| {
+            Duration::from_micros(self.0.latency_micros.load(Ordering::Relaxed) / responses)
+        })
+    }
+
+    /// Requests being sent or received right now
+    pub fn active_transfers(&self) -> usize {
+        self.0.active.load(Ordering::Relaxed)
+    }
+
+    /// Requests waiting for a free request slot, see
+    /// [`WebAssetPlugin::max_concurrent_requests`](crate::WebAssetPlugin::max_concurrent_requests)
+    pub fn queued_transfers(&self) -> usize {
+        self.0.queued.load(Ordering::Relaxed)
+    }
+
+    /// Remote assets that failed to load since the app started
+    pub fn failures(&self) -> u64 {
+        self.0.failures.load(Ordering::Relaxed)
+    }
+
+    /// Counts a request as active as long as the returned guard lives
+    pub(crate) fn active(&self) -> CountGuard<'_> {
+        CountGuard::new(&self.0.active)
+    }
+
+    /// Counts a request as queued as long as the returned guard lives
+    pub(crate) fn queued(&self) -> CountGuard<'_> {
+        CountGuard::new(&self.0.queued)
+    }
+
+    pub(crate) fn responded(&self, status: u16, latency: Duration) {
+        if let Some(class) = StatusClass::of(status) {
+            self.0.responses[class as usize].fetch_add(1, Ordering::Relaxed);
+            self.0
+                .latency_micros
+                .fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn transport_error(&self) {
+        self.0.transport_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn received(&self, bytes: usize) {
+        self.0.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn failed(&self) {
+        self.0.failures.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl fmt::Debug for WebAssetNetStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebAssetNetStats")
+            .field("bytes_downloaded", &self.bytes_downloaded())
+            .field("requests", &self.requests())
+            .field("transport_errors", &self.transport_errors())
+            .field("average_latency", &self.average_latency())
+            .field("active_transfers", &self.active_transfers())
+            .field("queued_transfers", &self.queued_transfers())
+            .field("failures", &self.failures())
+            .finish()
+    }
+}
+
+/// Decrements a count when dropped, so cancelled requests aren't counted forever
+pub(crate) struct CountGuard<'a>(&'a AtomicUsize);
+
+impl<'a> CountGuard<'a> {
+    fn new(count: &'a AtomicUsize) -> Self {
+        count.fetch_add(1, Ordering::Relaxed);
+        Self(count)
+    }
+}
+
+impl Drop for CountGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,214 @@
+use bevy::utils::BoxedFuture;
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    http_client::{HttpClient, HttpRequest, Method},
+    timeout::with_timeout,
+    AccessToken, AuthProvider, BearerTokenAuth, HostPattern,
+};
+
+/// Token responses are a few hundred bytes of json, anything much larger isn't one
+const MAX_TOKEN_RESPONSE_SIZE: usize = 64 * 1024;
+
+/// How long a token request may take, it isn't covered by the plugin's
+/// [`Timeouts`](crate::Timeouts)
+const TOKEN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The client of an OAuth 2 token endpoint, see [`ClientCredentialsAuth`]
+#[derive(Debug, Clone, Default)]
+pub struct ClientCredentials {
+    /// Where tokens are requested, e.g. `https://auth.example.com/oauth2/token`
+    pub token_url: String,
+    /// The id of the game, as registered with the authorization server
+    pub client_id: String,
+    /// The secret of the game, as registered with the authorization server
+    pub client_secret: String,
+    /// Space separated scopes to request, `None` for the server's default
+    pub scope: Option<String>,
+    /// Hosts the tokens are sent to, e.g. `"*.example.com"`, none if empty
+    pub hosts: Vec<HostPattern>,
+}
+
+/// An [`AuthProvider`] getting tokens with the OAuth 2 client credentials grant,
+/// refreshing them before they expire.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{ClientCredentials, ClientCredentialsAuth, WebAssetAuth};
+/// fn authorize(auth: Res<WebAssetAuth>) {
+///     auth.set(ClientCredentialsAuth::new(ClientCredentials {
+///         token_url: "https://auth.example.com/oauth2/token".to_string(),
+///         client_id: "my-game".to_string(),
+///         client_secret: "secret".to_string(),
+///         scope: Some("assets.read".to_string()),
+///         hosts: vec!["assets.example.com".into()],
+///     }));
+/// }
+/// ```
+pub struct ClientCredentialsAuth(BearerTokenAuth);
+
+struct Client {
+    credentials: ClientCredentials,
+    http_client: Arc<dyn HttpClient>,
+}
+
+impl ClientCredentialsAuth {
+    /// Requests tokens with the default client of the platform
+    pub fn new(credentials: ClientCredentials) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let http_client = Arc::new(crate::SurfClient::default());
+        #[cfg(target_arch = "wasm32")]
+        let http_client = Arc::new(crate::FetchClient::default());
+        Self::with_http_client(credentials, http_client)
+    }
+
+    /// Requests tokens with `http_client`, e.g. one going through a proxy
+    pub fn with_http_client(
+        credentials: ClientCredentials,
+        http_client: Arc<dyn HttpClient>,
+    ) -> Self {
+        let hosts = credentials.hosts.clone();
+        let client = Arc::new(Client {
+            credentials,
+            http_client,
+        });
+        Self(BearerTokenAuth::new(hosts, move || {
+            let client = client.clone();
+            Box::pin(async move { client.fetch_token().await })
+        }))
+    }
+}
+
+impl Client {
+    /// The form body of a token request
+    fn form(&self) -> String {
+        let mut form = url::form_urlencoded::Serializer::new(String::new());
+        form.append_pair("grant_type", "client_credentials");
+        if let Some(scope) = &self.credentials.scope {
+            form.append_pair("scope", scope);
+        }
+        form.finish()
+    }
+
+    async fn fetch_token(&self) -> Result<AccessToken, String> {
+        with_timeout(Some(TOKEN_TIMEOUT), self.request_token())
+            .await
+            .map_err(|_| "token request timed out".to_string())?
+    }
+
+    async fn request_token(&self) -> Result<AccessToken, String> {
+        let credentials = &self.credentials;
+        let mut request = HttpRequest {
+            method: Method::Post,
+            body: Some(self.form().into_bytes()),
+            ..HttpRequest::get(&credentials.token_url)
+        };
+        // The id and secret are form encoded before they're joined, see RFC 6749 2.3.1
+        let encode = |value: &str| url::form_urlencoded::byte_serialize(value.as_bytes()).collect();
+        let user: String = encode(&credentials.client_id);
+        let password: String = encode(&credentials.client_secret);
+        let _ = request.headers.insert(
+            "Authorization",
+            format!("Basic {}", base64::encode(format!("{user}:{password}"))),
+        );
+        let _ = request
+            .headers
+            .insert("Content-Type", "application/x-www-form-urlencoded");
+        let _ = request.headers.insert("Accept", "application/json");
+        let response = self
+            .http_client
+            .send(request)
+            .await
+            .map_err(|err| err.to_string())?;
+        let status = response.status;
+        let body = response
+            .bytes_up_to(MAX_TOKEN_RESPONSE_SIZE)
+            .await
+            .map_err(|err| err.to_string())?;
+        if !(200..300).contains(&status) {
+            let body = String::from_utf8_lossy(&body);
+            return Err(format!("token request failed with status {status}: {body}"));
+        }
+        let json: serde_json::Value =
+            serde_json::from_slice(&body).map_err(|err| err.to_string())?;
+        Ok(AccessToken {
+            token: json["access_token"]
+                .as_str()
+                .ok_or("token response has no access_token")?
+                .to_string(),
+            expires_in: json["expires_in"].as_u64().map(Duration::from_secs),
+        })
+    }
+}
+
+impl AuthProvider for ClientCredentialsAuth {
+    fn authorization<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, Option<String>> {
+        self.0.authorization(url)
+    }
+
+    fn invalidate(&self, url: &str) {
+        self.0.invalidate(url)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use futures_lite::future;
+
+    use super::*;
+    use crate::test_util::{MockResponse, MockServer};
+
+    fn client(server: &MockServer) -> Client {
+        Client {
+            credentials: ClientCredentials {
+                token_url: server.url("/token"),
+                client_id: "my game".to_string(),
+                client_secret: "s:cret".to_string(),
+                scope: Some("assets.read".to_string()),
+                hosts: Vec::new(),
+            },
+            http_client: Arc::new(crate::SurfClient::default()),
+        }
+    }
+
+    #[test]
+    fn requests_tokens() {
+        let server = MockServer::start();
+        server.mock(
+            "/token",
+            MockResponse::ok(r#"{"access_token":"secret","expires_in":3600}"#),
+        );
+        let token = future::block_on(client(&server).fetch_token()).unwrap();
+        assert_eq!(token.token, "secret");
+        assert_eq!(token.expires_in, Some(Duration::from_secs(3600)));
+
+        let request = &server.requests_to("/token")[0];
+        assert_eq!(request.method, Method::Post);
+        assert_eq!(
+            request.headers.get("authorization"),
+            Some(format!("Basic {}", base64::encode("my+game:s%3Acret")).as_str())
+        );
+        assert_eq!(
+            request.body,
+            b"grant_type=client_credentials&scope=assets.read"
+        );
+    }
+
+    #[test]
+    fn rejects_failed_and_oversized_responses() {
+        let server = MockServer::start();
+        let body = format!(
+            r#"{{"access_token":"{}"}}"#,
+            "a".repeat(MAX_TOKEN_RESPONSE_SIZE)
+        );
+        server.mock("/token", MockResponse::ok(body));
+        assert!(future::block_on(client(&server).fetch_token()).is_err());
+
+        server.mock(
+            "/token",
+            MockResponse::new(400).body(r#"{"error":"invalid_client"}"#),
+        );
+        let err = future::block_on(client(&server).fetch_token()).unwrap_err();
+        assert!(err.contains("status 400"), "{err}");
+    }
+}
@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Switches remote assets to offline mode, where they're only loaded from the
+/// [cache](crate::WebAssetPlugin::cache) and nothing is sent over the network, e.g. for a
+/// "play offline" option.
+///
+/// Offline, cached assets are used even if they're stale, and everything else fails
+/// right away with [`WebAssetError::Offline`](crate::WebAssetError::Offline), so
+/// [fallbacks](crate::WebAssetPlugin::fallbacks) still apply.
+///
+/// The plugin inserts this as a resource, set it at any time:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetOffline;
+/// fn toggle_offline(keys: Res<Input<KeyCode>>, offline: Res<WebAssetOffline>) {
+///     if keys.just_pressed(KeyCode::O) {
+///         offline.set(!offline.get());
+///     }
+/// }
+/// ```
+#[derive(Resource, Clone, Default, Debug)]
+pub struct WebAssetOffline(Arc<AtomicBool>);
+
+impl WebAssetOffline {
+    /// Starting offline if `offline` is set
+    pub fn new(offline: bool) -> Self {
+        Self(Arc::new(AtomicBool::new(offline)))
+    }
+
+    /// Whether remote assets are only loaded from the cache
+    pub fn get(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Goes offline, or back online
+    pub fn set(&self, offline: bool) {
+        self.0.store(offline, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,173 @@
+use js_sys::{IteratorNext, Uint8Array};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    FileSystemDirectoryHandle, FileSystemFileHandle, FileSystemGetDirectoryOptions,
+    FileSystemGetFileOptions, FileSystemRemoveOptions, FileSystemWritableFileStream,
+};
+
+use crate::{global_scope::GlobalScope, CacheConfig};
+
+/// Name of the file the cache index is kept in
+const INDEX: &str = "index.json";
+
+fn js_error(err: JsValue) -> String {
+    format!("{err:?}")
+}
+
+/// Cache entries kept in the browser's Origin Private File System, laid out like the
+/// native cache: a body file named by its key, and a `.json` file with its metadata
+pub(crate) struct Store {
+    name: String,
+}
+
+impl Store {
+    pub(crate) fn new(config: &CacheConfig) -> Self {
+        Self {
+            name: config.name.clone(),
+        }
+    }
+
+    async fn root() -> Result<FileSystemDirectoryHandle, String> {
+        let root = JsFuture::from(GlobalScope::get()?.storage().get_directory())
+            .await
+            .map_err(js_error)?;
+        Ok(root.unchecked_into())
+    }
+
+    async fn dir(&self) -> Result<FileSystemDirectoryHandle, String> {
+        let options = FileSystemGetDirectoryOptions::new();
+        options.set_create(true);
+        let dir = JsFuture::from(
+            Self::root()
+                .await?
+                .get_directory_handle_with_options(&self.name, &options),
+        )
+        .await
+        .map_err(js_error)?;
+        Ok(dir.unchecked_into())
+    }
+
+    async fn read_file(&self, name: &str) -> Option<Vec<u8>> {
+        let handle: FileSystemFileHandle =
+            JsFuture::from(self.dir().await.ok()?.get_file_handle(name))
+                .await
+                .ok()?
+                .unchecked_into();
+        let file: web_sys::File = JsFuture::from(handle.get_file())
+            .await
+            .ok()?
+            .unchecked_into();
+        let data = JsFuture::from(file.array_buffer()).await.ok()?;
+        Some(Uint8Array::new(&data).to_vec())
+    }
+
+    /// Writes `contents` to `name`, which the browser only swaps in once it's closed,
+    /// so a closed tab never leaves half a file
+    async fn write_file(&self, name: &str, contents: &[u8]) -> Result<(), String> {
+        let options = FileSystemGetFileOptions::new();
+        options.set_create(true);
+        let handle: FileSystemFileHandle = JsFuture::from(
+            self.dir()
+                .await?
+                .get_file_handle_with_options(name, &options),
+        )
+        .await
+        .map_err(js_error)?
+        .unchecked_into();
+        let writable: FileSystemWritableFileStream = JsFuture::from(handle.create_writable())
+            .await
+            .map_err(js_error)?
+            .unchecked_into();
+        JsFuture::from(writable.write_with_u8_array(contents).map_err(js_error)?)
+            .await
+            .map_err(js_error)?;
+        JsFuture::from(writable.close()).await.map_err(js_error)?;
+        Ok(())
+    }
+
+    /// The metadata and body stored under `key`
+    pub(crate) async fn read(&self, key: &str) -> Option<(String, Vec<u8>)> {
+        let meta = String::from_utf8(self.read_file(&format!("{key}.json")).await?).ok()?;
+        let body = self.read_file(key).await?;
+        Some((meta, body))
+    }
+
+    pub(crate) async fn write(&self, key: &str, meta: &str, body: &[u8]) -> Result<(), String> {
+        self.write_file(key, body).await?;
+        self.write_file(&format!("{key}.json"), meta.as_bytes())
+            .await
+    }
+
+    pub(crate) async fn remove(&self, key: &str) -> Result<(), String> {
+        let dir = self.dir().await?;
+        for name in [format!("{key}.json"), key.to_string()] {
+            if let Err(err) = JsFuture::from(dir.remove_entry(&name)).await {
+                if !is_not_found(&err) {
+                    return Err(js_error(err));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes every entry, and the index
+    pub(crate) async fn clear(&self) -> Result<(), String> {
+        let options = FileSystemRemoveOptions::new();
+        options.set_recursive(true);
+        match JsFuture::from(
+            Self::root()
+                .await?
+                .remove_entry_with_options(&self.name, &options),
+        )
+        .await
+        {
+            Err(err) if !is_not_found(&err) => Err(js_error(err)),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) async fn read_index(&self) -> Option<String> {
+        String::from_utf8(self.read_file(INDEX).await?).ok()
+    }
+
+    pub(crate) async fn write_index(&self, index: &str) -> Result<(), String> {
+        self.write_file(INDEX, index.as_bytes()).await
+    }
+
+    /// The metadata of every entry, for rebuilding a missing index
+    pub(crate) async fn metas(&self) -> Vec<String> {
+        let Ok(dir) = self.dir().await else {
+            return Vec::new();
+        };
+        let names = dir.keys();
+        let mut metas = Vec::new();
+        loop {
+            let Ok(next) = names.next() else {
+                break;
+            };
+            let Ok(next) = JsFuture::from(next).await else {
+                break;
+            };
+            let next: IteratorNext = next.unchecked_into();
+            if next.done() {
+                break;
+            }
+            let Some(name) = next.value().as_string() else {
+                continue;
+            };
+            if !name.ends_with(".json") || name == INDEX {
+                continue;
+            }
+            if let Some(meta) = self.read_file(&name).await {
+                metas.extend(String::from_utf8(meta).ok());
+            }
+        }
+        metas
+    }
+}
+
+fn is_not_found(err: &JsValue) -> bool {
+    err.dyn_ref::<js_sys::Error>()
+        .is_some_and(|err| err.name() == "NotFoundError")
+}
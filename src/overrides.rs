@@ -0,0 +1,40 @@
+use std::path::{Path, PathBuf};
+
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::log::warn;
+
+/// Where the local copy of the remote asset `uri` is looked for in `dir`:
+/// `<dir>/<host>/<path>`, without the query
+pub(crate) fn path(dir: &Path, uri: &str) -> Option<PathBuf> {
+    let url = url::Url::parse(uri).ok()?;
+    let mut path = dir.join(url.host_str()?);
+    for segment in url.path_segments()? {
+        let segment = percent_encoding::percent_decode_str(segment)
+            .decode_utf8()
+            .ok()?;
+        // Never look outside of the override directory
+        if segment.is_empty() || segment == "." || segment == ".." || segment.contains(['/', '\\'])
+        {
+            continue;
+        }
+        path.push(&*segment);
+    }
+    Some(path)
+}
+
+/// Writes the downloaded remote asset `uri` to where [`path`] looks for it in `dir`
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn record(dir: &Path, uri: &str, body: &[u8]) {
+    let Some(path) = path(dir, uri) else {
+        return;
+    };
+    let written = async {
+        if let Some(parent) = path.parent() {
+            async_std::fs::create_dir_all(parent).await?;
+        }
+        async_std::fs::write(&path, body).await
+    };
+    if let Err(err) = written.await {
+        warn!("Failed to record {uri} to {}: {err}", path.display());
+    }
+}
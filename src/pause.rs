@@ -0,0 +1,169 @@
+use async_channel::{Receiver, Sender};
+use bevy::prelude::*;
+use std::sync::{Arc, Mutex};
+
+/// Pauses every download, e.g. during a latency-sensitive multiplayer match, until
+/// it's resumed.
+///
+/// While paused, no requests are sent, and transfers that were running stop. Once
+/// resumed, they continue with a `Range` request from where they stopped if the
+/// server supports it, and start over otherwise.
+///
+/// The plugin inserts this as a resource, pause and resume it at any time:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetPaused;
+/// fn start_match(paused: Res<WebAssetPaused>) {
+///     paused.pause();
+/// }
+///
+/// fn end_match(paused: Res<WebAssetPaused>) {
+///     paused.resume();
+/// }
+/// ```
+#[derive(Resource, Clone, Default)]
+pub struct WebAssetPaused(Arc<Mutex<State>>);
+
+struct State {
+    paused: bool,
+    /// Closed once paused
+    pause: Signal,
+    /// Closed once resumed
+    resume: Signal,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            pause: Signal::new(),
+            resume: Signal::new(),
+        }
+    }
+}
+
+/// A channel nothing is sent on, which wakes up its receivers when closed
+struct Signal(Sender<()>, Receiver<()>);
+
+impl Signal {
+    fn new() -> Self {
+        let (sender, receiver) = async_channel::bounded(1);
+        Self(sender, receiver)
+    }
+}
+
+impl WebAssetPaused {
+    /// Whether downloads are paused
+    pub fn is_paused(&self) -> bool {
+        self.0.lock().unwrap().paused
+    }
+
+    /// Pauses every download
+    pub fn pause(&self) {
+        let mut state = self.0.lock().unwrap();
+        if !state.paused {
+            state.paused = true;
+            state.pause.0.close();
+            state.resume = Signal::new();
+        }
+    }
+
+    /// Resumes the paused downloads
+    pub fn resume(&self) {
+        let mut state = self.0.lock().unwrap();
+        if state.paused {
+            state.paused = false;
+            state.resume.0.close();
+            state.pause = Signal::new();
+        }
+    }
+
+    /// Waits until downloads aren't paused
+    pub(crate) async fn resumed(&self) {
+        self.wait(true).await;
+    }
+
+    /// Waits until downloads are paused
+    pub(crate) async fn paused(&self) {
+        self.wait(false).await;
+    }
+
+    async fn wait(&self, while_paused: bool) {
+        loop {
+            let signal = {
+                let state = self.0.lock().unwrap();
+                if state.paused != while_paused {
+                    return;
+                }
+                match while_paused {
+                    true => state.resume.1.clone(),
+                    false => state.pause.1.clone(),
+                }
+            };
+            let _ = signal.recv().await;
+        }
+    }
+}
+
+impl std::fmt::Debug for WebAssetPaused {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("WebAssetPaused")
+            .field(&self.is_paused())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_lite::future;
+
+    use super::*;
+
+    #[test]
+    fn signals_pause_and_resume() {
+        let paused = WebAssetPaused::default();
+        assert!(!paused.is_paused());
+        assert_eq!(
+            future::block_on(future::poll_once(paused.resumed())),
+            Some(())
+        );
+
+        let mut until_paused = Box::pin(paused.paused());
+        assert_eq!(future::block_on(future::poll_once(&mut until_paused)), None);
+        paused.pause();
+        paused.pause();
+        assert!(paused.is_paused());
+        assert_eq!(format!("{paused:?}"), "WebAssetPaused(true)");
+        assert_eq!(future::block_on(future::poll_once(until_paused)), Some(()));
+
+        let mut until_resumed = Box::pin(paused.resumed());
+        assert_eq!(
+            future::block_on(future::poll_once(&mut until_resumed)),
+            None
+        );
+        paused.resume();
+        assert!(!paused.is_paused());
+        assert_eq!(future::block_on(future::poll_once(until_resumed)), Some(()));
+
+        // The signals are renewed, so pausing again is noticed again
+        let mut until_paused = Box::pin(paused.paused());
+        assert_eq!(future::block_on(future::poll_once(&mut until_paused)), None);
+        paused.clone().pause();
+        assert_eq!(future::block_on(future::poll_once(until_paused)), Some(()));
+    }
+
+    #[test]
+    fn wakes_up_waiting_tasks() {
+        let paused = WebAssetPaused::default();
+        paused.pause();
+        let waiting = std::thread::spawn({
+            let paused = paused.clone();
+            move || future::block_on(paused.resumed())
+        });
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        assert!(!waiting.is_finished());
+        paused.resume();
+        waiting.join().unwrap();
+    }
+}
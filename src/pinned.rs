@@ -0,0 +1,172 @@
+use async_lock::OnceCell;
+use bevy::{log::warn, utils::HashMap};
+use serde_json::Value;
+
+use crate::{WebAssetError, WebAssetIo};
+
+/// Where assets are loaded from as of a pinned manifest, downloaded once per session so
+/// assets changing on the server mid-session never mix old and new versions.
+///
+/// The manifest maps logical asset paths to the urls of their pinned versions, relative
+/// to the manifest or absolute, with an optional version:
+///
+/// ```json
+/// {
+///     "version": "2023.06.1",
+///     "assets": {
+///         "textures/hero.png": "v42/textures/hero.png",
+///         "music/theme.ogg": "https://audio.example.com/theme-7f3a.ogg"
+///     }
+/// }
+/// ```
+pub(crate) struct PinnedManifest {
+    url: String,
+    snapshot: OnceCell<Snapshot>,
+}
+
+#[derive(Default)]
+struct Snapshot {
+    version: Option<String>,
+    /// Urls by logical path
+    assets: HashMap<String, String>,
+}
+
+impl PinnedManifest {
+    pub(crate) fn new(url: String) -> Self {
+        Self {
+            url,
+            snapshot: OnceCell::new(),
+        }
+    }
+
+    /// Whether the manifest was downloaded, or failed to
+    pub(crate) fn is_loaded(&self) -> bool {
+        self.snapshot.is_initialized()
+    }
+
+    /// The url of the pinned version of `path`, once the manifest is loaded
+    pub(crate) fn get(&self, path: &str) -> Option<&str> {
+        self.snapshot.get()?.assets.get(path).map(String::as_str)
+    }
+}
+
+fn parse(manifest_url: &str, manifest: &[u8]) -> Result<Snapshot, String> {
+    let json: Value = serde_json::from_slice(manifest)
+        .map_err(|err| format!("invalid pinned manifest: {err}"))?;
+    let assets = json
+        .get("assets")
+        .and_then(Value::as_object)
+        .ok_or("pinned manifest has no assets object")?;
+    let base = url::Url::parse(manifest_url).map_err(|err| err.to_string())?;
+    let assets = assets
+        .iter()
+        .map(|(path, url)| {
+            let url = url
+                .as_str()
+                .and_then(|url| base.join(url).ok())
+                .ok_or_else(|| format!("invalid pinned url of {path:?}"))?;
+            Ok((path.clone(), url.to_string()))
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(Snapshot {
+        version: json
+            .get("version")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        assets,
+    })
+}
+
+impl WebAssetIo {
+    /// Downloads the pinned manifest, unless it was already. A manifest that fails to
+    /// download pins nothing for the rest of the session.
+    pub(crate) async fn load_pinned_manifest(&self) {
+        let Some(pinned) = &self.pinned else {
+            return;
+        };
+        pinned
+            .snapshot
+            .get_or_init(|| async {
+                let snapshot = self
+                    .fetch_shared(&pinned.url, 0)
+                    .await
+                    .map_err(|err: WebAssetError| err.to_string())
+                    .and_then(|manifest| parse(&pinned.url, &manifest));
+                snapshot.unwrap_or_else(|err| {
+                    warn!("Loading assets unpinned, {err}");
+                    Snapshot::default()
+                })
+            })
+            .await;
+    }
+
+    /// The version of the pinned manifest assets are loaded as of, once it's loaded
+    pub fn pinned_version(&self) -> Option<&str> {
+        self.pinned.as_ref()?.snapshot.get()?.version.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_lite::future;
+
+    use super::*;
+
+    #[test]
+    fn resolves_pinned_urls() {
+        let snapshot = parse(
+            "https://example.com/content/pinned.json",
+            br#"{
+                "version": "2023.06.1",
+                "assets": {
+                    "textures/hero.png": "v42/textures/hero.png",
+                    "levels/1.ron": "/levels/1-ab12.ron",
+                    "music/theme.ogg": "https://audio.example.com/theme-7f3a.ogg"
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(snapshot.version.as_deref(), Some("2023.06.1"));
+        assert_eq!(
+            snapshot.assets["textures/hero.png"],
+            "https://example.com/content/v42/textures/hero.png"
+        );
+        assert_eq!(
+            snapshot.assets["levels/1.ron"],
+            "https://example.com/levels/1-ab12.ron"
+        );
+        assert_eq!(
+            snapshot.assets["music/theme.ogg"],
+            "https://audio.example.com/theme-7f3a.ogg"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_manifests() {
+        let parse = |manifest: &str| parse("https://example.com/pinned.json", manifest.as_bytes());
+        assert!(parse("not json").is_err());
+        assert!(parse(r#"{ "version": "1" }"#).is_err());
+        assert!(parse(r#"{ "assets": { "hero.png": 42 } }"#).is_err());
+        let unversioned = parse(r#"{ "assets": {} }"#).unwrap();
+        assert!(unversioned.version.is_none() && unversioned.assets.is_empty());
+    }
+
+    #[test]
+    fn pins_nothing_until_loaded() {
+        let pinned = PinnedManifest::new("https://example.com/pinned.json".to_string());
+        assert!(!pinned.is_loaded());
+        assert_eq!(pinned.get("hero.png"), None);
+
+        let snapshot = parse(
+            &pinned.url,
+            br#"{ "assets": { "hero.png": "v2/hero.png" } }"#,
+        );
+        future::block_on(pinned.snapshot.get_or_init(|| async { snapshot.unwrap() }));
+        assert!(pinned.is_loaded());
+        assert_eq!(
+            pinned.get("hero.png"),
+            Some("https://example.com/v2/hero.png")
+        );
+        assert_eq!(pinned.get("other.png"), None);
+    }
+}
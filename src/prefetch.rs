@@ -0,0 +1,172 @@
+use bevy::{
+    asset::{Asset, HandleId},
+    ecs::system::SystemParam,
+    log::warn,
+    prelude::*,
+    tasks::{IoTaskPool, Task},
+};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    archive, priority::BACKGROUND_PRIORITY, web_asset_io::is_remote, HandleProgress, WebAssetError,
+    WebAssetHandle, WebAssetIo,
+};
+
+/// Downloads remote assets ahead of time, without loading them.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssets;
+/// fn warm_next_level(web_assets: WebAssets) {
+///     web_assets.prefetch([
+///         "https://example.com/level2/map.glb",
+///         "https://example.com/level2/music.ogg",
+///     ]);
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct WebAssets<'w> {
+    asset_server: Res<'w, AssetServer>,
+}
+
+impl<'w> WebAssets<'w> {
+    /// Downloads `paths` into the [cache](crate::WebAssetPlugin::cache) in the background,
+    /// so loading them later doesn't have to wait for the network, e.g. to warm the next
+    /// level's assets during gameplay.
+    ///
+    /// Archives are also kept in memory, so their entries load right away. With
+    /// [`WebAssetPlugin::max_concurrent_requests`](crate::WebAssetPlugin::max_concurrent_requests)
+    /// set, prefetches only get a request slot once no other assets wait for one.
+    pub fn prefetch<P: Into<PathBuf>>(&self, paths: impl IntoIterator<Item = P>) {
+        let Some(asset_io) = self.asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+            return;
+        };
+        if asset_io.cache.is_none() {
+            warn!("Prefetching without a cache, only archives are kept");
+        }
+        for path in paths {
+            let asset_server = self.asset_server.clone();
+            let path = path.into();
+            IoTaskPool::get()
+                .spawn(async move {
+                    let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>()
+                    else {
+                        warn!(
+                            "Can't prefetch {}, the asset io isn't a WebAssetIo",
+                            path.display()
+                        );
+                        return;
+                    };
+                    if let Err(err) = asset_io.prefetch(&path).await {
+                        warn!("Failed to prefetch {}: {err}", path.display());
+                    }
+                })
+                .detach();
+        }
+    }
+}
+
+impl<'w> WebAssets<'w> {
+    /// Loads the asset at `path` like [`AssetServer::load`], cancelling its download
+    /// once every handle this returned for `path`, and their clones, are dropped
+    /// before it loaded
+    pub fn load<T: Asset>(&self, path: impl Into<PathBuf>) -> WebAssetHandle<T> {
+        let handle = self.asset_server.load(path.into());
+        WebAssetHandle::new(handle, self.asset_server.clone())
+    }
+
+    /// How far the loads of `handles` got, counting the bytes of their downloads,
+    /// for loading-state crates like `bevy_asset_loader`, see [`HandleProgress`]
+    pub fn progress<H: Into<HandleId>>(
+        &self,
+        handles: impl IntoIterator<Item = H>,
+    ) -> HandleProgress {
+        let asset_io = self.asset_server.asset_io().downcast_ref::<WebAssetIo>();
+        let mut progress = HandleProgress::default();
+        for handle in handles {
+            let handle = handle.into();
+            let state = self.asset_server.get_load_state(handle);
+            let downloaded = asset_io
+                .zip(self.asset_server.get_handle_path(handle))
+                .and_then(|(asset_io, path)| asset_io.download_fraction(path.path()));
+            progress.add(state, downloaded);
+        }
+        progress
+    }
+
+    /// Cancels the downloads of `paths`, loads and prefetches alike, see
+    /// [`WebAssetIo::cancel`]
+    pub fn cancel<P: AsRef<Path>>(&self, paths: impl IntoIterator<Item = P>) {
+        let Some(asset_io) = self.asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+            return;
+        };
+        for path in paths {
+            asset_io.cancel(path.as_ref());
+        }
+    }
+
+    /// Loads a remote asset without an extension, e.g. `https://example.com/assets/12345`,
+    /// with the asset loader for the `Content-Type` the server sends for it. The task
+    /// finishes with the handle once the content type is known, see
+    /// [`WebAssetIo::loader_path`].
+    pub fn load_by_content_type(
+        &self,
+        path: impl Into<PathBuf>,
+    ) -> Task<Result<HandleUntyped, WebAssetError>> {
+        let asset_server = self.asset_server.clone();
+        let path = path.into();
+        IoTaskPool::get().spawn(async move {
+            let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+                return Ok(asset_server.load_untyped(path));
+            };
+            let path = asset_io.loader_path(&path).await?;
+            Ok(asset_server.load_untyped(path))
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<'w> WebAssets<'w> {
+    /// Downloads the remote asset at `path` to the file `destination` in the background,
+    /// see [`WebAssetIo::download_to`]
+    pub fn download_to(
+        &self,
+        path: impl Into<PathBuf>,
+        destination: impl Into<PathBuf>,
+    ) -> Task<Result<u64, WebAssetError>> {
+        let asset_server = self.asset_server.clone();
+        let path = path.into();
+        let destination = destination.into();
+        IoTaskPool::get().spawn(async move {
+            let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+                return Err(WebAssetError::Transport {
+                    url: path.to_string_lossy().into_owned(),
+                    message: "the asset io isn't a WebAssetIo".to_string(),
+                });
+            };
+            asset_io.download_to(&path, &destination).await
+        })
+    }
+}
+
+impl WebAssetIo {
+    /// Downloads the remote asset at `path` without loading it
+    pub(crate) async fn prefetch(&self, path: &Path) -> Result<(), WebAssetError> {
+        let resolved = self.resolve_path(path);
+        let path = resolved.as_deref().unwrap_or(path);
+        let uri = path.to_string_lossy();
+        if !is_remote(path) || self.scheme_handler(path).is_some() {
+            return Err(WebAssetError::Transport {
+                url: uri.into_owned(),
+                message: "not a remote path".to_string(),
+            });
+        }
+        match archive::split(&uri) {
+            Some((url, _)) => self.archive(url, BACKGROUND_PRIORITY).await.map(|_| ()),
+            None => self
+                .fetch_shared(&uri, BACKGROUND_PRIORITY)
+                .await
+                .map(|_| ()),
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use bevy::{log::warn, prelude::*, tasks::IoTaskPool};
+use crossbeam_channel::{Receiver, Sender};
+use serde_json::Value;
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use crate::{web_asset_io::RequestOptions, WebAssetError, WebAssetIo};
+
+/// Lists of assets to preload are as small as directory manifests
+const MAX_PRELOAD_LIST_SIZE: u64 = crate::web_asset_io::MAX_MANIFEST_SIZE;
+
+/// Sent once the assets in [`WebAssetPlugin::preload`](crate::WebAssetPlugin::preload)
+/// and [`preload_list_url`](crate::WebAssetPlugin::preload_list_url) are in the
+/// cache, e.g. to leave the splash screen.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetsPreloaded;
+/// fn leave_splash_screen(mut preloaded: EventReader<WebAssetsPreloaded>) {
+///     for preloaded in preloaded.iter() {
+///         info!("Preloaded {} assets", preloaded.preloaded);
+///         for url in &preloaded.failed {
+///             warn!("Couldn't preload {url}");
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WebAssetsPreloaded {
+    /// Assets that are in the cache now
+    pub preloaded: usize,
+    /// Urls that failed to download, including the list's
+    pub failed: Vec<String>,
+}
+
+/// Receives the event sent once preloading finished
+#[derive(Resource)]
+pub(crate) struct PreloadReceiver(pub(crate) Receiver<WebAssetsPreloaded>);
+
+pub(crate) fn send_preloaded_events(
+    receiver: Res<PreloadReceiver>,
+    mut events: EventWriter<WebAssetsPreloaded>,
+) {
+    events.send_batch(receiver.0.try_iter());
+}
+
+/// What's left of the preload, sent once nothing's left
+struct Preload {
+    remaining: usize,
+    preloaded: WebAssetsPreloaded,
+    sender: Sender<WebAssetsPreloaded>,
+}
+
+impl Preload {
+    fn finish(&mut self, url: String, result: Result<(), WebAssetError>) {
+        match result {
+            Ok(()) => self.preloaded.preloaded += 1,
+            Err(err) => {
+                warn!("Failed to preload {url}: {err}");
+                self.preloaded.failed.push(url);
+            }
+        }
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            // The receiver only goes away with the app
+            let _ = self.sender.send(std::mem::take(&mut self.preloaded));
+        }
+    }
+}
+
+/// Starts downloading `urls`, and the assets listed at `list_url`, into the cache
+pub(crate) fn preload(
+    urls: Vec<String>,
+    list_url: Option<String>,
+    sender: Sender<WebAssetsPreloaded>,
+) -> impl Fn(Res<AssetServer>) {
+    move |asset_server: Res<AssetServer>| {
+        let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+            return;
+        };
+        if asset_io.cache.is_none() {
+            warn!("Preloading without a cache, only archives are kept");
+        }
+        let (urls, list_url, sender) = (urls.clone(), list_url.clone(), sender.clone());
+        let asset_server = asset_server.clone();
+        IoTaskPool::get()
+            .spawn(async move {
+                let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+                    warn!("Can't preload assets, the asset io isn't a WebAssetIo");
+                    return;
+                };
+                let mut preloaded = WebAssetsPreloaded::default();
+                let mut urls = urls;
+                if let Some(list_url) = list_url {
+                    match asset_io.preload_list(&list_url).await {
+                        Ok(listed) => urls.extend(listed),
+                        Err(err) => {
+                            warn!("Failed to download the preload list: {err}");
+                            preloaded.failed.push(list_url);
+                        }
+                    }
+                }
+                if urls.is_empty() {
+                    let _ = sender.send(preloaded);
+                    return;
+                }
+                let preload = Arc::new(Mutex::new(Preload {
+                    remaining: urls.len(),
+                    preloaded,
+                    sender,
+                }));
+                // Tasks can't be awaited in the browser, so the last one reports
+                for url in urls {
+                    let asset_server = asset_server.clone();
+                    let preload = preload.clone();
+                    IoTaskPool::get()
+                        .spawn(async move {
+                            let Some(asset_io) =
+                                asset_server.asset_io().downcast_ref::<WebAssetIo>()
+                            else {
+                                let err = WebAssetError::Transport {
+                                    url: url.clone(),
+                                    message: "the asset io isn't a WebAssetIo".to_string(),
+                                };
+                                preload.lock().unwrap().finish(url, Err(err));
+                                return;
+                            };
+                            let result = asset_io.prefetch(Path::new(&url)).await;
+                            preload.lock().unwrap().finish(url, result);
+                        })
+                        .detach();
+                }
+            })
+            .detach();
+    }
+}
+
+impl WebAssetIo {
+    /// The urls listed at `list_url`, relative ones resolved against it
+    async fn preload_list(&self, list_url: &str) -> Result<Vec<String>, WebAssetError> {
+        // Relative entries are resolved against the url the list is downloaded from
+        let resolved = self.resolve_path(Path::new(list_url));
+        let list_url = match &resolved {
+            Some(resolved) => resolved.to_string_lossy().into_owned(),
+            None => list_url.to_string(),
+        };
+        let list_url = list_url.as_str();
+        let options = RequestOptions {
+            max_size: Some(MAX_PRELOAD_LIST_SIZE),
+            ..RequestOptions::default()
+        };
+        let bytes = self.fetch_with_options(list_url, &options).await?;
+        let decode = |message: String| WebAssetError::Decode {
+            url: list_url.to_string(),
+            message,
+        };
+        let base = url::Url::parse(list_url).map_err(|err| decode(err.to_string()))?;
+        parse_list(&bytes)
+            .map_err(decode)?
+            .into_iter()
+            .map(|entry| match base.join(&entry) {
+                Ok(url) => Ok(url.to_string()),
+                Err(err) => Err(decode(format!("invalid entry {entry:?}: {err}"))),
+            })
+            .collect()
+    }
+}
+
+/// The entries of a preload list, an array of urls, or an object with a `urls`
+/// array:
+///
+/// ```json
+/// { "urls": ["textures/hero.png", "https://cdn.example.com/music/theme.ogg"] }
+/// ```
+fn parse_list(bytes: &[u8]) -> Result<Vec<String>, String> {
+    let json: Value =
+        serde_json::from_slice(bytes).map_err(|err| format!("invalid preload list: {err}"))?;
+    let urls = match &json {
+        Value::Array(urls) => urls,
+        Value::Object(list) => list
+            .get("urls")
+            .and_then(Value::as_array)
+            .ok_or("preload list has no urls array")?,
+        _ => return Err("preload list must be an array or an object".to_string()),
+    };
+    urls.iter()
+        .map(|url| {
+            url.as_str()
+                .map(str::to_string)
+                .ok_or_else(|| "preload list entries must be strings".to_string())
+        })
+        .collect()
+}
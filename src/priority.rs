@@ -0,0 +1,246 @@
+use bevy::{prelude::*, utils::HashMap};
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    sync::{Arc, Mutex, RwLock},
+};
+
+/// Priority of downloads nobody is waiting for, like prefetches, below assets
+/// without a priority
+pub(crate) const BACKGROUND_PRIORITY: i32 = -1;
+
+/// Download priorities of asset paths, when
+/// [`WebAssetPlugin::max_concurrent_requests`](crate::WebAssetPlugin::max_concurrent_requests)
+/// makes requests wait for a free slot.
+///
+/// Waiting requests with a higher priority are sent first, those with the same
+/// priority in the order they were made. Assets without a priority have priority
+/// `0`, so negative priorities are for background downloads.
+///
+/// The plugin inserts this as a resource, and priorities set at runtime apply to
+/// loads started afterwards.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetPriorities;
+/// fn load_level(asset_server: Res<AssetServer>, priorities: Res<WebAssetPriorities>) {
+///     priorities.set("https://cdn.example.com/level2.png", 10);
+///     priorities.set("https://cdn.example.com/music/level3.ogg", -10);
+///     let _level: Handle<Image> = asset_server.load("https://cdn.example.com/level2.png");
+/// }
+/// ```
+#[derive(Resource, Clone, Default)]
+pub struct WebAssetPriorities(Arc<RwLock<HashMap<String, i32>>>);
+
+impl WebAssetPriorities {
+    /// Downloads `path`, as passed to the asset server or as the url it resolves to,
+    /// with `priority`
+    pub fn set(&self, path: impl Into<String>, priority: i32) {
+        self.0.write().unwrap().insert(path.into(), priority);
+    }
+
+    /// Downloads `path` with the default priority again
+    pub fn remove(&self, path: &str) {
+        self.0.write().unwrap().remove(path);
+    }
+
+    /// The priority set for `path`, if any
+    pub fn get(&self, path: &str) -> Option<i32> {
+        self.0.read().unwrap().get(path).copied()
+    }
+}
+
+struct Waiter {
+    priority: i32,
+    /// Breaks ties in arrival order
+    sequence: u64,
+    sender: async_channel::Sender<()>,
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+#[derive(Default)]
+struct SlotState {
+    in_use: usize,
+    waiting: BinaryHeap<Waiter>,
+    next_sequence: u64,
+}
+
+/// Limits the number of simultaneous requests, handing free slots to the waiting
+/// request with the highest priority
+pub(crate) struct RequestSlots {
+    max: usize,
+    state: Mutex<SlotState>,
+}
+
+impl RequestSlots {
+    pub(crate) fn new(max: usize) -> Self {
+        Self {
+            max: max.max(1),
+            state: default(),
+        }
+    }
+
+    /// Waits for a free slot, which is released when the permit is dropped
+    pub(crate) async fn acquire(&self, priority: i32) -> SlotPermit<'_> {
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            if state.in_use < self.max && state.waiting.is_empty() {
+                state.in_use += 1;
+                return SlotPermit(self);
+            }
+            let (sender, receiver) = async_channel::bounded(1);
+            let sequence = state.next_sequence;
+            state.next_sequence += 1;
+            state.waiting.push(Waiter {
+                priority,
+                sequence,
+                sender,
+            });
+            receiver
+        };
+        let mut waiting = Waiting {
+            slots: self,
+            receiver,
+            done: false,
+        };
+        // The sender is only dropped after handing over a slot
+        let _ = waiting.receiver.recv().await;
+        waiting.done = true;
+        SlotPermit(self)
+    }
+
+    /// Hands the slot to the next waiter, or frees it
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        while let Some(waiter) = state.waiting.pop() {
+            // Fails if the waiter gave up in the meantime
+            if waiter.sender.try_send(()).is_ok() {
+                return;
+            }
+        }
+        state.in_use -= 1;
+    }
+}
+
+/// A request waiting for a slot, which passes the slot on if it stops waiting
+/// right after getting one
+struct Waiting<'a> {
+    slots: &'a RequestSlots,
+    receiver: async_channel::Receiver<()>,
+    done: bool,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        self.receiver.close();
+        if self.receiver.try_recv().is_ok() {
+            self.slots.release();
+        }
+    }
+}
+
+/// A request slot, released when dropped
+pub(crate) struct SlotPermit<'a>(&'a RequestSlots);
+
+impl Drop for SlotPermit<'_> {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_lite::future;
+    use std::{future::Future, pin::Pin};
+
+    use super::*;
+
+    type Acquire<'a> = Pin<Box<dyn Future<Output = SlotPermit<'a>> + 'a>>;
+
+    /// Starts waiting for a slot, `None` if it was free right away
+    fn wait(slots: &RequestSlots, priority: i32) -> Acquire<'_> {
+        let mut acquire: Acquire = Box::pin(slots.acquire(priority));
+        assert!(future::block_on(future::poll_once(&mut acquire)).is_none());
+        acquire
+    }
+
+    fn poll<'a>(acquire: &mut Acquire<'a>) -> Option<SlotPermit<'a>> {
+        future::block_on(future::poll_once(acquire))
+    }
+
+    #[test]
+    fn hands_slots_out_by_priority_then_arrival() {
+        let slots = RequestSlots::new(1);
+        let permit = future::block_on(slots.acquire(0));
+        let mut background = wait(&slots, BACKGROUND_PRIORITY);
+        let mut first = wait(&slots, 1);
+        let mut urgent = wait(&slots, 10);
+        let mut second = wait(&slots, 1);
+
+        drop(permit);
+        let permit = poll(&mut urgent).unwrap();
+        assert!(poll(&mut first).is_none() && poll(&mut second).is_none());
+        drop(permit);
+        let permit = poll(&mut first).unwrap();
+        assert!(poll(&mut second).is_none());
+        drop(permit);
+        let permit = poll(&mut second).unwrap();
+        assert!(poll(&mut background).is_none());
+        drop(permit);
+        drop(poll(&mut background).unwrap());
+
+        assert_eq!(slots.state.lock().unwrap().in_use, 0);
+    }
+
+    #[test]
+    fn passes_slots_on_when_waiters_give_up() {
+        let slots = RequestSlots::new(2);
+        let first = future::block_on(slots.acquire(0));
+        let second = future::block_on(slots.acquire(0));
+        let gives_up = wait(&slots, 5);
+        let mut waits = wait(&slots, 0);
+
+        drop(first);
+        // Given the slot, but dropped before it noticed
+        drop(gives_up);
+        let permit = poll(&mut waits).unwrap();
+        drop((second, permit));
+        assert_eq!(slots.state.lock().unwrap().in_use, 0);
+    }
+
+    #[test]
+    fn stores_priorities() {
+        let priorities = WebAssetPriorities::default();
+        priorities.set("https://cdn.example.com/level2.png", 10);
+        assert_eq!(
+            priorities.get("https://cdn.example.com/level2.png"),
+            Some(10)
+        );
+        priorities.remove("https://cdn.example.com/level2.png");
+        assert_eq!(priorities.get("https://cdn.example.com/level2.png"), None);
+    }
+}
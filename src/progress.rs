@@ -0,0 +1,80 @@
+use bevy::{prelude::*, utils::HashMap};
+use crossbeam_channel::{Receiver, Sender};
+
+use crate::WebAssetLoadProgress;
+
+/// Sent while a web asset is being downloaded.
+///
+/// At most one event per url is sent each frame, reporting the latest progress.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetProgress;
+/// fn loading_bar(mut progress: EventReader<WebAssetProgress>) {
+///     for progress in progress.iter() {
+///         if let Some(fraction) = progress.fraction() {
+///             info!("{}: {:.0}%", progress.url, fraction * 100.0);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WebAssetProgress {
+    /// The url being downloaded
+    pub url: String,
+    /// Number of bytes received so far
+    pub bytes_downloaded: u64,
+    /// Size of the whole response, if the server sent a `Content-Length`
+    pub total_bytes: Option<u64>,
+}
+
+impl WebAssetProgress {
+    /// How much of the asset has been downloaded, between `0.0` and `1.0`
+    pub fn fraction(&self) -> Option<f32> {
+        match self.total_bytes {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.bytes_downloaded as f64 / total as f64) as f32),
+            None => None,
+        }
+    }
+}
+
+/// Receives progress reported by [`WebAssetIo`](crate::WebAssetIo)
+#[derive(Resource)]
+pub(crate) struct ProgressReceiver(pub(crate) Receiver<WebAssetProgress>);
+
+/// Reports progress for a single download
+pub(crate) struct ProgressReporter<'a> {
+    pub(crate) sender: &'a Sender<WebAssetProgress>,
+    pub(crate) url: &'a str,
+    pub(crate) total_bytes: Option<u64>,
+    /// Where the download of `asset` is counted, if it's an asset
+    pub(crate) load_progress: &'a WebAssetLoadProgress,
+    pub(crate) asset: Option<&'a str>,
+}
+
+impl ProgressReporter<'_> {
+    pub(crate) fn report(&self, bytes_downloaded: u64) {
+        if let Some(asset) = self.asset {
+            self.load_progress
+                .receive(asset, bytes_downloaded, self.total_bytes);
+        }
+        // The receiver only goes away with the app
+        let _ = self.sender.send(WebAssetProgress {
+            url: self.url.to_string(),
+            bytes_downloaded,
+            total_bytes: self.total_bytes,
+        });
+    }
+}
+
+pub(crate) fn send_progress_events(
+    receiver: Res<ProgressReceiver>,
+    mut events: EventWriter<WebAssetProgress>,
+) {
+    let mut latest = HashMap::<String, WebAssetProgress>::default();
+    for progress in receiver.0.try_iter() {
+        latest.insert(progress.url.clone(), progress);
+    }
+    events.send_batch(latest.into_values());
+}
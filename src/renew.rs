@@ -0,0 +1,53 @@
+use bevy::utils::{BoxedFuture, HashMap};
+use std::sync::RwLock;
+
+/// Renews expired signed urls, e.g. presigned S3 urls that stopped working after 15
+/// minutes, so hot reloading and retries keep working.
+///
+/// Set in [`WebAssetPlugin::url_renewer`](crate::WebAssetPlugin::url_renewer). Called when
+/// a request is rejected with `403 Forbidden`, with the url as it was loaded, and
+/// the request is sent again to the url returned. That url is used for every later
+/// request of the asset, until it's renewed again. Closures taking a `&str` and
+/// returning a boxed future implement this.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetPlugin;
+/// # use std::sync::Arc;
+/// let mut plugin = WebAssetPlugin::default();
+/// plugin.url_renewer = Some(Arc::new(|url: &str| {
+///     let url = url.to_string();
+///     Box::pin(async move {
+///         // Ask your backend to sign `url` again here
+///         Some(format!("{url}&renewed"))
+///     }) as _
+/// }));
+/// App::new().add_plugin(plugin);
+/// ```
+pub trait UrlRenewer: Send + Sync + 'static {
+    /// A freshly signed url for `url`, or `None` if it can't be renewed
+    fn renew<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, Option<String>>;
+}
+
+impl<F> UrlRenewer for F
+where
+    F: Fn(&str) -> BoxedFuture<'static, Option<String>> + Send + Sync + 'static,
+{
+    fn renew<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, Option<String>> {
+        self(url)
+    }
+}
+
+/// The latest renewed url by url as it was loaded
+#[derive(Default)]
+pub(crate) struct RenewedUrls(RwLock<HashMap<String, String>>);
+
+impl RenewedUrls {
+    pub(crate) fn get(&self, url: &str) -> Option<String> {
+        self.0.read().unwrap().get(url).cloned()
+    }
+
+    pub(crate) fn insert(&self, url: String, renewed: String) {
+        self.0.write().unwrap().insert(url, renewed);
+    }
+}
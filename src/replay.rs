@@ -0,0 +1,154 @@
+use async_std::fs;
+use bevy::{log::warn, utils::BoxedFuture};
+use serde_json::json;
+use std::{path::PathBuf, sync::Arc};
+
+use crate::{
+    http_client::{
+        BufferedBody, HttpClient, HttpError, HttpRequest, HttpResponse, Method, ResponseBody,
+    },
+    HeaderMap,
+};
+
+/// Whether responses are written to, or read back from, a folder, set with
+/// [`WebAssetPlugin::response_recording`](crate::WebAssetPlugin::response_recording).
+///
+/// Responses are stored with their status and headers, by method, url and request
+/// body, so a replayed run gets exactly what the recorded one did, e.g. for
+/// deterministic demos and CI. Native only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResponseRecording {
+    /// Sends requests as usual, and writes every response to the folder
+    Record(PathBuf),
+    /// Answers requests with the responses in the folder, never touching the network.
+    /// Requests that weren't recorded fail.
+    Replay(PathBuf),
+}
+
+/// Sends requests with `inner` while recording, or answers them from the folder
+pub(crate) struct RecordingClient {
+    inner: Arc<dyn HttpClient>,
+    recording: ResponseRecording,
+}
+
+impl RecordingClient {
+    pub(crate) fn new(inner: Arc<dyn HttpClient>, recording: ResponseRecording) -> Self {
+        Self { inner, recording }
+    }
+}
+
+/// The name a response is stored under, since urls aren't valid file names
+fn key(request: &HttpRequest) -> String {
+    let mut bytes = format!("{} {}\n", request.method, request.url).into_bytes();
+    bytes.extend(request.body.as_deref().unwrap_or_default());
+    crate::integrity::sha256(&bytes)
+}
+
+impl HttpClient for RecordingClient {
+    fn send(&self, request: HttpRequest) -> BoxedFuture<'_, Result<HttpResponse, HttpError>> {
+        Box::pin(async move {
+            match &self.recording {
+                ResponseRecording::Replay(dir) => replay(dir.clone(), &request).await,
+                ResponseRecording::Record(dir) => {
+                    let key = key(&request);
+                    let meta = |status, headers: &HeaderMap| {
+                        json!({
+                            "method": request.method.as_str(),
+                            "url": request.url,
+                            "status": status,
+                            "headers": headers.iter().collect::<Vec<_>>(),
+                        })
+                        .to_string()
+                    };
+                    let mut response = self.inner.send(request.clone()).await?;
+                    // Revalidations and resumed downloads would replace the full response
+                    if response.status == 304 || response.status == 206 {
+                        return Ok(response);
+                    }
+                    let mut recording = RecordingBody {
+                        inner: Box::new(BufferedBody::new(Vec::new())),
+                        dir: dir.clone(),
+                        key,
+                        meta: meta(response.status, &response.headers),
+                        body: Vec::new(),
+                    };
+                    if request.method != Method::Head {
+                        if response.is_success() {
+                            recording.inner = response.body;
+                            response.body = Box::new(recording);
+                            return Ok(response);
+                        }
+                        while let Some(chunk) = response.body.next_chunk().await? {
+                            recording.body.extend(chunk);
+                        }
+                    }
+                    // Only part of error bodies is read, and none of `HEAD` ones, so
+                    // they're recorded right away
+                    recording.record().await;
+                    response.body = Box::new(BufferedBody::new(recording.body));
+                    Ok(response)
+                }
+            }
+        })
+    }
+}
+
+async fn replay(dir: PathBuf, request: &HttpRequest) -> Result<HttpResponse, HttpError> {
+    let key = key(request);
+    let not_recorded = |_| format!("no recorded response to {} {}", request.method, request.url);
+    let meta = fs::read_to_string(dir.join(format!("{key}.json")))
+        .await
+        .map_err(not_recorded)?;
+    let body = fs::read(dir.join(&key)).await.map_err(not_recorded)?;
+    let meta: serde_json::Value = serde_json::from_str(&meta)?;
+    let mut headers = HeaderMap::new();
+    for header in meta["headers"].as_array().into_iter().flatten() {
+        if let (Some(name), Some(value)) = (header[0].as_str(), header[1].as_str()) {
+            headers.append(name, value)?;
+        }
+    }
+    Ok(HttpResponse {
+        status: meta["status"]
+            .as_u64()
+            .ok_or("recorded response without a status")? as u16,
+        headers,
+        body: Box::new(BufferedBody::new(body)),
+    })
+}
+
+/// Passes `inner` through, and writes the whole response once it's been read
+struct RecordingBody {
+    inner: Box<dyn ResponseBody>,
+    dir: PathBuf,
+    key: String,
+    meta: String,
+    body: Vec<u8>,
+}
+
+impl RecordingBody {
+    // `&mut`, since the inner body isn't `Sync`
+    async fn write(&mut self) -> std::io::Result<()> {
+        fs::create_dir_all(&self.dir).await?;
+        fs::write(self.dir.join(&self.key), &self.body).await?;
+        fs::write(self.dir.join(format!("{}.json", self.key)), &self.meta).await
+    }
+
+    async fn record(&mut self) {
+        if let Err(err) = self.write().await {
+            warn!("Can't record the response to {}: {err}", self.dir.display());
+        }
+    }
+}
+
+impl ResponseBody for RecordingBody {
+    fn next_chunk(&mut self) -> BoxedFuture<'_, Result<Option<Vec<u8>>, HttpError>> {
+        Box::pin(async move {
+            let chunk = self.inner.next_chunk().await?;
+            match &chunk {
+                Some(chunk) => self.body.extend(chunk),
+                None => self.record().await,
+            }
+            Ok(chunk)
+        })
+    }
+}
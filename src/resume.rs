@@ -0,0 +1,275 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    fs::File,
+    io::{self, Seek, SeekFrom, Write},
+};
+
+use crate::HeaderMap;
+
+/// A download in progress, kept between attempts so an interrupted transfer
+/// can be resumed with a `Range` request instead of starting over.
+#[derive(Default)]
+pub(crate) struct Download {
+    /// The part of the body received so far
+    pub(crate) body: Vec<u8>,
+    /// Where the body is written instead of `body`, for downloads straight to disk
+    #[cfg(not(target_arch = "wasm32"))]
+    file: Option<File>,
+    /// The size of the body written to `file`
+    #[cfg(not(target_arch = "wasm32"))]
+    written: u64,
+    /// Whether the server advertised `Accept-Ranges: bytes`
+    resumable: bool,
+    /// `ETag` or `Last-Modified` of the partial body, sent as `If-Range`
+    validator: Option<String>,
+    /// `Content-Encoding` of the body
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) encoding: Option<String>,
+    /// Headers of the latest response
+    pub(crate) headers: HeaderMap,
+}
+
+impl Download {
+    /// A download writing its body to `file`, rather than keeping it in memory
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn to_file(file: File) -> Self {
+        Self {
+            file: Some(file),
+            ..Self::default()
+        }
+    }
+
+    /// The size of the body received so far
+    pub(crate) fn received(&self) -> u64 {
+        #[cfg(not(target_arch = "wasm32"))]
+        if !self.in_memory() {
+            return self.written;
+        }
+        self.body.len() as u64
+    }
+
+    /// Whether the body is kept in `body`
+    pub(crate) fn in_memory(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.file.is_none();
+        #[cfg(target_arch = "wasm32")]
+        true
+    }
+
+    /// Adds the next `chunk` of the body
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn append(&mut self, chunk: &[u8]) -> io::Result<()> {
+        let Some(file) = &mut self.file else {
+            self.body.extend_from_slice(chunk);
+            return Ok(());
+        };
+        // Restarted downloads overwrite what was written before
+        file.seek(SeekFrom::Start(self.written))?;
+        file.write_all(chunk)?;
+        self.written += chunk.len() as u64;
+        Ok(())
+    }
+
+    /// Adds the next `chunk` of the body
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn append(&mut self, chunk: &[u8]) -> Result<(), std::convert::Infallible> {
+        self.body.extend_from_slice(chunk);
+        Ok(())
+    }
+
+    /// Cuts the file off after the body, which a restarted download may have written
+    /// less of, and flushes it to disk
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn finish_file(&mut self) -> io::Result<()> {
+        match &self.file {
+            Some(file) => {
+                file.set_len(self.written)?;
+                file.sync_all()
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Throws away the body received so far
+    fn clear(&mut self) {
+        self.body.clear();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.written = 0;
+        }
+    }
+
+    /// Adds `Range` headers if the next attempt can continue where the last one stopped
+    pub(crate) fn add_range_headers(&self, headers: &mut HeaderMap) -> bool {
+        if !self.resumable || self.received() == 0 {
+            return false;
+        }
+        let Some(validator) = &self.validator else {
+            // Without a validator we can't tell if the resource changed in the meantime
+            return false;
+        };
+        let range = format!("bytes={}-", self.received());
+        headers.insert("Range", range).is_ok() && headers.insert("If-Range", validator).is_ok()
+    }
+
+    /// Prepares for receiving the body of `headers`, returning the total size if known.
+    ///
+    /// Returns `None` if the server sent a range that doesn't continue the partial body,
+    /// in which case the download starts over on the next attempt.
+    pub(crate) fn start(&mut self, status: u16, headers: &HeaderMap) -> Option<Option<u64>> {
+        self.resumable = headers
+            .get_all("accept-ranges")
+            .any(|ranges| ranges.split(',').any(|unit| unit.trim() == "bytes"));
+        self.validator = headers
+            .get("etag")
+            .filter(|etag| !etag.starts_with("W/"))
+            .or_else(|| headers.get("last-modified"))
+            .map(str::to_string);
+        self.headers = headers.clone();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.encoding = headers.get("content-encoding").map(str::to_string);
+        }
+
+        let content_length = headers
+            .get("content-length")
+            .and_then(|len| len.trim().parse().ok());
+
+        if status != 206 {
+            // The server sent the whole thing
+            self.clear();
+            return Some(content_length);
+        }
+
+        match headers.get("content-range").and_then(parse_content_range) {
+            Some((start, total)) if start == self.received() => {
+                Some(total.or_else(|| content_length.map(|len| start + len)))
+            }
+            _ => {
+                self.reset();
+                None
+            }
+        }
+    }
+
+    /// `ETag` or `Last-Modified` of the body
+    pub(crate) fn validator(&self) -> Option<&str> {
+        self.validator.as_deref()
+    }
+
+    /// Throws away the partial body
+    pub(crate) fn reset(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        let file = self.file.take();
+        *self = Self::default();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.file = file;
+        }
+    }
+}
+
+/// Parses `bytes <start>-<end>/<total>` into the start offset and total size
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _end) = range.split_once('-')?;
+    Some((start.trim().parse().ok()?, total.trim().parse().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, *value).unwrap();
+        }
+        headers
+    }
+
+    /// A download that received the first 4 of 10 bytes
+    fn interrupted(response: &[(&str, &str)]) -> Download {
+        let mut download = Download::default();
+        let mut response = headers(response);
+        response.insert("Content-Length", "10").unwrap();
+        assert_eq!(download.start(200, &response), Some(Some(10)));
+        download.append(b"0123").unwrap();
+        download
+    }
+
+    #[test]
+    fn resumes_with_range_request() {
+        let download = interrupted(&[("Accept-Ranges", "none, bytes"), ("ETag", "\"v1\"")]);
+        let mut request = HeaderMap::new();
+        assert!(download.add_range_headers(&mut request));
+        assert_eq!(request.get("range"), Some("bytes=4-"));
+        assert_eq!(request.get("if-range"), Some("\"v1\""));
+    }
+
+    #[test]
+    fn needs_ranges_and_strong_validator() {
+        let without_ranges = interrupted(&[("ETag", "\"v1\"")]);
+        let weak = interrupted(&[("Accept-Ranges", "bytes"), ("ETag", "W/\"v1\"")]);
+        for download in [without_ranges, weak] {
+            let mut request = HeaderMap::new();
+            assert!(!download.add_range_headers(&mut request));
+            assert!(request.is_empty());
+        }
+        let dated = interrupted(&[
+            ("Accept-Ranges", "bytes"),
+            ("ETag", "W/\"v1\""),
+            ("Last-Modified", "Wed, 21 Oct 2015 07:28:00 GMT"),
+        ]);
+        assert_eq!(dated.validator(), Some("Wed, 21 Oct 2015 07:28:00 GMT"));
+    }
+
+    #[test]
+    fn continues_partial_content() {
+        let mut download = interrupted(&[("Accept-Ranges", "bytes"), ("ETag", "\"v1\"")]);
+        let partial = headers(&[("Content-Range", "bytes 4-9/10"), ("Content-Length", "6")]);
+        assert_eq!(download.start(206, &partial), Some(Some(10)));
+        download.append(b"456789").unwrap();
+        assert_eq!(download.body, b"0123456789");
+
+        // Without a total size, it's where the range starts plus its length
+        let mut download = interrupted(&[("Accept-Ranges", "bytes"), ("ETag", "\"v1\"")]);
+        let partial = headers(&[("Content-Range", "bytes 4-9/*"), ("Content-Length", "6")]);
+        assert_eq!(download.start(206, &partial), Some(Some(10)));
+    }
+
+    #[test]
+    fn starts_over_on_full_or_mismatched_response() {
+        let mut download = interrupted(&[("Accept-Ranges", "bytes"), ("ETag", "\"v1\"")]);
+        let full = headers(&[("Content-Length", "12"), ("ETag", "\"v2\"")]);
+        assert_eq!(download.start(200, &full), Some(Some(12)));
+        assert_eq!(download.received(), 0);
+
+        let mut download = interrupted(&[("Accept-Ranges", "bytes"), ("ETag", "\"v1\"")]);
+        let elsewhere = headers(&[("Content-Range", "bytes 2-9/10")]);
+        assert_eq!(download.start(206, &elsewhere), None);
+        assert_eq!(download.received(), 0);
+        assert_eq!(download.validator(), None);
+    }
+
+    #[test]
+    fn writes_to_file() {
+        let path = std::env::temp_dir().join(format!("bevy_web_asset_{}", fastrand::u64(..)));
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .unwrap();
+        let mut download = Download::to_file(file);
+        download.append(b"0123456789").unwrap();
+        // A restarted download overwrites the start, and is cut off after its end
+        download.start(200, &HeaderMap::new());
+        download.append(b"abc").unwrap();
+        download.finish_file().unwrap();
+        assert!(!download.in_memory());
+        assert_eq!(download.received(), 3);
+        assert_eq!(std::fs::read(&path).unwrap(), b"abc");
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+/// Controls how failed http requests are retried.
+///
+/// Only transient failures (connection errors, `408`, `429` and `5xx` responses)
+/// are retried. Between attempts we wait for an exponentially growing backoff,
+/// optionally randomized by `jitter` so many assets failing at once don't all
+/// hit the server again at the same instant.
+///
+/// If a download breaks off halfway and the server supports range requests,
+/// the retry continues from the last received byte.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one. `1` disables retries.
+    pub max_attempts: u32,
+    /// How long to wait before the first retry.
+    pub initial_backoff: Duration,
+    /// Upper bound for the wait between two attempts.
+    pub max_backoff: Duration,
+    /// Factor the backoff is multiplied with after each failed attempt.
+    pub multiplier: f32,
+    /// Fraction of the backoff (between `0.0` and `1.0`) that is randomized.
+    pub jitter: f32,
+    /// Whether to resume interrupted downloads with a `Range` request instead
+    /// of starting over.
+    pub resume: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.2,
+            resume: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Whether another attempt should be made after `attempt` attempts failed.
+    pub(crate) fn should_retry(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+
+    /// The time to wait after `attempt` (starting at 1) attempts failed.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1) as i32;
+        let backoff = self.initial_backoff.as_secs_f32() * self.multiplier.powi(exponent);
+        let backoff = backoff.min(self.max_backoff.as_secs_f32());
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = 1.0 - jitter + 2.0 * jitter * fastrand::f32();
+        Duration::from_secs_f32((backoff * factor).max(0.0))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(duration: Duration) {
+    async_std::task::sleep(duration).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(duration: Duration) {
+    use wasm_bindgen::JsCast;
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        let scheduled = crate::global_scope::GlobalScope::get().is_ok_and(|global| {
+            global
+                .set_timeout(resolve.unchecked_ref(), duration.as_millis() as i32)
+                .is_ok()
+        });
+        // Without a timer, retry right away rather than panicking
+        if !scheduled {
+            let _ = resolve.call0(&wasm_bindgen::JsValue::NULL);
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
@@ -0,0 +1,161 @@
+/// Rewrites remote urls before they're requested, e.g. to append build hashes,
+/// switch regions or redirect to a staging host.
+///
+/// Registered in [`WebAssetPlugin::url_rewriters`](crate::WebAssetPlugin::url_rewriters),
+/// and applied in order after aliases are expanded. Closures taking a `&str` and
+/// returning an `Option<String>` implement this.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetPlugin;
+/// # use std::sync::Arc;
+/// let mut plugin = WebAssetPlugin::default();
+/// plugin.url_rewriters.push(Arc::new(|url: &str| {
+///     let staging = url.strip_prefix("https://assets.mygame.com/")?;
+///     Some(format!("https://staging.mygame.com/{staging}"))
+/// }));
+/// App::new().add_plugin(plugin);
+/// ```
+pub trait UrlRewriter: Send + Sync + 'static {
+    /// The url to request instead of `url`, or `None` to leave it as it is
+    fn rewrite(&self, url: &str) -> Option<String>;
+}
+
+impl<F> UrlRewriter for F
+where
+    F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+{
+    fn rewrite(&self, url: &str) -> Option<String> {
+        self(url)
+    }
+}
+
+/// Adds the [`WebAssetPlugin::cache_bust`](crate::WebAssetPlugin::cache_bust) query
+/// parameter to http urls
+pub(crate) struct CacheBust {
+    pub(crate) name: String,
+    pub(crate) value: String,
+}
+
+impl UrlRewriter for CacheBust {
+    fn rewrite(&self, url: &str) -> Option<String> {
+        let mut url = url::Url::parse(url).ok()?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return None;
+        }
+        url.query_pairs_mut().append_pair(&self.name, &self.value);
+        Some(url.into())
+    }
+}
+
+/// `url` without the copy of its extension after the query, which is only there so
+/// Bevy finds the asset loader, e.g. `https://cdn/tex.png?sig=abc.png` requests
+/// `https://cdn/tex.png?sig=abc`. Bevy picks loaders by what follows a dot in the file
+/// name, so it would look for one for `png?sig=abc` otherwise.
+///
+/// Urls without an extension get theirs in a last query parameter starting with a
+/// dot, e.g. `https://cdn/assets/12345?.png`, which is removed as well, like the one
+/// [`WebAssetPath`](crate::WebAssetPath) adds. Plain `https://cdn/tex.png?sig=abc`
+/// paths have no extension Bevy can find, so there's nothing to strip.
+pub(crate) fn strip_loader_extension(url: &str) -> Option<String> {
+    let (path, query) = url.split_once('?')?;
+    let marker = query.rfind(['?', '&']).map_or(0, |i| i + 1);
+    let start = match query[marker..].strip_prefix('.') {
+        Some(extension) if !extension.contains('=') => marker,
+        _ => {
+            let name = path.rsplit('/').next()?;
+            let (_, extension) = name.rsplit_once('.')?;
+            let start = query.len().checked_sub(extension.len() + 1)?;
+            let suffix = query.get(start..)?.strip_prefix('.')?;
+            if !suffix.eq_ignore_ascii_case(extension) {
+                return None;
+            }
+            start
+        }
+    };
+    let query = query[..start].trim_end_matches('&');
+    Some(match query.is_empty() {
+        true => path.to_string(),
+        false => format!("{path}?{query}"),
+    })
+}
+
+/// `url` as it's sent, if that's different: percent-encoded, e.g. spaces as `%20`,
+/// with international domain names in punycode and the scheme and host lowercase.
+/// Only http urls are normalized, the other schemes encode their paths when they're
+/// turned into requests.
+pub(crate) fn normalize(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url).ok()?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.as_str() == url {
+        return None;
+    }
+    Some(parsed.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_loader_extensions() {
+        let strip = |url| strip_loader_extension(url);
+        assert_eq!(
+            strip("https://cdn/tex.png?sig=abc.png").as_deref(),
+            Some("https://cdn/tex.png?sig=abc")
+        );
+        assert_eq!(
+            strip("https://cdn/tex.PNG?sig=abc.png").as_deref(),
+            Some("https://cdn/tex.PNG?sig=abc")
+        );
+        assert_eq!(
+            strip("https://cdn/assets/12345?.png").as_deref(),
+            Some("https://cdn/assets/12345")
+        );
+        assert_eq!(
+            strip("https://cdn/assets/12345?v=2&.png").as_deref(),
+            Some("https://cdn/assets/12345?v=2")
+        );
+        assert_eq!(
+            strip("https://cdn/tex.png?.png").as_deref(),
+            Some("https://cdn/tex.png")
+        );
+        assert_eq!(strip("https://cdn/tex.png?sig=abc"), None);
+        assert_eq!(strip("https://cdn/tex.png?sig=abc.jpg"), None);
+        assert_eq!(strip("https://cdn/tex.png"), None);
+        assert_eq!(strip("https://cdn/assets/12345?v=.png"), None);
+    }
+
+    #[test]
+    fn normalizes_http_urls() {
+        assert_eq!(
+            normalize("HTTPS://CDN.Example.com/my hero.png").as_deref(),
+            Some("https://cdn.example.com/my%20hero.png")
+        );
+        assert_eq!(
+            normalize("https://bücher.example/ü.png").as_deref(),
+            Some("https://xn--bcher-kva.example/%C3%BC.png")
+        );
+        assert_eq!(normalize("https://cdn.example.com/hero.png"), None);
+        assert_eq!(normalize("https://cdn.example.com/my%20hero.png"), None);
+        assert_eq!(normalize("s3://bucket/my hero.png"), None);
+        assert_eq!(normalize("not a url"), None);
+    }
+
+    #[test]
+    fn busts_caches_of_http_urls() {
+        let bust = CacheBust {
+            name: "v".to_string(),
+            value: "1.2 beta".to_string(),
+        };
+        assert_eq!(
+            bust.rewrite("https://cdn/hero.png").as_deref(),
+            Some("https://cdn/hero.png?v=1.2+beta")
+        );
+        assert_eq!(
+            bust.rewrite("http://cdn/hero.png?sig=abc#frag").as_deref(),
+            Some("http://cdn/hero.png?sig=abc&v=1.2+beta#frag")
+        );
+        assert_eq!(bust.rewrite("s3://bucket/hero.png"), None);
+        assert_eq!(bust.rewrite("hero.png"), None);
+    }
+}
@@ -0,0 +1,69 @@
+use bevy::{
+    asset::{AssetIoError, FileType, Metadata},
+    utils::BoxedFuture,
+};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Loads assets for a custom url scheme, such as `mod://` or `steam://`.
+///
+/// Registered in [`WebAssetPlugin::scheme_handlers`](crate::WebAssetPlugin::scheme_handlers),
+/// and consulted before any of the built in schemes, so a handler for `https`
+/// replaces the default one.
+///
+/// ```no_run
+/// # use bevy::{asset::AssetIoError, prelude::*, utils::BoxedFuture};
+/// # use bevy_web_asset::{SchemeHandler, WebAssetPlugin};
+/// # use std::{path::Path, sync::Arc};
+/// struct ModHandler;
+///
+/// impl SchemeHandler for ModHandler {
+///     fn load<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+///         Box::pin(async move {
+///             let path = path.to_str().unwrap().trim_start_matches("mod://");
+///             Ok(std::fs::read(Path::new("mods").join(path))?)
+///         })
+///     }
+/// }
+///
+/// let mut plugin = WebAssetPlugin::default();
+/// plugin.scheme_handlers.insert("mod".to_string(), Arc::new(ModHandler));
+/// App::new().add_plugin(plugin);
+/// ```
+pub trait SchemeHandler: Send + Sync + 'static {
+    /// Loads the asset at `path`, e.g. `mod://textures/hero.png`
+    fn load<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>>;
+
+    /// Lists the directory at `path`, unsupported by default
+    fn read_directory(
+        &self,
+        path: &Path,
+    ) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        Err(AssetIoError::Io(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("can't list {}", path.display()),
+        )))
+    }
+
+    /// Whether `path` is a directory, `false` by default
+    fn is_dir(&self, _path: &Path) -> bool {
+        false
+    }
+
+    /// Metadata of `path`, a file by default
+    fn get_metadata(&self, _path: &Path) -> Result<Metadata, AssetIoError> {
+        Ok(Metadata::new(FileType::File))
+    }
+}
+
+/// The scheme of `path`, lowercased, e.g. `mod` for `mod://textures/hero.png`
+pub(crate) fn scheme(path: &Path) -> Option<String> {
+    let (scheme, _) = path.to_str()?.split_once("://")?;
+    let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then(|| scheme.to_ascii_lowercase())
+}
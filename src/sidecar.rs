@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use crate::{cache::CacheStrategy, http_client::Method, HeaderMap};
+
+/// How the asset a sidecar belongs to is cached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CachePolicy {
+    Strategy(CacheStrategy),
+    /// Never read from or written to the cache
+    NoStore,
+}
+
+/// Request options of a single asset, read from a sidecar file next to it, e.g.
+/// `hero.png.http.json`:
+///
+/// ```json
+/// {
+///     "method": "POST",
+///     "body": "{ \"format\": \"binary\" }",
+///     "headers": { "X-Api-Key": "abc" },
+///     "timeout": 5.0,
+///     "cache": "stale-while-revalidate"
+/// }
+/// ```
+///
+/// Every field is optional, and merged over the matching
+/// [`AssetRequest`](crate::AssetRequest), if any. `timeout` is in seconds, for the request as a whole, and
+/// `cache` is one of `revalidate`, `stale-while-revalidate` or `no-store`.
+#[derive(Debug, Default)]
+pub(crate) struct Sidecar {
+    pub(crate) method: Option<Method>,
+    pub(crate) body: Option<Vec<u8>>,
+    /// Sent in addition to the configured headers
+    pub(crate) headers: HeaderMap,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) cache: Option<CachePolicy>,
+}
+
+impl Sidecar {
+    pub(crate) fn parse(json: &[u8]) -> Result<Self, String> {
+        let json: serde_json::Value =
+            serde_json::from_slice(json).map_err(|err| format!("invalid sidecar: {err}"))?;
+        let json = json
+            .as_object()
+            .ok_or("invalid sidecar: expected an object")?;
+        let mut sidecar = Self::default();
+        if let Some(method) = json.get("method") {
+            let method = method.as_str().ok_or("invalid sidecar method")?;
+            sidecar.method = Some(match method.to_ascii_uppercase().as_str() {
+                "GET" => Method::Get,
+                "HEAD" => Method::Head,
+                "POST" => Method::Post,
+                "PUT" => Method::Put,
+                "DELETE" => Method::Delete,
+                other => Method::Other(other.to_string()),
+            });
+        }
+        if let Some(body) = json.get("body") {
+            let body = body.as_str().ok_or("invalid sidecar body")?;
+            sidecar.body = Some(body.as_bytes().to_vec());
+        }
+        if let Some(headers) = json.get("headers") {
+            let headers = headers.as_object().ok_or("invalid sidecar headers")?;
+            for (name, value) in headers {
+                let value = value.as_str().ok_or("invalid sidecar header value")?;
+                sidecar
+                    .headers
+                    .insert(name, value)
+                    .map_err(|err| format!("invalid sidecar header: {err}"))?;
+            }
+        }
+        if let Some(timeout) = json.get("timeout") {
+            let timeout = timeout
+                .as_f64()
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .ok_or("invalid sidecar timeout")?;
+            sidecar.timeout = Some(timeout);
+        }
+        if let Some(cache) = json.get("cache") {
+            sidecar.cache = Some(match cache.as_str() {
+                Some("revalidate") => CachePolicy::Strategy(CacheStrategy::Revalidate),
+                Some("stale-while-revalidate") => {
+                    CachePolicy::Strategy(CacheStrategy::StaleWhileRevalidate)
+                }
+                Some("no-store") => CachePolicy::NoStore,
+                _ => return Err("invalid sidecar cache policy".to_string()),
+            });
+        }
+        Ok(sidecar)
+    }
+}
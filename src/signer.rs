@@ -0,0 +1,37 @@
+use crate::HttpRequest;
+
+/// Signs requests right before they're sent, e.g. with AWS SigV4 for a custom
+/// endpoint, an HMAC in the query, or a CDN's token scheme.
+///
+/// Registered in [`WebAssetPlugin::request_signers`](crate::WebAssetPlugin::request_signers),
+/// and called in order for every attempt, after the
+/// [request interceptors](crate::RequestInterceptor) and the built-in bucket signatures,
+/// so nothing changes the request afterwards. `unix_time` is the time of the attempt in
+/// seconds since the unix epoch, which is also available in browsers. Closures taking a
+/// `&mut HttpRequest` and a `u64` implement this.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{HttpRequest, WebAssetPlugin};
+/// # use std::sync::Arc;
+/// let mut plugin = WebAssetPlugin::default();
+/// plugin.request_signers.push(Arc::new(|request: &mut HttpRequest, unix_time: u64| {
+///     let expires = unix_time + 300;
+///     let token = format!("{:x}", request.url.len() as u64 ^ expires); // Compute a real HMAC here
+///     request.url = format!("{}?expires={expires}&token={token}", request.url);
+/// }));
+/// App::new().add_plugin(plugin);
+/// ```
+pub trait RequestSigner: Send + Sync + 'static {
+    /// Signs `request`, sent at `unix_time`
+    fn sign(&self, request: &mut HttpRequest, unix_time: u64);
+}
+
+impl<F> RequestSigner for F
+where
+    F: Fn(&mut HttpRequest, u64) + Send + Sync + 'static,
+{
+    fn sign(&self, request: &mut HttpRequest, unix_time: u64) {
+        self(request, unix_time)
+    }
+}
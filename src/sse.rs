@@ -0,0 +1,192 @@
+use bevy::{log::warn, prelude::*, tasks::IoTaskPool};
+use std::time::Duration;
+
+use crate::{
+    http_client::{HttpClient, HttpRequest},
+    retry::sleep,
+    WebAssetIo,
+};
+
+/// Reconnect delay used until the server sends a `retry:` field
+const DEFAULT_RECONNECT_DELAY: Duration = Duration::from_secs(3);
+
+/// Parses a `text/event-stream` body as it arrives
+#[derive(Default)]
+pub(crate) struct EventStream {
+    /// The incomplete line at the end of the last chunk
+    line: Vec<u8>,
+    /// A trailing `\r` at the end of the last chunk, where a `\n` may follow
+    pending_cr: bool,
+    data: Vec<String>,
+    /// `id:` of the last event, sent as `Last-Event-ID` when reconnecting
+    pub(crate) last_event_id: Option<String>,
+    /// Reconnect delay requested by the server
+    pub(crate) retry: Option<Duration>,
+}
+
+impl EventStream {
+    /// Forgets the partial event of a dropped connection
+    fn reconnected(&mut self) {
+        self.line.clear();
+        self.pending_cr = false;
+        self.data.clear();
+    }
+
+    /// Feeds a chunk of the body, returning the data of the events it completed
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> Vec<String> {
+        let mut events = Vec::new();
+        for &byte in chunk {
+            let pending_cr = std::mem::take(&mut self.pending_cr);
+            match byte {
+                b'\n' if pending_cr => {}
+                b'\n' | b'\r' => {
+                    self.pending_cr = byte == b'\r';
+                    let line = std::mem::take(&mut self.line);
+                    events.extend(self.line_done(&String::from_utf8_lossy(&line)));
+                }
+                _ => self.line.push(byte),
+            }
+        }
+        events
+    }
+
+    fn line_done(&mut self, line: &str) -> Option<String> {
+        if line.is_empty() {
+            if self.data.is_empty() {
+                return None;
+            }
+            return Some(std::mem::take(&mut self.data).join("\n"));
+        }
+        if line.starts_with(':') {
+            // Comment, usually a keep-alive
+            return None;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "data" => self.data.push(value.to_string()),
+            "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+            "retry" => {
+                if let Ok(millis) = value.parse() {
+                    self.retry = Some(Duration::from_millis(millis));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+impl WebAssetIo {
+    /// Reads the event stream at `url` until the connection closes
+    async fn read_events(
+        &self,
+        url: &str,
+        events: &mut EventStream,
+        asset_server: &AssetServer,
+    ) -> Result<(), String> {
+        events.reconnected();
+        let mut request = HttpRequest::get(url);
+        if let Ok(parsed) = url::Url::parse(url) {
+            request.headers = self.headers.for_url(&parsed);
+        }
+        let _ = request.headers.insert("Accept", "text/event-stream");
+        if let Some(id) = &events.last_event_id {
+            let _ = request.headers.insert("Last-Event-ID", id);
+        }
+        if let Some(auth) = self.auth.get() {
+            if let Some(authorization) = auth.authorization(url).await {
+                let _ = request.headers.insert("Authorization", authorization);
+            }
+        }
+        for interceptor in &self.request_interceptors {
+            interceptor.intercept(&mut request);
+        }
+
+        let mut response = self
+            .client
+            .send(request)
+            .await
+            .map_err(|err| err.to_string())?;
+        if !response.is_success() {
+            return Err(format!("status {}", response.status));
+        }
+        while let Some(chunk) = response
+            .body
+            .next_chunk()
+            .await
+            .map_err(|err| err.to_string())?
+        {
+            for data in events.feed(&chunk) {
+                self.reload_notified(asset_server, &data);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Subscribes to the Server-Sent Events at `url`, reconnecting whenever the connection drops
+pub(crate) fn subscribe(url: String) -> impl Fn(Res<AssetServer>) {
+    move |asset_server: Res<AssetServer>| {
+        let url = url.clone();
+        let asset_server = asset_server.clone();
+        IoTaskPool::get()
+            .spawn(async move {
+                let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+                    return;
+                };
+                let mut events = EventStream::default();
+                loop {
+                    match asset_io.read_events(&url, &mut events, &asset_server).await {
+                        Ok(()) => info!("Change notifications from {url} ended, reconnecting"),
+                        Err(err) => warn!("Change notifications from {url} failed: {err}"),
+                    }
+                    sleep(events.retry.unwrap_or(DEFAULT_RECONNECT_DELAY)).await;
+                }
+            })
+            .detach();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_events() {
+        let mut stream = EventStream::default();
+        let events = stream.feed(b"data: a.png\n\n: keep-alive\n\ndata:b.png\ndata: c.png\n\n");
+        assert_eq!(events, ["a.png", "b.png\nc.png"]);
+    }
+
+    #[test]
+    fn parses_events_split_across_chunks() {
+        let mut stream = EventStream::default();
+        assert!(stream.feed(b"data: hero").is_empty());
+        assert!(stream.feed(b".png\r").is_empty());
+        assert_eq!(stream.feed(b"\n\r\n"), ["hero.png"]);
+        assert_eq!(stream.feed(b"data: a\r\rdata: b\n\n"), ["a", "b"]);
+    }
+
+    #[test]
+    fn keeps_last_event_id_and_retry() {
+        let mut stream = EventStream::default();
+        let events = stream.feed(b"id: 7\nretry: 500\nevent: change\ndata: a.png\n\nid: \0\n\n");
+        assert_eq!(events, ["a.png"]);
+        assert_eq!(stream.last_event_id.as_deref(), Some("7"));
+        assert_eq!(stream.retry, Some(Duration::from_millis(500)));
+
+        stream.feed(b"retry: soon\nid\n\n");
+        assert_eq!(stream.last_event_id.as_deref(), Some(""));
+        assert_eq!(stream.retry, Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn drops_partial_event_when_reconnecting() {
+        let mut stream = EventStream::default();
+        stream.feed(b"id: 1\ndata: a.png\n\ndata: b.p");
+        stream.reconnected();
+        assert_eq!(stream.feed(b"data: c.png\n\n"), ["c.png"]);
+        assert_eq!(stream.last_event_id.as_deref(), Some("1"));
+    }
+}
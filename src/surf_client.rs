@@ -0,0 +1,258 @@
+use async_std::io::ReadExt;
+use bevy::utils::{BoxedFuture, Duration, Instant};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
+};
+use surf::http;
+
+use crate::{
+    dns,
+    http_client::{HttpClient, HttpError, HttpRequest, HttpResponse, ResponseBody},
+    proxy, DnsConfig, HeaderMap, ProxyConfig, TlsConfig, TlsConfigError,
+};
+
+/// Maximum size of the chunks response bodies are read in
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// How the default native client keeps connections open between requests, set
+/// with [`WebAssetPlugin::connection_pool`](crate::WebAssetPlugin::connection_pool),
+/// so repeated requests to the same host skip the TCP and TLS handshakes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolConfig {
+    /// Whether connections are kept open after a response for the next request to
+    /// the host. Defaults to `true`.
+    pub keep_alive: bool,
+    /// Connections per host, open at the same time. Further requests to the host
+    /// wait for one of them. Defaults to 50.
+    pub max_connections_per_host: usize,
+    /// Kept connections are closed once nothing was sent for this long, rather than
+    /// reusing connections the server may have closed already. `None`, the default,
+    /// keeps them until the server closes them.
+    pub idle_timeout: Option<Duration>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            keep_alive: true,
+            max_connections_per_host: 50,
+            idle_timeout: None,
+        }
+    }
+}
+
+/// The default [`HttpClient`] on native, built on [surf](https://docs.rs/surf)
+#[derive(Clone, Debug, Default)]
+pub struct SurfClient {
+    client: Arc<RwLock<surf::Client>>,
+    /// Set when a body was dropped halfway, leaving the rest of it unread on a pooled
+    /// connection, which surf would hand to the next request as is
+    dirty: Arc<AtomicBool>,
+    /// When the pool was last used, if idle connections are closed
+    last_used: Arc<Mutex<Option<Instant>>>,
+    idle_timeout: Option<Duration>,
+    proxy: ProxyConfig,
+    dns: DnsConfig,
+    tls: TlsConnector,
+}
+
+/// `async_tls::TlsConnector` doesn't implement `Clone` or `Debug`
+#[derive(Clone, Default)]
+struct TlsConnector(Arc<async_tls::TlsConnector>);
+
+impl std::fmt::Debug for TlsConnector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TlsConnector")
+    }
+}
+
+impl SurfClient {
+    /// Uses `client` to send requests.
+    ///
+    /// Once a download is cancelled halfway, e.g. by a timeout, the client is rebuilt
+    /// from its [`Config`](surf::Config) for a fresh connection pool, which drops its
+    /// middleware.
+    pub fn new(client: surf::Client) -> Self {
+        Self {
+            client: Arc::new(RwLock::new(client)),
+            ..Default::default()
+        }
+    }
+
+    /// The client to send the next request with
+    fn client(&self) -> surf::Client {
+        if let Some(idle_timeout) = self.idle_timeout {
+            let last_used = self.last_used.lock().unwrap().replace(Instant::now());
+            if last_used.is_some_and(|last_used| last_used.elapsed() > idle_timeout) {
+                self.dirty.store(true, Ordering::Relaxed);
+            }
+        }
+        if self.dirty.swap(false, Ordering::Relaxed) {
+            let mut client = self.client.write().unwrap();
+            *client = from_config(client.config().clone());
+        }
+        self.client.read().unwrap().clone()
+    }
+
+    /// Sends requests through the proxies in `proxy`.
+    ///
+    /// Proxied requests don't go through the surf client, and use a new
+    /// connection for every request.
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Resolves host names with `dns`.
+    ///
+    /// Requests to hosts it changes anything for don't go through the surf client,
+    /// and use a new connection for every request.
+    pub fn with_dns(mut self, dns: DnsConfig) -> Self {
+        self.dns = dns;
+        self
+    }
+
+    /// Keeps connections open between requests as configured by `pool`.
+    ///
+    /// This rebuilds the surf client from its [`Config`](surf::Config), so
+    /// middleware added to a client passed to [`SurfClient::new`] is dropped.
+    ///
+    /// # Panics
+    ///
+    /// If `max_connections_per_host` is `0`.
+    pub fn with_pool(mut self, pool: &PoolConfig) -> Self {
+        assert!(
+            pool.max_connections_per_host > 0,
+            "max_connections_per_host must be at least 1"
+        );
+        let config = self
+            .client
+            .read()
+            .unwrap()
+            .config()
+            .clone()
+            .set_http_keep_alive(pool.keep_alive)
+            .set_max_connections_per_host(pool.max_connections_per_host);
+        self.client = Arc::new(RwLock::new(from_config(config)));
+        self.idle_timeout = pool.idle_timeout;
+        self
+    }
+
+    /// Uses `tls` for https connections, including those tunneled through a proxy.
+    ///
+    /// This rebuilds the surf client from its [`Config`](surf::Config), so
+    /// middleware added to a client passed to [`SurfClient::new`] is dropped.
+    pub fn with_tls(mut self, tls: &TlsConfig) -> Result<Self, TlsConfigError> {
+        let config = tls.to_rustls()?;
+        let client_config = self.client.read().unwrap().config().clone();
+        let client = from_config(client_config.set_tls_config(Some(config.clone())));
+        self.client = Arc::new(RwLock::new(client));
+        self.tls = TlsConnector(Arc::new(config.into()));
+        Ok(self)
+    }
+}
+
+fn from_config(config: surf::Config) -> surf::Client {
+    match surf::Client::try_from(config) {
+        Ok(client) => client,
+        Err(infallible) => match infallible {},
+    }
+}
+
+struct SurfBody {
+    response: surf::Response,
+    finished: bool,
+    /// The [`SurfClient::dirty`] flag of the client
+    dirty: Arc<AtomicBool>,
+    /// The [`SurfClient::last_used`] time of the client
+    last_used: Arc<Mutex<Option<Instant>>>,
+}
+
+impl ResponseBody for SurfBody {
+    fn next_chunk(&mut self) -> BoxedFuture<'_, Result<Option<Vec<u8>>, HttpError>> {
+        Box::pin(async move {
+            let mut chunk = vec![0; CHUNK_SIZE];
+            let read = self.response.read(&mut chunk).await?;
+            if read == 0 {
+                self.finished = true;
+                return Ok(None);
+            }
+            chunk.truncate(read);
+            Ok(Some(chunk))
+        })
+    }
+}
+
+impl Drop for SurfBody {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        // The connection only goes idle once the body was read
+        if let Some(last_used) = self.last_used.lock().unwrap().as_mut() {
+            *last_used = Instant::now();
+        }
+    }
+}
+
+fn to_http_request(request: HttpRequest) -> Result<http::Request, HttpError> {
+    let method = http::Method::from_str(request.method.as_str())?;
+    let url = surf::Url::parse(&request.url)?;
+    let mut http_request = http::Request::new(method, url);
+    for (name, value) in request.headers.iter() {
+        // Names are validated by `HeaderMap`, so this can't panic
+        http_request.append_header(name, value);
+    }
+    if let Some(body) = request.body {
+        http_request.set_body(body);
+    }
+    Ok(http_request)
+}
+
+impl HttpClient for SurfClient {
+    fn send(&self, request: HttpRequest) -> BoxedFuture<'_, Result<HttpResponse, HttpError>> {
+        Box::pin(async move {
+            let request = to_http_request(request)?;
+            let head = request.method() == http::Method::Head;
+
+            let (response, pooled): (surf::Response, _) = match self.proxy.for_url(request.url()) {
+                Some(proxy) => (
+                    proxy::send(proxy, request, &self.tls.0).await?.into(),
+                    false,
+                ),
+                None if self.dns.applies_to(request.url()) => (
+                    dns::send(&self.dns, request, &self.tls.0).await?.into(),
+                    false,
+                ),
+                None => (self.client().send(request).await?, true),
+            };
+            // Unpooled requests have their own connection, and these have nothing to read
+            let finished = !pooled
+                || head
+                || response.len() == Some(0)
+                || matches!(response.status() as u16, 204 | 304);
+
+            let mut headers = HeaderMap::new();
+            for (name, values) in response.iter() {
+                for value in values {
+                    let _ = headers.append(name.as_str(), value.as_str());
+                }
+            }
+
+            Ok(HttpResponse {
+                status: response.status().into(),
+                headers,
+                body: Box::new(SurfBody {
+                    response,
+                    finished,
+                    dirty: self.dirty.clone(),
+                    last_used: self.last_used.clone(),
+                }),
+            })
+        })
+    }
+}
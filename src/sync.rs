@@ -0,0 +1,523 @@
+use bevy::{
+    log::warn,
+    prelude::*,
+    tasks::{IoTaskPool, Task},
+};
+use serde_json::Value;
+use std::sync::{Arc, RwLock};
+
+use crate::{
+    cache::{CacheEntry, Fetched},
+    delta,
+    http_client::Method,
+    integrity::{parse_digest, sha256},
+    priority::BACKGROUND_PRIORITY,
+    web_asset_io::{RequestOptions, MAX_MANIFEST_SIZE},
+    WebAssetError, WebAssetIo,
+};
+
+/// Keeps the cache in sync with a remote manifest of files, like the content updater of
+/// a launcher. Add it after [`WebAssetPlugin`](crate::WebAssetPlugin), with a
+/// [cache](crate::WebAssetPlugin::cache) configured.
+///
+/// The manifest lists the files, relative to the manifest url or absolute, with their
+/// sha256 and size if known:
+///
+/// ```json
+/// {
+///     "files": [
+///         { "path": "textures/hero.png", "sha256": "9f86d0...", "size": 52012 },
+///         { "path": "music/theme.ogg" }
+///     ]
+/// }
+/// ```
+///
+/// Files missing from the cache or not matching their sha256 are downloaded, the others
+/// are left alone. Files without a sha256 are revalidated with the server.
+///
+/// Files can list bsdiff patches from older versions, by the sha256 of the older
+/// version. When the cached copy has one, the patch is downloaded and applied to it
+/// instead of downloading the whole file, see
+/// [the patch format](https://crates.io/crates/bsdiff):
+///
+/// ```json
+/// { "path": "level.pak", "sha256": "2c26b4...", "patches": [
+///     { "from": "fcde2b...", "path": "level.pak.v1.patch" }
+/// ] }
+/// ```
+///
+/// The sync starts with the app, and its progress is in the [`WebAssetSync`] resource:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{CacheConfig, WebAssetPlugin, WebAssetSync, WebAssetSyncPlugin};
+/// App::new()
+///     .add_plugin(WebAssetPlugin {
+///         cache: Some(CacheConfig::default()),
+///         ..default()
+///     })
+///     .add_plugin(WebAssetSyncPlugin {
+///         manifest_url: "https://example.com/content/manifest.json".to_string(),
+///     })
+///     .add_system(update_screen);
+///
+/// fn update_screen(sync: Res<WebAssetSync>) {
+///     let status = sync.status();
+///     info!("{}/{} files", status.files_done, status.files_total);
+///     if status.is_done() {
+///         // Start the game
+///     }
+/// }
+/// ```
+pub struct WebAssetSyncPlugin {
+    /// Where the manifest is downloaded from
+    pub manifest_url: String,
+}
+
+impl Plugin for WebAssetSyncPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WebAssetSync {
+            manifest_url: self.manifest_url.clone(),
+            status: default(),
+        })
+        .add_startup_system(start_sync);
+    }
+}
+
+fn start_sync(sync: Res<WebAssetSync>, asset_server: Res<AssetServer>) {
+    sync.start(&asset_server);
+}
+
+/// The progress of [`WebAssetSyncPlugin`], inserted as a resource
+#[derive(Resource, Clone)]
+pub struct WebAssetSync {
+    manifest_url: String,
+    status: Arc<RwLock<SyncStatus>>,
+}
+
+impl WebAssetSync {
+    /// How far the sync got
+    pub fn status(&self) -> SyncStatus {
+        self.status.read().unwrap().clone()
+    }
+
+    /// Syncs again in the background, e.g. after a failed sync, unless a sync is
+    /// already running
+    pub fn start(&self, asset_server: &AssetServer) {
+        {
+            let mut status = self.status.write().unwrap();
+            if status.state == SyncState::Syncing {
+                return;
+            }
+            *status = SyncStatus {
+                state: SyncState::Syncing,
+                ..default()
+            };
+        }
+        let sync = self.clone();
+        let asset_server = asset_server.clone();
+        IoTaskPool::get()
+            .spawn(async move {
+                let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+                    sync.finish(Some("the asset io isn't a WebAssetIo".to_string()));
+                    return;
+                };
+                let result = sync.run(&asset_server, asset_io).await;
+                sync.finish(result.err());
+            })
+            .detach();
+    }
+
+    fn finish(&self, error: Option<String>) {
+        let mut status = self.status.write().unwrap();
+        status.state = match error {
+            Some(error) => SyncState::Failed(error),
+            None if status.failed.is_empty() => SyncState::Done,
+            None => SyncState::Failed(format!(
+                "{} of {} files failed to sync",
+                status.failed.len(),
+                status.files_total
+            )),
+        };
+    }
+
+    async fn run(&self, asset_server: &AssetServer, asset_io: &WebAssetIo) -> Result<(), String> {
+        if asset_io.cache.is_none() {
+            return Err("syncing needs WebAssetPlugin::cache".to_string());
+        }
+        let options = RequestOptions {
+            max_size: Some(MAX_MANIFEST_SIZE),
+            ..default()
+        };
+        let manifest = asset_io
+            .fetch_with_options(&self.manifest_url, &options)
+            .await
+            .map_err(|err| err.to_string())?;
+        let files = parse(&self.manifest_url, &manifest)?;
+        {
+            let mut status = self.status.write().unwrap();
+            status.files_total = files.len();
+            status.bytes_total = files.iter().filter_map(|file| file.size).sum();
+        }
+        // Downloads share the request slots of the asset io, so they're started at once
+        let tasks: Vec<Task<_>> = files
+            .into_iter()
+            .map(|file| {
+                let asset_server = asset_server.clone();
+                IoTaskPool::get().spawn(async move {
+                    let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>()
+                    else {
+                        let err = WebAssetError::Transport {
+                            url: file.url.clone(),
+                            message: "the asset io isn't a WebAssetIo".to_string(),
+                        };
+                        return (file, Err(err));
+                    };
+                    let result = asset_io.sync_file(&file).await;
+                    (file, result)
+                })
+            })
+            .collect();
+        for task in tasks {
+            let (file, result) = task.await;
+            let mut status = self.status.write().unwrap();
+            status.files_done += 1;
+            status.bytes_done += file.size.unwrap_or_default();
+            if let Err(err) = result {
+                warn!("Failed to sync {}: {err}", file.url);
+                status.failed.push((file.url, err));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How far a [`WebAssetSync`] got
+#[derive(Debug, Clone, Default)]
+pub struct SyncStatus {
+    /// Whether the sync is running, finished, or failed
+    pub state: SyncState,
+    /// The number of files in the manifest
+    pub files_total: usize,
+    /// The number of files checked or downloaded so far, including failed ones
+    pub files_done: usize,
+    /// The total size of the files with a size in the manifest
+    pub bytes_total: u64,
+    /// The size in the manifest of the files done so far
+    pub bytes_done: u64,
+    /// The urls of files that failed to sync, and why
+    pub failed: Vec<(String, WebAssetError)>,
+}
+
+impl SyncStatus {
+    /// Whether every file is in the cache and up to date
+    pub fn is_done(&self) -> bool {
+        self.state == SyncState::Done
+    }
+
+    /// How much of the manifest has been synced, between `0.0` and `1.0`, by size if
+    /// the manifest lists sizes, by the number of files otherwise
+    pub fn fraction(&self) -> f32 {
+        if self.bytes_total > 0 {
+            (self.bytes_done as f64 / self.bytes_total as f64).min(1.0) as f32
+        } else if self.files_total > 0 {
+            self.files_done as f32 / self.files_total as f32
+        } else {
+            match self.state {
+                SyncState::Done => 1.0,
+                _ => 0.0,
+            }
+        }
+    }
+}
+
+/// What a [`WebAssetSync`] is doing
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SyncState {
+    /// The sync hasn't started yet
+    #[default]
+    Pending,
+    /// Files are being checked and downloaded
+    Syncing,
+    /// Every file is in the cache and up to date
+    Done,
+    /// The manifest couldn't be downloaded, or some files failed, see
+    /// [`SyncStatus::failed`]
+    Failed(String),
+}
+
+/// A file listed in a sync manifest
+struct SyncFile {
+    url: String,
+    /// Hex encoded
+    sha256: Option<String>,
+    size: Option<u64>,
+    patches: Vec<SyncPatch>,
+}
+
+/// A patch turning an older version of a [`SyncFile`] into the current one
+struct SyncPatch {
+    /// Hex encoded sha256 of the older version
+    from: String,
+    url: String,
+}
+
+fn parse(manifest_url: &str, manifest: &[u8]) -> Result<Vec<SyncFile>, String> {
+    let json: Value =
+        serde_json::from_slice(manifest).map_err(|err| format!("invalid sync manifest: {err}"))?;
+    let files = json
+        .get("files")
+        .and_then(Value::as_array)
+        .ok_or("sync manifest has no files array")?;
+    let base = url::Url::parse(manifest_url).map_err(|err| err.to_string())?;
+    files
+        .iter()
+        .map(|file| {
+            let path = file
+                .get("path")
+                .and_then(Value::as_str)
+                .ok_or("sync manifest files need a path")?;
+            let url = base
+                .join(path)
+                .map_err(|err| format!("invalid sync manifest path {path:?}: {err}"))?;
+            let sha256 = match file.get("sha256").and_then(Value::as_str) {
+                Some(digest) => Some(
+                    parse_digest(digest)
+                        .ok_or_else(|| format!("invalid sha256 of {path:?}: {digest}"))?,
+                ),
+                None => None,
+            };
+            let patches = match file.get("patches") {
+                Some(patches) => patches
+                    .as_array()
+                    .ok_or_else(|| format!("invalid patches of {path:?}"))?
+                    .iter()
+                    .map(|patch| parse_patch(&url, patch))
+                    .collect::<Result<_, _>>()?,
+                None => Vec::new(),
+            };
+            Ok(SyncFile {
+                url: url.to_string(),
+                sha256,
+                size: file.get("size").and_then(Value::as_u64),
+                patches,
+            })
+        })
+        .collect()
+}
+
+/// Parses a patch of the file at `url`, relative to it
+fn parse_patch(url: &url::Url, patch: &Value) -> Result<SyncPatch, String> {
+    let invalid = || format!("invalid patch of {url}");
+    let from = patch
+        .get("from")
+        .and_then(Value::as_str)
+        .and_then(parse_digest)
+        .ok_or_else(invalid)?;
+    let path = patch
+        .get("path")
+        .and_then(Value::as_str)
+        .ok_or_else(invalid)?;
+    let url = url.join(path).map_err(|_| invalid())?;
+    Ok(SyncPatch {
+        from,
+        url: url.to_string(),
+    })
+}
+
+impl WebAssetIo {
+    /// Makes sure the cached copy of `file` is up to date, downloading it if not
+    async fn sync_file(&self, file: &SyncFile) -> Result<(), WebAssetError> {
+        let Some(cache) = &self.cache else {
+            return Ok(());
+        };
+        let Some(expected) = &file.sha256 else {
+            self.fetch_shared(&file.url, BACKGROUND_PRIORITY).await?;
+            return Ok(());
+        };
+        if let Some(cached) = cache.get(&file.url).await {
+            let cached_sha256 = sha256(&cached.body);
+            if cached_sha256 == *expected {
+                return Ok(());
+            }
+            if let Some(patch) = file
+                .patches
+                .iter()
+                .find(|patch| patch.from == cached_sha256)
+            {
+                match self.patch(file, expected, patch, &cached.body).await {
+                    Ok(entry) => {
+                        cache.put(&entry).await;
+                        if let Some(memory) = &self.memory_cache {
+                            memory.remove(&file.url);
+                        }
+                        return Ok(());
+                    }
+                    Err(err) => warn!("Failed to patch {}, downloading all of it: {err}", file.url),
+                }
+            }
+            // The revalidated copy would be just as outdated
+            cache.remove(&file.url).await;
+        }
+        if let Some(memory) = &self.memory_cache {
+            memory.remove(&file.url);
+        }
+        let body = self.fetch_shared(&file.url, BACKGROUND_PRIORITY).await?;
+        let actual = sha256(&body);
+        if actual != *expected {
+            cache.remove(&file.url).await;
+            return Err(WebAssetError::Integrity {
+                url: file.url.clone(),
+                expected: expected.clone(),
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    /// The cache entry of `file` made by applying `patch` to its cached `old` body
+    async fn patch(
+        &self,
+        file: &SyncFile,
+        expected: &str,
+        patch: &SyncPatch,
+        old: &[u8],
+    ) -> Result<CacheEntry, WebAssetError> {
+        let diff = self
+            .fetch_with_options(&patch.url, &RequestOptions::default())
+            .await?;
+        let max_len = self.max_download_size.unwrap_or(u64::MAX);
+        let body = delta::apply(old, &diff, max_len).map_err(|message| WebAssetError::Decode {
+            url: patch.url.clone(),
+            message,
+        })?;
+        let actual = sha256(&body);
+        if actual != expected {
+            return Err(WebAssetError::Integrity {
+                url: file.url.clone(),
+                expected: expected.to_string(),
+                actual,
+            });
+        }
+        // What a download would have stored, so later loads can revalidate it
+        let head = RequestOptions {
+            method: Some(Method::Head),
+            ..default()
+        };
+        let (_, response) = self.send_with_retries(&file.url, &head).await?;
+        let fetched = Fetched {
+            body,
+            validator: response.validator().map(str::to_string),
+            headers: response.headers,
+        };
+        CacheEntry::new(&file.url, &fetched).ok_or_else(|| WebAssetError::Decode {
+            url: file.url.clone(),
+            message: "the server doesn't allow caching it".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABC: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[test]
+    fn parses_manifests() {
+        let manifest = format!(
+            r#"{{ "files": [
+                {{ "path": "textures/hero.png", "sha256": "{}", "size": 3 }},
+                {{ "path": "https://other.example.com/theme.ogg" }},
+                {{ "path": "/level.pak", "patches": [
+                    {{ "from": "sha256-ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=", "path": "level.pak.v1.patch" }}
+                ] }}
+            ] }}"#,
+            ABC.to_ascii_uppercase()
+        );
+        let files = parse(
+            "https://example.com/content/manifest.json",
+            manifest.as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(files.len(), 3);
+        assert_eq!(
+            files[0].url,
+            "https://example.com/content/textures/hero.png"
+        );
+        assert_eq!(files[0].sha256.as_deref(), Some(ABC));
+        assert_eq!(files[0].size, Some(3));
+        assert_eq!(files[1].url, "https://other.example.com/theme.ogg");
+        assert_eq!((&files[1].sha256, files[1].size), (&None, None));
+        assert_eq!(files[2].url, "https://example.com/level.pak");
+        assert_eq!(files[2].patches[0].from, ABC);
+        assert_eq!(
+            files[2].patches[0].url,
+            "https://example.com/level.pak.v1.patch"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_manifests() {
+        let parse =
+            |manifest: &str| parse("https://example.com/manifest.json", manifest.as_bytes());
+        assert!(parse("not json").is_err());
+        assert!(parse(r#"{ "assets": [] }"#).is_err());
+        assert!(parse(r#"{ "files": [{ "size": 3 }] }"#).is_err());
+        assert!(parse(r#"{ "files": [{ "path": "a", "sha256": "abc" }] }"#).is_err());
+        assert!(parse(r#"{ "files": [{ "path": "a", "patches": {} }] }"#).is_err());
+        assert!(
+            parse(r#"{ "files": [{ "path": "a", "patches": [{ "path": "a.patch" }] }] }"#).is_err()
+        );
+        assert!(parse(r#"{ "files": [] }"#).unwrap().is_empty());
+    }
+
+    #[test]
+    fn computes_fraction() {
+        let status = |files_done, bytes_done, bytes_total| SyncStatus {
+            files_total: 4,
+            files_done,
+            bytes_total,
+            bytes_done,
+            ..default()
+        };
+        assert_eq!(status(1, 0, 0).fraction(), 0.25);
+        assert_eq!(status(1, 30, 40).fraction(), 0.75);
+        // Files without a size in the manifest don't count towards the bytes
+        assert_eq!(status(4, 50, 40).fraction(), 1.0);
+
+        let mut empty = SyncStatus::default();
+        assert_eq!(empty.fraction(), 0.0);
+        empty.state = SyncState::Done;
+        assert_eq!(empty.fraction(), 1.0);
+        assert!(empty.is_done());
+    }
+
+    #[test]
+    fn fails_if_any_file_failed() {
+        let sync = WebAssetSync {
+            manifest_url: "https://example.com/manifest.json".to_string(),
+            status: default(),
+        };
+        sync.finish(None);
+        assert_eq!(sync.status().state, SyncState::Done);
+
+        sync.status.write().unwrap().files_total = 2;
+        sync.status.write().unwrap().failed.push((
+            "https://example.com/a.png".to_string(),
+            WebAssetError::Offline {
+                url: "https://example.com/a.png".to_string(),
+            },
+        ));
+        sync.finish(None);
+        assert_eq!(
+            sync.status().state,
+            SyncState::Failed("1 of 2 files failed to sync".to_string())
+        );
+        sync.finish(Some("manifest is gone".to_string()));
+        assert_eq!(
+            sync.status().state,
+            SyncState::Failed("manifest is gone".to_string())
+        );
+    }
+}
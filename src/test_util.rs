@@ -0,0 +1,463 @@
+//! A local http server for testing code that loads web assets, enabled by the
+//! `test-util` feature. Native only.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_web_asset::test_util::{MockResponse, MockServer};
+//! # use futures_lite::future;
+//! # use std::path::Path;
+//! let server = MockServer::start();
+//! server.mock("/hero.png", MockResponse::ok("png").etag("\"v1\""));
+//!
+//! let mut app = App::new();
+//! app.add_plugins(MinimalPlugins).add_plugin(server.plugin());
+//! let asset_io = app.world.resource::<AssetServer>().asset_io();
+//! let body = future::block_on(asset_io.load_path(Path::new("mock://hero.png"))).unwrap();
+//!
+//! assert_eq!(body, b"png");
+//! assert_eq!(server.requests_to("/hero.png").len(), 1);
+//! ```
+
+use async_std::{
+    io::{ReadExt, WriteExt},
+    net::{TcpListener, TcpStream},
+    task::{self, JoinHandle},
+};
+use bevy::utils::default;
+use futures_lite::future;
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{HeaderMap, Method, RetryPolicy, WebAssetPlugin};
+
+/// Longest request head the server reads
+const MAX_HEAD_LEN: usize = 64 * 1024;
+
+/// What the [`MockServer`] answers a request with
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: u16,
+    headers: HeaderMap,
+    body: Vec<u8>,
+    delay: Duration,
+    cut_off: Option<usize>,
+}
+
+impl MockResponse {
+    /// An empty response with `status`
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+            delay: Duration::ZERO,
+            cut_off: None,
+        }
+    }
+
+    /// A `200 OK` response with `body`
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self::new(200).body(body)
+    }
+
+    /// Replaces the body
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Adds a header, panicking if it's invalid
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers
+            .append(name, value)
+            .unwrap_or_else(|err| panic!("Invalid mock response header: {err}"));
+        self
+    }
+
+    /// Sets the `ETag`, answering requests with a matching `If-None-Match` with
+    /// `304 Not Modified`
+    pub fn etag(self, etag: &str) -> Self {
+        self.header("ETag", etag)
+    }
+
+    /// Sets `Last-Modified`, answering requests with the same `If-Modified-Since`
+    /// with `304 Not Modified`
+    pub fn last_modified(self, date: &str) -> Self {
+        self.header("Last-Modified", date)
+    }
+
+    /// Waits this long before answering, e.g. to test timeouts
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Closes the connection after sending the first `len` bytes of the body, e.g. to
+    /// test resuming interrupted downloads
+    pub fn cut_off(mut self, len: usize) -> Self {
+        self.cut_off = Some(len);
+        self
+    }
+
+    /// Whether the client already has this version
+    fn not_modified(&self, request: &ReceivedRequest) -> bool {
+        let same = |ours: &str, theirs: &str| {
+            self.headers.get(ours).is_some()
+                && self.headers.get(ours) == request.headers.get(theirs)
+        };
+        self.status == 200
+            && (same("etag", "if-none-match") || same("last-modified", "if-modified-since"))
+    }
+}
+
+/// A request the [`MockServer`] received
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    /// The request method
+    pub method: Method,
+    /// The path, including the query
+    pub path: String,
+    /// The request headers
+    pub headers: HeaderMap,
+    /// The request body
+    pub body: Vec<u8>,
+}
+
+struct Mock {
+    method: Option<Method>,
+    pattern: String,
+    /// Served in order, the last one repeating
+    responses: Vec<MockResponse>,
+    served: usize,
+}
+
+#[derive(Default)]
+struct State {
+    mocks: Vec<Mock>,
+    requests: Vec<ReceivedRequest>,
+}
+
+/// An http server on localhost that answers requests with mocked responses, and
+/// records every request it receives so tests can assert on them. Requests
+/// nothing was mocked for get a `404`. It's stopped when dropped.
+pub struct MockServer {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Starts a server on a free port
+    pub fn start() -> Self {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap_or_else(|err| panic!("Can't start the mock server: {err}"));
+        let address = listener.local_addr().unwrap();
+        let listener = TcpListener::from(listener);
+        let state = Arc::<Mutex<State>>::default();
+        let task = task::spawn({
+            let state = state.clone();
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    task::spawn(serve(stream, state.clone()));
+                }
+            }
+        });
+        Self {
+            address,
+            state,
+            task: Some(task),
+        }
+    }
+
+    /// The url of `path` on this server, e.g. `http://127.0.0.1:34567/hero.png`
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}/{}", self.address, path.trim_start_matches('/'))
+    }
+
+    /// A plugin loading `mock://` paths from this server, e.g. `mock://hero.png`
+    /// from `/hero.png`, retrying without waiting so tests stay fast
+    pub fn plugin(&self) -> WebAssetPlugin {
+        let mut plugin = WebAssetPlugin {
+            retry: RetryPolicy {
+                initial_backoff: Duration::ZERO,
+                max_backoff: Duration::ZERO,
+                ..default()
+            },
+            ..default()
+        };
+        plugin
+            .aliases
+            .insert("mock".to_string(), format!("http://{}", self.address));
+        plugin
+    }
+
+    /// Answers requests with any method to paths matching `pattern`, where `*`
+    /// matches any run of characters, with `response`. Later mocks take precedence.
+    pub fn mock(&self, pattern: &str, response: MockResponse) -> &Self {
+        self.add(None, pattern, vec![response])
+    }
+
+    /// Answers requests with `method` to paths matching `pattern` with `response`
+    pub fn mock_method(&self, method: Method, pattern: &str, response: MockResponse) -> &Self {
+        self.add(Some(method), pattern, vec![response])
+    }
+
+    /// Answers consecutive requests to paths matching `pattern` with `responses` in
+    /// order, repeating the last one, e.g. a `503` and then a `200` to test retries
+    pub fn mock_sequence(
+        &self,
+        pattern: &str,
+        responses: impl IntoIterator<Item = MockResponse>,
+    ) -> &Self {
+        let responses: Vec<_> = responses.into_iter().collect();
+        assert!(!responses.is_empty(), "A mock needs at least one response");
+        self.add(None, pattern, responses)
+    }
+
+    fn add(&self, method: Option<Method>, pattern: &str, responses: Vec<MockResponse>) -> &Self {
+        self.state.lock().unwrap().mocks.push(Mock {
+            method,
+            pattern: pattern.to_string(),
+            responses,
+            served: 0,
+        });
+        self
+    }
+
+    /// Every request received so far, in order
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// The requests received so far for `path`, ignoring the query
+    pub fn requests_to(&self, path: &str) -> Vec<ReceivedRequest> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request.path.split('?').next() == Some(path))
+            .collect()
+    }
+
+    /// Forgets every mock, and the requests received so far
+    pub fn reset(&self) {
+        *self.state.lock().unwrap() = default();
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            future::block_on(task.cancel());
+        }
+    }
+}
+
+/// Answers the requests sent over `stream`
+async fn serve(mut stream: TcpStream, state: Arc<Mutex<State>>) {
+    while let Some(request) = read_request(&mut stream).await {
+        let response = {
+            let mut state = state.lock().unwrap();
+            state.requests.push(request.clone());
+            respond(&mut state, &request)
+        };
+        if !response.delay.is_zero() {
+            task::sleep(response.delay).await;
+        }
+        let mut head = format!("HTTP/1.1 {} \r\n", response.status);
+        for (name, value) in response.headers.iter() {
+            head += &format!("{name}: {value}\r\n");
+        }
+        head += &format!("Content-Length: {}\r\n\r\n", response.body.len());
+        let mut bytes = head.into_bytes();
+        if request.method != Method::Head {
+            let len = response.cut_off.unwrap_or(usize::MAX);
+            bytes.extend(response.body.iter().take(len));
+        }
+        if stream.write_all(&bytes).await.is_err() || response.cut_off.is_some() {
+            return;
+        }
+    }
+}
+
+/// The response to `request`, from the latest matching mock
+fn respond(state: &mut State, request: &ReceivedRequest) -> MockResponse {
+    let path = request.path.split('?').next().unwrap_or_default();
+    let mock = state.mocks.iter_mut().rev().find(|mock| {
+        mock.method
+            .as_ref()
+            .is_none_or(|method| *method == request.method)
+            && crate::fallback::matches(&mock.pattern, path)
+    });
+    let Some(mock) = mock else {
+        return MockResponse::new(404);
+    };
+    let response = mock.responses[mock.served.min(mock.responses.len() - 1)].clone();
+    mock.served += 1;
+    if response.not_modified(request) {
+        return MockResponse {
+            status: 304,
+            body: Vec::new(),
+            ..response
+        };
+    }
+    response
+}
+
+/// Reads the next request, `None` once the client closed the connection
+async fn read_request(stream: &mut TcpStream) -> Option<ReceivedRequest> {
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        let mut byte = [0];
+        if stream.read(&mut byte).await.ok()? == 0 || head.len() > MAX_HEAD_LEN {
+            return None;
+        }
+        head.push(byte[0]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split(' ');
+    let method = match request_line.next()? {
+        "GET" => Method::Get,
+        "HEAD" => Method::Head,
+        "POST" => Method::Post,
+        "PUT" => Method::Put,
+        "DELETE" => Method::Delete,
+        other => Method::Other(other.to_string()),
+    };
+    let path = request_line.next()?.to_string();
+    let mut headers = HeaderMap::new();
+    for line in lines.filter(|line| !line.is_empty()) {
+        let (name, value) = line.split_once(':')?;
+        headers.append(name.trim(), value.trim()).ok()?;
+    }
+    let length = headers
+        .get("content-length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body).await.ok()?;
+    Some(ReceivedRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::{Shutdown, TcpStream},
+    };
+
+    use super::*;
+
+    /// Sends the raw `requests` over one connection, returning the raw responses
+    fn send(server: &MockServer, requests: &str) -> String {
+        let mut stream = TcpStream::connect(server.address).unwrap();
+        stream.write_all(requests.as_bytes()).unwrap();
+        stream.shutdown(Shutdown::Write).unwrap();
+        let mut responses = String::new();
+        stream.read_to_string(&mut responses).unwrap();
+        responses
+    }
+
+    fn get(server: &MockServer, path: &str, headers: &str) -> String {
+        send(server, &format!("GET {path} HTTP/1.1\r\n{headers}\r\n"))
+    }
+
+    #[test]
+    fn answers_with_latest_matching_mock() {
+        let server = MockServer::start();
+        server
+            .mock("/textures/*", MockResponse::ok("any"))
+            .mock("/textures/hero.png", MockResponse::ok("hero"));
+        assert!(get(&server, "/textures/hero.png?v=1", "").ends_with("\r\n\r\nhero"));
+        assert!(get(&server, "/textures/tree.png", "").ends_with("\r\n\r\nany"));
+        assert!(get(&server, "/sounds/jump.ogg", "").starts_with("HTTP/1.1 404 "));
+    }
+
+    #[test]
+    fn matches_methods() {
+        let server = MockServer::start();
+        server.mock_method(Method::Put, "/upload", MockResponse::new(201));
+        assert!(get(&server, "/upload", "").starts_with("HTTP/1.1 404 "));
+        let response = send(
+            &server,
+            "PUT /upload HTTP/1.1\r\nContent-Length: 4\r\n\r\nbody",
+        );
+        assert!(response.starts_with("HTTP/1.1 201 "));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].method, Method::Put);
+        assert_eq!(requests[1].body, b"body");
+        assert_eq!(requests[1].headers.get("content-length"), Some("4"));
+    }
+
+    #[test]
+    fn serves_sequences_repeating_the_last() {
+        let server = MockServer::start();
+        server.mock_sequence("/flaky", [MockResponse::new(503), MockResponse::ok("ok")]);
+        // Several requests over one connection
+        let responses = send(&server, &"GET /flaky HTTP/1.1\r\n\r\n".repeat(3));
+        assert_eq!(responses.matches("HTTP/1.1 503 ").count(), 1);
+        assert_eq!(responses.matches("HTTP/1.1 200 ").count(), 2);
+        assert_eq!(server.requests_to("/flaky").len(), 3);
+    }
+
+    #[test]
+    fn answers_conditional_requests() {
+        let server = MockServer::start();
+        server
+            .mock("/etag", MockResponse::ok("body").etag("\"v1\""))
+            .mock("/date", MockResponse::ok("body").last_modified("yesterday"));
+        assert!(get(&server, "/etag", "If-None-Match: \"v1\"\r\n").starts_with("HTTP/1.1 304 "));
+        assert!(get(&server, "/etag", "If-None-Match: \"v0\"\r\n").starts_with("HTTP/1.1 200 "));
+        let not_modified = get(&server, "/date", "If-Modified-Since: yesterday\r\n");
+        assert!(not_modified.starts_with("HTTP/1.1 304 "));
+        assert!(not_modified.ends_with("Content-Length: 0\r\n\r\n"));
+    }
+
+    #[test]
+    fn leaves_out_head_bodies() {
+        let server = MockServer::start();
+        server.mock("/hero.png", MockResponse::ok("png"));
+        let response = send(&server, "HEAD /hero.png HTTP/1.1\r\n\r\n");
+        assert!(response.ends_with("Content-Length: 3\r\n\r\n"));
+    }
+
+    #[test]
+    fn cuts_off_bodies() {
+        let server = MockServer::start();
+        server.mock("/level.bin", MockResponse::ok("0123456789").cut_off(4));
+        // Closed before the rest of the body
+        let response = get(&server, "/level.bin", "");
+        assert!(response.ends_with("Content-Length: 10\r\n\r\n0123"));
+    }
+
+    #[test]
+    fn resets_mocks_and_requests() {
+        let server = MockServer::start();
+        server.mock("/hero.png", MockResponse::ok("png"));
+        get(&server, "/hero.png", "");
+        server.reset();
+        assert!(server.requests().is_empty());
+        assert!(get(&server, "/hero.png", "").starts_with("HTTP/1.1 404 "));
+    }
+
+    #[test]
+    fn builds_urls_and_plugin() {
+        let server = MockServer::start();
+        assert_eq!(
+            server.url("/hero.png"),
+            format!("http://{}/hero.png", server.address)
+        );
+        let plugin = server.plugin();
+        assert_eq!(plugin.aliases["mock"], format!("http://{}", server.address));
+        assert_eq!(plugin.retry.initial_backoff, Duration::ZERO);
+    }
+}
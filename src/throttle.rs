@@ -0,0 +1,114 @@
+use bevy::utils::{HashMap, Instant};
+use std::{sync::Mutex, time::Duration};
+
+use crate::{retry::sleep, WebAssetIo};
+
+/// Paces reads so downloads sharing it stay below a bandwidth limit
+pub(crate) struct Throttle {
+    bytes_per_second: u64,
+    /// When the bytes read so far are paid for
+    next_free: Mutex<Instant>,
+}
+
+impl Throttle {
+    pub(crate) fn new(bytes_per_second: u64) -> Self {
+        Self {
+            bytes_per_second: bytes_per_second.max(1),
+            next_free: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// How long to wait after reading `bytes`
+    pub(crate) fn delay(&self, bytes: usize) -> Duration {
+        let now = Instant::now();
+        let mut next_free = self.next_free.lock().unwrap();
+        let cost = Duration::from_secs_f64(bytes as f64 / self.bytes_per_second as f64);
+        // Idle time isn't saved up, so a burst after a pause is paced too
+        *next_free = (*next_free).max(now) + cost;
+        *next_free - now
+    }
+}
+
+/// Bandwidth limits for all downloads and for specific hosts
+#[derive(Default)]
+pub(crate) struct Throttles {
+    pub(crate) global: Option<Throttle>,
+    pub(crate) domains: HashMap<String, Throttle>,
+}
+
+impl WebAssetIo {
+    /// Waits until reading `bytes` from `uri` fits the bandwidth limits
+    pub(crate) async fn throttle(&self, uri: &str, bytes: usize) {
+        let throttles = &self.throttles;
+        if throttles.global.is_none() && throttles.domains.is_empty() {
+            return;
+        }
+        let domain = url::Url::parse(uri)
+            .ok()
+            .and_then(|url| throttles.domains.get(url.host_str()?));
+        let delay = [throttles.global.as_ref(), domain]
+            .into_iter()
+            .flatten()
+            .map(|throttle| throttle.delay(bytes))
+            .max()
+            .unwrap_or_default();
+        if !delay.is_zero() {
+            sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use futures_lite::future;
+
+    use super::*;
+    use crate::WebAssetPlugin;
+
+    fn assert_near(actual: Duration, expected_ms: u64) {
+        let expected = Duration::from_millis(expected_ms);
+        let slack = Duration::from_millis(20);
+        assert!(
+            actual + slack >= expected && actual <= expected + slack,
+            "{actual:?} isn't about {expected:?}"
+        );
+    }
+
+    #[test]
+    fn paces_reads() {
+        let throttle = Throttle::new(1000);
+        assert_near(throttle.delay(500), 500);
+        // Reads sharing the throttle queue up behind each other
+        assert_near(throttle.delay(500), 1000);
+        assert_near(throttle.delay(0), 1000);
+
+        // Idle time isn't saved up
+        *throttle.next_free.lock().unwrap() = Instant::now() - Duration::from_secs(10);
+        assert_near(throttle.delay(100), 100);
+
+        assert_near(Throttle::new(0).delay(0), 0);
+    }
+
+    #[test]
+    fn limits_hosts_separately() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(WebAssetPlugin {
+            domain_max_bandwidth: [("slow.example.com".to_string(), 10_000)]
+                .into_iter()
+                .collect(),
+            ..default()
+        });
+        let asset_server = app.world.resource::<AssetServer>();
+        let asset_io: &WebAssetIo = asset_server.asset_io().downcast_ref().unwrap();
+
+        future::block_on(asset_io.throttle("https://fast.example.com/a.png", 1_000_000));
+        let started = Instant::now();
+        future::block_on(asset_io.throttle("https://slow.example.com/a.png", 500));
+        // Slept for the 50ms the slow host takes, give or take the scheduler
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(30) && elapsed < Duration::from_secs(1));
+        let slow = &asset_io.throttles.domains["slow.example.com"];
+        assert_near(slow.delay(0), 0);
+    }
+}
@@ -0,0 +1,62 @@
+use futures_lite::{future, Future};
+use std::time::Duration;
+
+use crate::retry::sleep;
+
+/// Limits for how long a single http request may take.
+///
+/// `None` disables the respective limit. Requests hitting a timeout are
+/// treated as transient failures, and are retried according to the
+/// [`RetryPolicy`](crate::RetryPolicy).
+#[derive(Debug, Clone)]
+pub struct Timeouts {
+    /// Maximum time to wait for the response headers, including dns lookup
+    /// and connection setup.
+    pub connect: Option<Duration>,
+    /// Maximum time to wait for the next chunk of the response body.
+    pub read: Option<Duration>,
+    /// Maximum time a single attempt may take from start to finish.
+    pub total: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            connect: Some(Duration::from_secs(30)),
+            read: Some(Duration::from_secs(30)),
+            total: None,
+        }
+    }
+}
+
+impl Timeouts {
+    /// No timeouts at all, requests may hang forever.
+    pub fn none() -> Self {
+        Self {
+            connect: None,
+            read: None,
+            total: None,
+        }
+    }
+}
+
+/// Returned when a future didn't complete in time
+#[derive(Debug)]
+pub(crate) struct Elapsed;
+
+/// Runs `fut`, giving up after `duration` if there is one.
+pub(crate) async fn with_timeout<T>(
+    duration: Option<Duration>,
+    fut: impl Future<Output = T>,
+) -> Result<T, Elapsed> {
+    match duration {
+        Some(duration) => {
+            let timeout = async move {
+                sleep(duration).await;
+                Err(Elapsed)
+            };
+            future::or(async move { Ok(fut.await) }, timeout).await
+        }
+        None => Ok(fut.await),
+    }
+}
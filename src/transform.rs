@@ -0,0 +1,31 @@
+/// Changes downloaded bodies before they reach the asset loaders, e.g. to decrypt
+/// them or unwrap a custom envelope format.
+///
+/// Registered in [`WebAssetPlugin::response_transforms`](crate::WebAssetPlugin::response_transforms),
+/// and applied in order to every remote body once it has been decompressed. An
+/// error fails the load with [`WebAssetError::Decode`](crate::WebAssetError::Decode).
+/// Closures taking the url and the body implement this.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetPlugin;
+/// # use std::sync::Arc;
+/// let mut plugin = WebAssetPlugin::default();
+/// plugin.response_transforms.push(Arc::new(|_url: &str, body: Vec<u8>| {
+///     Ok(body.into_iter().map(|byte| byte ^ 0x5a).collect())
+/// }));
+/// App::new().add_plugin(plugin);
+/// ```
+pub trait ResponseTransform: Send + Sync + 'static {
+    /// The body to hand to the asset loader instead of `body`, downloaded from `url`
+    fn transform(&self, url: &str, body: Vec<u8>) -> Result<Vec<u8>, String>;
+}
+
+impl<F> ResponseTransform for F
+where
+    F: Fn(&str, Vec<u8>) -> Result<Vec<u8>, String> + Send + Sync + 'static,
+{
+    fn transform(&self, url: &str, body: Vec<u8>) -> Result<Vec<u8>, String> {
+        self(url, body)
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    tasks::{IoTaskPool, Task},
+};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    content_type, http_client::Method, web_asset_io::RequestOptions, HeaderMap, WebAssetError,
+    WebAssetIo,
+};
+
+/// Uploads assets to http(s) urls with `PUT`, e.g. screenshots or user-made levels.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetWriter;
+/// fn save_level(writer: WebAssetWriter) {
+///     let level = b"(tiles: [])".to_vec();
+///     writer
+///         .write("https://example.com/levels/mine.level", level)
+///         .detach();
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct WebAssetWriter<'w> {
+    asset_server: Res<'w, AssetServer>,
+}
+
+impl<'w> WebAssetWriter<'w> {
+    /// Uploads `bytes` to `path` in the background, see [`WebAssetIo::write_path`]
+    pub fn write(
+        &self,
+        path: impl Into<PathBuf>,
+        bytes: impl Into<Vec<u8>>,
+    ) -> Task<Result<(), WebAssetError>> {
+        let asset_server = self.asset_server.clone();
+        let path = path.into();
+        let bytes = bytes.into();
+        IoTaskPool::get().spawn(async move {
+            let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+                return Err(WebAssetError::Transport {
+                    url: path.to_string_lossy().into_owned(),
+                    message: "the asset io isn't a WebAssetIo".to_string(),
+                });
+            };
+            asset_io.write_path(&path, bytes).await
+        })
+    }
+}
+
+impl WebAssetIo {
+    /// Uploads `bytes` to the http(s) url `path` with `PUT`, sent with the configured
+    /// headers and authorization, and a `Content-Type` by its extension. Aliases and
+    /// rewriters apply as for downloads, and the cached copy of `path` is dropped.
+    pub async fn write_path(
+        &self,
+        path: impl AsRef<Path>,
+        bytes: Vec<u8>,
+    ) -> Result<(), WebAssetError> {
+        let path = path.as_ref();
+        let resolved = self.resolve_path(path);
+        let uri = resolved.as_deref().unwrap_or(path).to_string_lossy();
+        if !uri.starts_with("http://") && !uri.starts_with("https://") {
+            return Err(WebAssetError::Transport {
+                url: uri.into_owned(),
+                message: "only http(s) urls can be written to".to_string(),
+            });
+        }
+        let mut headers = HeaderMap::new();
+        let _ = headers.insert("Content-Type", content_type::of(&uri));
+        let options = RequestOptions {
+            method: Some(Method::Put),
+            body: Some(bytes),
+            headers,
+            ..default()
+        };
+        self.send_with_retries(&uri, &options).await?;
+        if let Some(memory) = &self.memory_cache {
+            memory.remove(&uri);
+        }
+        if let Some(cache) = &self.cache {
+            cache.remove(&uri).await;
+        }
+        Ok(())
+    }
+}
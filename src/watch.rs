@@ -0,0 +1,336 @@
+use bevy::{
+    log::warn,
+    prelude::*,
+    tasks::IoTaskPool,
+    utils::{HashMap, HashSet, Instant},
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use crate::{
+    archive, cache::CacheEntry, priority::BACKGROUND_PRIORITY, sidecar::CachePolicy, HeaderMap,
+    WebAssetIo,
+};
+
+/// What a download looked like, to tell whether it changed since
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Version {
+    /// `ETag` or `Last-Modified` sent by the server
+    Validator(String),
+    /// Checksum of the body, for servers without validators
+    Checksum(u32),
+}
+
+impl Version {
+    pub(crate) fn of(validator: Option<&str>, body: &[u8]) -> Self {
+        match validator {
+            Some(validator) => Version::Validator(validator.to_string()),
+            None => Version::Checksum(crc32fast::hash(body)),
+        }
+    }
+
+    /// Adds the headers that make the server answer `304 Not Modified` if unchanged
+    pub(crate) fn add_conditional_headers(&self, headers: &mut HeaderMap) {
+        if let Version::Validator(validator) = self {
+            let name = match validator.starts_with('"') {
+                true => "If-None-Match",
+                false => "If-Modified-Since",
+            };
+            let _ = headers.insert(name, validator);
+        }
+    }
+}
+
+/// Remote urls watched for changes, by polling or through change notifications
+pub(crate) struct Watcher {
+    /// How often to poll, `None` if changes are only pushed by the server
+    interval: Option<Duration>,
+    /// The asset paths to reload by watched url
+    watched: Mutex<HashMap<String, HashSet<PathBuf>>>,
+    /// Latest version of every url downloaded
+    versions: Mutex<HashMap<String, Version>>,
+    /// Bodies of the changes polling found, so reloading them doesn't download them again,
+    /// with the poll that found them
+    changes: Mutex<HashMap<String, (u64, Vec<u8>)>>,
+    /// Number of polls started
+    polls: AtomicU64,
+    polling: AtomicBool,
+    next_poll: Mutex<Instant>,
+}
+
+impl Watcher {
+    pub(crate) fn new(interval: Option<Duration>) -> Self {
+        Self {
+            interval,
+            watched: default(),
+            versions: default(),
+            changes: default(),
+            polls: AtomicU64::new(0),
+            polling: AtomicBool::new(false),
+            next_poll: Mutex::new(Instant::now() + interval.unwrap_or_default()),
+        }
+    }
+
+    /// Reloads `to_reload` whenever `url` changes
+    pub(crate) fn watch(&self, url: &str, to_reload: PathBuf) {
+        let mut watched = self.watched.lock().unwrap();
+        watched
+            .entry(url.to_string())
+            .or_default()
+            .insert(to_reload);
+    }
+
+    pub(crate) fn version(&self, url: &str) -> Option<Version> {
+        self.versions.lock().unwrap().get(url).cloned()
+    }
+
+    pub(crate) fn set_version(&self, url: &str, version: Version) {
+        let mut versions = self.versions.lock().unwrap();
+        versions.insert(url.to_string(), version);
+    }
+
+    /// The body of the change to `url` found by polling, if it hasn't been loaded yet
+    pub(crate) fn take_change(&self, url: &str) -> Option<Vec<u8>> {
+        let (_, body) = self.changes.lock().unwrap().remove(url)?;
+        Some(body)
+    }
+
+    /// Numbers a new poll, dropping the changes no reload took since the poll before, like
+    /// those of assets that were unloaded meanwhile
+    fn next_poll(&self) -> u64 {
+        let poll = self.polls.fetch_add(1, Ordering::AcqRel);
+        let mut changes = self.changes.lock().unwrap();
+        changes.retain(|_, (found, _)| *found + 1 >= poll);
+        poll
+    }
+
+    /// Whether a poll is due, marking it as started if so
+    fn start_poll(&self) -> bool {
+        if self.interval.is_none() || Instant::now() < *self.next_poll.lock().unwrap() {
+            return false;
+        }
+        !self.polling.swap(true, Ordering::AcqRel)
+    }
+
+    fn finish_poll(&self) {
+        *self.next_poll.lock().unwrap() = Instant::now() + self.interval.unwrap_or_default();
+        self.polling.store(false, Ordering::Release);
+    }
+}
+
+impl WebAssetIo {
+    /// Drops the copies of `url` kept in memory, since it changed
+    fn forget(&self, url: &str) {
+        self.archives.remove(url);
+        if let Some(memory) = &self.memory_cache {
+            memory.remove(url);
+        }
+    }
+
+    /// The asset paths to reload because `path` changed, which is either a watched asset
+    /// path or the url it's loaded from
+    pub(crate) fn changed(&self, path: &str) -> Vec<PathBuf> {
+        let Some(watcher) = &self.watcher else {
+            return Vec::new();
+        };
+        let resolved = self.resolve_path(Path::new(path));
+        let url = resolved.as_deref().and_then(Path::to_str).unwrap_or(path);
+        let url = archive::split(url).map_or(url, |(url, _)| url);
+        self.forget(url);
+
+        let watched = watcher.watched.lock().unwrap();
+        let mut to_reload: HashSet<_> = watched.get(url).cloned().unwrap_or_default();
+        for paths in watched.values() {
+            to_reload.extend(
+                paths
+                    .iter()
+                    .filter(|p| p.as_path() == Path::new(path))
+                    .cloned(),
+            );
+        }
+        to_reload.into_iter().collect()
+    }
+
+    /// Checks every watched url, returning the asset paths to reload
+    async fn poll_changes(&self) -> Vec<PathBuf> {
+        let Some(watcher) = &self.watcher else {
+            return Vec::new();
+        };
+        if self.offline.get() {
+            return Vec::new();
+        }
+        let poll = watcher.next_poll();
+        let watched: Vec<_> = watcher
+            .watched
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(url, to_reload)| (url.clone(), to_reload.clone()))
+            .collect();
+
+        let mut changed = Vec::new();
+        for (url, to_reload) in watched {
+            let before = watcher.version(&url);
+            let (mut options, policy) = match self.asset_options(&url, BACKGROUND_PRIORITY).await {
+                Ok(options) => options,
+                Err(err) => {
+                    warn!("Failed to check {url} for changes: {err}");
+                    continue;
+                }
+            };
+            if let Some(version) = &before {
+                version.add_conditional_headers(&mut options.headers);
+            }
+            match self.fetch_from_origin(&url, &options).await {
+                Err(err) if err.status() == Some(304) => {}
+                Err(err) => warn!("Failed to check {url} for changes: {err}"),
+                Ok(_) if watcher.version(&url) == before => {}
+                Ok(fetched) => {
+                    // The reload gets the new body from the caches and `changes` instead
+                    // of downloading it again
+                    let cache = self
+                        .cache
+                        .as_ref()
+                        .filter(|_| policy != Some(CachePolicy::NoStore));
+                    if let Some((cache, entry)) = cache.zip(CacheEntry::new(&url, &fetched)) {
+                        cache.put(&entry).await;
+                    }
+                    self.forget(&url);
+                    let mut changes = watcher.changes.lock().unwrap();
+                    changes.insert(url, (poll, fetched.body));
+                    changed.extend(to_reload);
+                }
+            }
+        }
+        changed
+    }
+}
+
+impl WebAssetIo {
+    /// Reloads the assets named by a change notification, one url or asset path per line
+    pub(crate) fn reload_notified(&self, asset_server: &AssetServer, message: &str) {
+        let to_reload = message
+            .lines()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .flat_map(|path| self.changed(path))
+            .collect();
+        reload(asset_server, to_reload);
+    }
+}
+
+/// Reloads the assets that changed
+pub(crate) fn reload(asset_server: &AssetServer, to_reload: Vec<PathBuf>) {
+    for path in to_reload {
+        info!("Reloading {}", path.display());
+        asset_server.reload_asset(path.as_path());
+    }
+}
+
+/// Polls watched remote assets in the background, reloading the ones that changed
+pub(crate) fn poll_for_changes(asset_server: Res<AssetServer>) {
+    let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+        return;
+    };
+    if !asset_io.watcher.as_ref().is_some_and(Watcher::start_poll) {
+        return;
+    }
+    let asset_server = asset_server.clone();
+    IoTaskPool::get()
+        .spawn(async move {
+            let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+                warn!("Can't poll for changes, the asset io isn't a WebAssetIo");
+                return;
+            };
+            reload(&asset_server, asset_io.poll_changes().await);
+            if let Some(watcher) = &asset_io.watcher {
+                watcher.finish_poll();
+            }
+        })
+        .detach();
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use bevy::asset::AssetIo;
+    use futures_lite::future;
+
+    use super::*;
+    use crate::{
+        test_util::{MockResponse, MockServer},
+        WebAssetPlugin,
+    };
+
+    #[test]
+    fn polls_with_conditional_requests() {
+        let server = MockServer::start();
+        server.mock("/hero.png", MockResponse::ok("v1").etag("\"1\""));
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(WebAssetPlugin {
+            watch_interval: Some(Duration::from_secs(60)),
+            ..server.plugin()
+        });
+        let asset_server = app.world.resource::<AssetServer>();
+        let asset_io = asset_server
+            .asset_io()
+            .downcast_ref::<WebAssetIo>()
+            .unwrap();
+        let load = || future::block_on(asset_io.load_path(Path::new("mock://hero.png")));
+
+        assert_eq!(load().unwrap(), b"v1");
+        // As the asset server does for loaded assets
+        let path = Path::new("mock://hero.png");
+        asset_io.watch_path_for_changes(path, None).unwrap();
+        assert!(future::block_on(asset_io.poll_changes()).is_empty());
+        let requests = server.requests_to("/hero.png");
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].headers.get("if-none-match"), Some("\"1\""));
+
+        server.mock("/hero.png", MockResponse::ok("v2").etag("\"2\""));
+        assert_eq!(
+            future::block_on(asset_io.poll_changes()),
+            [PathBuf::from("mock://hero.png")]
+        );
+        // The reload uses the body the poll downloaded
+        assert_eq!(load().unwrap(), b"v2");
+        assert_eq!(server.requests_to("/hero.png").len(), 3);
+        assert!(future::block_on(asset_io.poll_changes()).is_empty());
+        let requests = server.requests_to("/hero.png");
+        assert_eq!(requests[3].headers.get("if-none-match"), Some("\"2\""));
+    }
+
+    #[test]
+    fn drops_changes_never_reloaded() {
+        let server = MockServer::start();
+        server.mock("/hero.png", MockResponse::ok("v1").etag("\"1\""));
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(WebAssetPlugin {
+            watch_interval: Some(Duration::from_secs(60)),
+            ..server.plugin()
+        });
+        let asset_server = app.world.resource::<AssetServer>();
+        let asset_io = asset_server
+            .asset_io()
+            .downcast_ref::<WebAssetIo>()
+            .unwrap();
+        let path = Path::new("mock://hero.png");
+        future::block_on(asset_io.load_path(path)).unwrap();
+        asset_io.watch_path_for_changes(path, None).unwrap();
+        let watcher = asset_io.watcher.as_ref().unwrap();
+        let url = server.url("/hero.png");
+
+        server.mock("/hero.png", MockResponse::ok("v2").etag("\"2\""));
+        assert_eq!(future::block_on(asset_io.poll_changes()).len(), 1);
+        // Kept for one more poll, so a slow reload still finds it
+        assert!(future::block_on(asset_io.poll_changes()).is_empty());
+        assert!(watcher.changes.lock().unwrap().contains_key(&url));
+        assert!(future::block_on(asset_io.poll_changes()).is_empty());
+        assert_eq!(watcher.take_change(&url), None);
+    }
+}
@@ -1,69 +1,1412 @@
 use bevy::{
     asset::{AssetIo, AssetIoError},
-    utils::BoxedFuture,
+    log::{info_span, warn},
+    utils::{
+        tracing::{field, Instrument, Span},
+        BoxedFuture, HashMap, Instant,
+    },
 };
-use std::path::{Path, PathBuf};
+use crossbeam_channel::Sender;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::{
+    archive::{self, Archive, Archives},
+    asset_request::AssetRequest,
+    auth::{UrlCredentials, WebAssetAuth},
+    azure::Azure,
+    cache::{Cache, CacheEntry, CacheStrategy, Fetched, WebAssetCacheStats},
+    cas::{self, CasConfig},
+    clock::unix_time,
+    content_type,
+    error::{body_snippet, WebAssetError, WebAssetFailed, BODY_SNIPPET_LEN},
+    fallback::FallbackAssets,
+    gcs::Gcs,
+    handle::LiveHandles,
+    headers::{HeaderMap, WebAssetHeaders},
+    host_pattern::HostFilter,
+    http_client::{HttpClient, HttpError, HttpRequest, Method},
+    in_flight::{InFlight, Join},
+    integrity::{sidecar_url, IntegrityManifest, WebAssetTampered},
+    interceptor::RequestInterceptor,
+    ipfs::IpfsConfig,
+    load_progress::WebAssetLoadProgress,
+    manifest,
+    memory_cache::MemoryCache,
+    net_stats::WebAssetNetStats,
+    offline::WebAssetOffline,
+    overrides,
+    pause::WebAssetPaused,
+    pinned::PinnedManifest,
+    priority::{RequestSlots, WebAssetPriorities, BACKGROUND_PRIORITY},
+    progress::{ProgressReporter, WebAssetProgress},
+    renew::{RenewedUrls, UrlRenewer},
+    resume::Download,
+    retry::{sleep, RetryPolicy},
+    rewrite::{self, UrlRewriter},
+    s3::S3,
+    scheme::{self, SchemeHandler},
+    sidecar::{CachePolicy, Sidecar},
+    signer::RequestSigner,
+    throttle::Throttles,
+    timeout::{with_timeout, Timeouts},
+    transform::ResponseTransform,
+    watch::{Version, Watcher},
+};
+
+/// Upper limit for reserving memory based on `Content-Length` before the body arrives
+const MAX_PREALLOCATION: usize = 16 * 1024 * 1024;
+
+/// Directory manifests are lists of names, anything larger is a misconfigured url
+pub(crate) const MAX_MANIFEST_SIZE: u64 = 4 * 1024 * 1024;
 
 /// Wraps the default bevy AssetIo and adds support for loading http urls
 pub struct WebAssetIo {
     pub(crate) default_io: Box<dyn AssetIo>,
+    pub(crate) retry: RetryPolicy,
+    pub(crate) timeouts: Timeouts,
+    pub(crate) domain_timeouts: HashMap<String, Timeouts>,
+    pub(crate) progress: Sender<WebAssetProgress>,
+    pub(crate) in_flight: InFlight,
+    /// Live [`WebAssetHandle`](crate::WebAssetHandle)s by path, cancelled when none are left
+    pub(crate) live_handles: LiveHandles,
+    /// Limits the number of simultaneous requests, if set
+    pub(crate) request_slots: Option<RequestSlots>,
+    pub(crate) priorities: WebAssetPriorities,
+    /// Larger responses fail, if set
+    pub(crate) max_download_size: Option<u64>,
+    /// Bandwidth limits, paced as the body arrives
+    pub(crate) throttles: Throttles,
+    /// Whether responses whose `Content-Type` doesn't match the url fail
+    pub(crate) validate_content_type: bool,
+    /// How error statuses are reported to bevy
+    pub(crate) status_errors: crate::StatusErrors,
+    /// Whether downloads are checked against the sha256 published next to them
+    pub(crate) checksum_sidecars: bool,
+    /// The expected sha256 of downloads, by url
+    pub(crate) integrity: IntegrityManifest,
+    /// Which hosts may be downloaded from
+    pub(crate) hosts: HostFilter,
+    /// Checked for local copies of remote assets before the network, in `default_io`
+    pub(crate) local_overrides: Option<PathBuf>,
+    /// Where loaded remote assets are written to, laid out like `local_overrides`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) record_to: Option<PathBuf>,
+    /// Loaded from `default_io` when remote assets fail
+    pub(crate) fallbacks: FallbackAssets,
+    /// Base urls that are tried in order when downloads from the base url they're
+    /// keyed by fail
+    pub(crate) mirrors: HashMap<String, Vec<String>>,
+    /// Keeps downloads between runs, if enabled
+    pub(crate) cache: Option<Cache>,
+    /// Recently loaded assets, if enabled
+    pub(crate) memory_cache: Option<MemoryCache>,
+    pub(crate) cache_stats: WebAssetCacheStats,
+    /// Only loads from the cache if set
+    pub(crate) offline: WebAssetOffline,
+    pub(crate) paused: WebAssetPaused,
+    /// Urls loaded from stale cache entries, to be revalidated in the background
+    pub(crate) stale: Sender<String>,
+    pub(crate) failed: Sender<WebAssetFailed>,
+    pub(crate) net_stats: WebAssetNetStats,
+    /// Sent with requests that don't have a `User-Agent` of their own
+    pub(crate) user_agent: Option<String>,
+    pub(crate) load_progress: WebAssetLoadProgress,
+    /// Whether the size of downloads waiting for a request slot is asked for
+    pub(crate) size_requests: bool,
+    pub(crate) tampered: Sender<WebAssetTampered>,
+    pub(crate) auth: WebAssetAuth,
+    pub(crate) url_credentials: UrlCredentials,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) cookies: Option<crate::WebAssetCookies>,
+    pub(crate) headers: WebAssetHeaders,
+    pub(crate) client: Arc<dyn HttpClient>,
+    /// Archives downloaded so far, so their entries can be loaded individually
+    pub(crate) archives: Archives,
+    pub(crate) s3: S3,
+    pub(crate) gcs: Gcs,
+    pub(crate) azure: Azure,
+    pub(crate) ipfs: IpfsConfig,
+    pub(crate) cas: CasConfig,
+    /// Custom handlers by scheme, e.g. `mod`
+    pub(crate) scheme_handlers: HashMap<String, Arc<dyn SchemeHandler>>,
+    /// Where logical paths are loaded from for the rest of the session, if set
+    pub(crate) pinned: Option<PinnedManifest>,
+    /// Base urls by alias scheme, e.g. `cdn`
+    pub(crate) aliases: HashMap<String, String>,
+    pub(crate) url_rewriters: Vec<Arc<dyn UrlRewriter>>,
+    pub(crate) request_interceptors: Vec<Arc<dyn RequestInterceptor>>,
+    pub(crate) request_signers: Vec<Arc<dyn RequestSigner>>,
+    pub(crate) url_renewer: Option<Arc<dyn UrlRenewer>>,
+    pub(crate) renewed_urls: RenewedUrls,
+    pub(crate) response_transforms: Vec<Arc<dyn ResponseTransform>>,
+    /// Polls watched remote assets for changes, if enabled
+    pub(crate) watcher: Option<Watcher>,
+    /// Appended to the path of remote assets to get the url of their request options,
+    /// e.g. `.http.json`
+    pub(crate) request_sidecar: Option<String>,
+    /// How assets of urls matching patterns are requested, the first match applies
+    pub(crate) asset_requests: Vec<(String, AssetRequest)>,
+    /// Name of the manifests listing remote directories, e.g. `index.json`
+    pub(crate) directory_manifest: Option<String>,
+    /// Asset loader extensions by content type, e.g. `image/png` to `png`
+    pub(crate) content_type_extensions: HashMap<String, String>,
+    #[cfg(feature = "webdav")]
+    pub(crate) dav_entries: crate::webdav::DavEntries,
 }
 
-fn is_http(path: &Path) -> bool {
-    path.starts_with("http://") || path.starts_with("https://")
+impl WebAssetIo {
+    /// The timeouts that apply to requests to `uri`
+    fn timeouts_for(&self, uri: &str) -> &Timeouts {
+        url::Url::parse(uri)
+            .ok()
+            .and_then(|url| self.domain_timeouts.get(url.host_str()?))
+            .unwrap_or(&self.timeouts)
+    }
 }
 
-impl AssetIo for WebAssetIo {
-    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
-        if is_http(path) {
-            let uri = path.to_str().unwrap();
+/// Whether `uri` names its content, so it never changes
+pub(crate) fn is_content_addressed(uri: &str) -> bool {
+    uri.starts_with("ipfs://") || uri.starts_with("cas://")
+}
+
+/// Whether `path` is loaded over the network
+pub(crate) fn is_remote(path: &Path) -> bool {
+    [
+        "http://", "https://", "s3://", "gs://", "azure://", "ipfs://", "cas://",
+    ]
+    .iter()
+    .any(|scheme| path.starts_with(scheme))
+}
+
+/// The url of the remote `path`, which has to be valid UTF-8
+fn remote_uri(path: &Path) -> Result<&str, AssetIoError> {
+    path.to_str().ok_or_else(|| {
+        AssetIoError::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} isn't a valid url", path.display()),
+        ))
+    })
+}
+
+/// Where a remote path is stored, which decides how requests are authorized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Storage {
+    Http,
+    S3,
+    Gcs,
+    Azure,
+}
 
-            #[cfg(target_arch = "wasm32")]
-            let fut = Box::pin(async move {
-                use wasm_bindgen::JsCast;
-                use wasm_bindgen_futures::JsFuture;
-                let window = web_sys::window().unwrap();
-                let response = JsFuture::from(window.fetch_with_str(uri))
-                    .await
-                    .map(|r| r.dyn_into::<web_sys::Response>().unwrap())
-                    .map_err(|e| e.dyn_into::<js_sys::TypeError>().unwrap());
+impl WebAssetIo {
+    /// The http url to request for `uri`, buckets are loaded from their https endpoint
+    fn request_url(&self, uri: &str) -> Result<(String, Storage), WebAssetError> {
+        let invalid = |expected: &str| WebAssetError::Transport {
+            url: uri.to_string(),
+            message: format!("invalid path, expected {expected}"),
+        };
+        if uri.starts_with("s3://") {
+            let url = self.s3.url(uri).ok_or_else(|| invalid("s3://bucket/key"))?;
+            Ok((url, Storage::S3))
+        } else if uri.starts_with("gs://") {
+            let url = self
+                .gcs
+                .url(uri)
+                .ok_or_else(|| invalid("gs://bucket/object"))?;
+            Ok((url, Storage::Gcs))
+        } else if uri.starts_with("cas://") {
+            let url = self
+                .cas
+                .url(uri)
+                .ok_or_else(|| invalid("cas://<sha256> and a base url"))?;
+            Ok((url, Storage::Http))
+        } else if uri.starts_with("azure://") {
+            let url = self
+                .azure
+                .url(uri)
+                .ok_or_else(|| invalid("azure://container/blob and a storage account"))?;
+            Ok((url, Storage::Azure))
+        } else {
+            Ok((uri.to_string(), Storage::Http))
+        }
+    }
+}
 
-                if let Err(err) = &response {
-                    // warn!("Failed to fetch asset {uri}: {err:?}");
+/// How a request differs from a plain `GET`
+#[derive(Default)]
+pub(crate) struct RequestOptions {
+    /// `GET` if not set
+    pub(crate) method: Option<Method>,
+    pub(crate) body: Option<Vec<u8>>,
+    /// Sent in addition to the configured headers
+    pub(crate) headers: HeaderMap,
+    /// Larger responses fail, in addition to the global limit
+    pub(crate) max_size: Option<u64>,
+    /// The path of the asset being downloaded, which is checked for integrity,
+    /// `None` for manifests and other metadata. Differs from the requested url for
+    /// mirrors and ipfs gateways.
+    pub(crate) asset: Option<String>,
+    /// Requests with a higher priority get a free request slot first
+    pub(crate) priority: i32,
+    /// Replaces the total timeout of the host
+    pub(crate) timeout: Option<Duration>,
+    /// Where the body is written, instead of keeping it in memory
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) download_to: Option<PathBuf>,
+}
+
+impl RequestOptions {
+    fn method(&self) -> Method {
+        self.method.clone().unwrap_or(Method::Get)
+    }
+}
+
+impl WebAssetIo {
+    /// The maximum size of the response to a request made with `options`
+    fn size_limit(&self, options: &RequestOptions) -> Option<u64> {
+        match (self.max_download_size, options.max_size) {
+            (Some(global), Some(request)) => Some(global.min(request)),
+            (global, request) => global.or(request),
+        }
+    }
+}
+
+/// Decompresses the body of a finished download, which may not grow past `limit`
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn decode_body(
+    uri: &str,
+    download: Download,
+    limit: Option<u64>,
+) -> Result<Vec<u8>, WebAssetError> {
+    let Some(encoding) = &download.encoding else {
+        return Ok(download.body);
+    };
+    let max_len = limit.unwrap_or(u64::MAX);
+    let body = crate::encoding::decode(encoding, download.body, max_len).map_err(|message| {
+        WebAssetError::Decode {
+            url: uri.to_string(),
+            message,
+        }
+    })?;
+    if body.len() as u64 > max_len {
+        return Err(WebAssetError::TooLarge {
+            url: uri.to_string(),
+            limit: max_len,
+        });
+    }
+    Ok(body)
+}
+
+/// Browsers decompress bodies themselves
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn decode_body(
+    _uri: &str,
+    download: Download,
+    _limit: Option<u64>,
+) -> Result<Vec<u8>, WebAssetError> {
+    Ok(download.body)
+}
+
+impl WebAssetIo {
+    /// Makes a single attempt at downloading `uri`, appending the body to `download` as it
+    /// arrives.
+    ///
+    /// On failure, `download` keeps whatever was received before the error, so the next
+    /// attempt can resume from there.
+    async fn fetch(
+        &self,
+        uri: &str,
+        storage: Storage,
+        options: &RequestOptions,
+        mut headers: HeaderMap,
+        timeouts: &Timeouts,
+        download: &mut Download,
+    ) -> Result<(), WebAssetError> {
+        let timeout = |_| WebAssetError::Timeout {
+            url: uri.to_string(),
+        };
+        let limit = self.size_limit(options);
+        let too_large = |limit| WebAssetError::TooLarge {
+            url: uri.to_string(),
+            limit,
+        };
+        let transport = |err: HttpError| WebAssetError::Transport {
+            url: uri.to_string(),
+            message: err.to_string(),
+        };
+
+        let method = options.method();
+        let resuming =
+            self.retry.resume && method == Method::Get && download.add_range_headers(&mut headers);
+        let mut request = HttpRequest {
+            method,
+            headers,
+            body: options.body.clone(),
+            ..HttpRequest::get(uri)
+        };
+        for interceptor in &self.request_interceptors {
+            interceptor.intercept(&mut request);
+        }
+        // Signatures cover the range and whatever the interceptors changed, so this comes last
+        if let Ok(url) = url::Url::parse(&request.url) {
+            match storage {
+                Storage::S3 => self.s3.sign(&request.method, &url, &mut request.headers),
+                Storage::Azure => self.azure.sign(&request.method, &url, &mut request.headers),
+                Storage::Http | Storage::Gcs => {}
+            }
+        }
+        let now = unix_time();
+        for signer in &self.request_signers {
+            signer.sign(&mut request, now);
+        }
+        // Interceptors and signers may have sent the request elsewhere
+        if !self.hosts.allows(&request.url) {
+            return Err(WebAssetError::Blocked { url: request.url });
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        let sent_to = url::Url::parse(&request.url).ok();
+
+        let sent = Instant::now();
+        let response = with_timeout(timeouts.connect, self.client.send(request))
+            .await
+            .map_err(timeout)
+            .and_then(|response| response.map_err(transport));
+        let mut response = response.inspect_err(|_| self.net_stats.transport_error())?;
+        self.net_stats.responded(response.status, sent.elapsed());
+        Span::current().record("status", response.status);
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some((url, cookies)) = sent_to.as_ref().zip(self.cookies.as_ref()) {
+            cookies.store_response(url, &response.headers);
+        }
+
+        if resuming && response.status == 416 {
+            download.reset();
+            return Err(WebAssetError::Transport {
+                url: uri.to_string(),
+                message: "server rejected the range of a resumed download".to_string(),
+            });
+        }
+
+        if !response.is_success() {
+            let mut snippet = Vec::new();
+            while snippet.len() < BODY_SNIPPET_LEN {
+                match with_timeout(timeouts.read, response.body.next_chunk()).await {
+                    Ok(Ok(Some(chunk))) => snippet.extend(chunk),
+                    _ => break,
                 }
+            }
+            return Err(WebAssetError::Status {
+                url: uri.to_string(),
+                status: response.status,
+                body: body_snippet(&snippet),
+                headers: response.headers,
+            });
+        }
+
+        let Some(total_bytes) = download.start(response.status, &response.headers) else {
+            return Err(WebAssetError::Transport {
+                url: uri.to_string(),
+                message: "server resumed the download at the wrong offset".to_string(),
+            });
+        };
+
+        if options.method() == Method::Head {
+            // There's no body, even if the headers describe one
+            return Ok(());
+        }
+
+        if let (Some(limit), Some(total_bytes)) = (limit, total_bytes) {
+            // Fail before downloading anything
+            if total_bytes > limit {
+                return Err(too_large(limit));
+            }
+        }
+
+        let progress = ProgressReporter {
+            sender: &self.progress,
+            url: uri,
+            total_bytes,
+            load_progress: &self.load_progress,
+            asset: options.asset.as_deref(),
+        };
+        progress.report(download.received());
+
+        // Don't trust the server with huge up-front allocations, the buffer grows as needed
+        if download.in_memory() {
+            let expected = total_bytes.unwrap_or_default() as usize;
+            let missing = expected.saturating_sub(download.body.len());
+            download.body.reserve(missing.min(MAX_PREALLOCATION));
+        }
+
+        while let Some(chunk) = with_timeout(timeouts.read, response.body.next_chunk())
+            .await
+            .map_err(timeout)?
+            .map_err(transport)?
+        {
+            download
+                .append(&chunk)
+                .map_err(|err| WebAssetError::Transport {
+                    url: uri.to_string(),
+                    message: format!("can't write the download: {err}"),
+                })?;
+            self.net_stats.received(chunk.len());
+            self.throttle(uri, chunk.len()).await;
+            if let Some(limit) = limit.filter(|&limit| download.received() > limit) {
+                download.reset();
+                return Err(too_large(limit));
+            }
+            progress.report(download.received());
+        }
+        // Browsers fail truncated bodies themselves, the native client just ends them
+        #[cfg(not(target_arch = "wasm32"))]
+        if total_bytes.is_some_and(|total_bytes| download.received() < total_bytes) {
+            return Err(WebAssetError::Transport {
+                url: uri.to_string(),
+                message: "connection closed before the download finished".to_string(),
+            });
+        }
+        Ok(())
+    }
 
-                let response = response.map_err(|_| AssetIoError::NotFound(path.to_path_buf()))?;
+    /// Sends the request described by `options` to `path`, retrying on failure
+    pub(crate) async fn fetch_with_options(
+        &self,
+        path: &str,
+        options: &RequestOptions,
+    ) -> Result<Vec<u8>, WebAssetError> {
+        let fetched = self.fetch_response(path, options).await?;
+        Ok(fetched.body)
+    }
 
-                let data = JsFuture::from(response.array_buffer().unwrap())
-                    .await
-                    .unwrap();
+    /// Sends the request described by `options` to `path`, retrying on failure, and
+    /// returns the body along with the headers it was sent with
+    async fn fetch_response(
+        &self,
+        path: &str,
+        options: &RequestOptions,
+    ) -> Result<Fetched, WebAssetError> {
+        let (uri, download) = self.send_with_retries(path, options).await?;
+        let limit = self.size_limit(options);
+        let validator = download.validator().map(str::to_string);
+        let headers = download.headers.clone();
+        // Metadata requests, like `HEAD`, have nothing to check or transform. Assets can
+        // be sent with other methods by their sidecar.
+        if options.method() != Method::Get && options.asset.is_none() {
+            let body = decode_body(&uri, download, limit)?;
+            return Ok(Fetched {
+                body,
+                validator,
+                headers,
+            });
+        }
+        if let Some(content_type) = download.headers.get("content-type") {
+            if self.validate_content_type && !content_type::is_expected(&uri, content_type) {
+                return Err(WebAssetError::ContentType {
+                    url: uri,
+                    content_type: content_type.to_string(),
+                });
+            }
+        }
+        let version = self
+            .watcher
+            .as_ref()
+            .map(|_| Version::of(download.validator(), &download.body));
+        let body = decode_body(&uri, download, limit)?;
+        if let Some(asset) = &options.asset {
+            self.verify(asset, path, &uri, &body).await?;
+        }
+        // Only once verified, so a tampered download isn't taken as the current version
+        if let Some((watcher, version)) = self.watcher.as_ref().zip(version) {
+            watcher.set_version(path, version);
+        }
+        Ok(Fetched {
+            body: self.transform_body(&uri, body)?,
+            validator,
+            headers,
+        })
+    }
 
-                let bytes = js_sys::Uint8Array::new(&data).to_vec();
+    /// Sends the request described by `options` to `path`, retrying on failure, and
+    /// returns the url it was sent to along with the response
+    pub(crate) async fn send_with_retries(
+        &self,
+        path: &str,
+        options: &RequestOptions,
+    ) -> Result<(String, Download), WebAssetError> {
+        let (uri, storage) = self.request_url(path)?;
+        let Some(renewer) = &self.url_renewer else {
+            return self.send_to(&uri, storage, options).await;
+        };
+        let current = self.renewed_urls.get(&uri);
+        match self
+            .send_to(current.as_deref().unwrap_or(&uri), storage, options)
+            .await
+        {
+            // Signed urls are rejected once they expire
+            Err(err) if err.status() == Some(403) => {
+                let Some(renewed) = renewer.renew(&uri).await else {
+                    return Err(err);
+                };
+                self.renewed_urls.insert(uri, renewed.clone());
+                self.send_to(&renewed, storage, options).await
+            }
+            result => result,
+        }
+    }
 
-                Ok(bytes)
+    /// Sends the request described by `options` to the request url `uri`, retrying on
+    /// failure
+    async fn send_to(
+        &self,
+        uri: &str,
+        storage: Storage,
+        options: &RequestOptions,
+    ) -> Result<(String, Download), WebAssetError> {
+        if self.offline.get() {
+            return Err(WebAssetError::Offline {
+                url: uri.to_string(),
+            });
+        }
+        if !self.hosts.allows(uri) {
+            return Err(WebAssetError::Blocked {
+                url: uri.to_string(),
             });
+        }
 
+        let mut timeouts = self.timeouts_for(uri).clone();
+        if let Some(timeout) = options.timeout {
+            timeouts.total = Some(timeout);
+        }
+        let auth = match storage {
+            Storage::Gcs => self.gcs.auth.clone(),
+            _ => self.auth.get(),
+        };
+        let mut reauthorized = false;
+        let mut attempt = 1;
+        let url = url::Url::parse(uri).ok();
+        let mut download = Download::default();
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = &options.download_to {
+            let file = std::fs::File::create(path).map_err(|err| WebAssetError::Transport {
+                url: uri.to_string(),
+                message: format!("can't create {}: {err}", path.display()),
+            })?;
+            download = Download::to_file(file);
+        }
+        loop {
+            let mut headers = match &url {
+                Some(url) => self.headers.for_url(url),
+                None => HeaderMap::new(),
+            };
+            // Browsers set this, and decompress bodies, themselves
+            #[cfg(not(target_arch = "wasm32"))]
+            if !headers.contains("accept-encoding") {
+                let _ = headers.insert("Accept-Encoding", crate::encoding::ACCEPT_ENCODING);
+            }
+            for (name, value) in options.headers.iter() {
+                let _ = headers.insert(name, value);
+            }
+            if let Some(user_agent) = &self.user_agent {
+                if !headers.contains("user-agent") {
+                    let _ = headers.insert("User-Agent", user_agent.clone());
+                }
+            }
+            if let Some(authorization) = url
+                .as_ref()
+                .and_then(|url| self.url_credentials.authorization(url))
+            {
+                let _ = headers.insert("Authorization", authorization);
+            }
             #[cfg(not(target_arch = "wasm32"))]
-            let fut = Box::pin(async move {
-                let bytes = surf::get(uri)
-                    .await
-                    .map_err(|_| AssetIoError::NotFound(path.to_path_buf()))?
-                    .body_bytes()
-                    .await
-                    .map_err(|_| AssetIoError::NotFound(path.to_path_buf()))?;
-                Ok(bytes)
+            if let Some(cookie) = url
+                .as_ref()
+                .zip(self.cookies.as_ref())
+                .and_then(|(url, cookies)| cookies.header(url))
+            {
+                let _ = headers.insert("Cookie", cookie);
+            }
+            if let Some(auth) = &auth {
+                if let Some(authorization) = auth.authorization(uri).await {
+                    if let Err(err) = headers.insert("Authorization", authorization) {
+                        warn!("Auth provider returned an {err}");
+                    }
+                }
+            }
+
+            self.paused.resumed().await;
+            let queued = self.net_stats.queued();
+            let permit = match &self.request_slots {
+                Some(slots) => {
+                    // Only sent while waiting, the response tells the size soon enough
+                    let ask_size = async {
+                        self.ask_size(uri, storage, options, &headers).await;
+                        futures_lite::future::pending().await
+                    };
+                    Some(futures_lite::future::or(slots.acquire(options.priority), ask_size).await)
+                }
+                None => None,
+            };
+            drop(queued);
+            let active = self.net_stats.active();
+            // Shows every request, retries included, in tracy and chrome traces
+            let span = info_span!(
+                "web_asset_request",
+                url = uri,
+                method = options.method().as_str(),
+                status = field::Empty,
+                bytes = field::Empty,
+                duration_ms = field::Empty,
+            );
+            let started = Instant::now();
+            let fetch = with_timeout(
+                timeouts.total,
+                self.fetch(uri, storage, options, headers, &timeouts, &mut download)
+                    .instrument(span.clone()),
+            );
+            let paused = async {
+                self.paused.paused().await;
+                None
+            };
+            let result = futures_lite::future::or(async { Some(fetch.await) }, paused).await;
+            span.record("duration_ms", started.elapsed().as_secs_f64() * 1000.0);
+            span.record("bytes", download.received());
+            drop((active, permit));
+            let Some(result) = result else {
+                // Continues from where it stopped once resumed, if the server supports ranges
+                continue;
+            };
+            let result = result.unwrap_or_else(|_| {
+                Err(WebAssetError::Timeout {
+                    url: uri.to_string(),
+                })
             });
+            match result {
+                Err(err) if err.status() == Some(401) && auth.is_some() && !reauthorized => {
+                    // The credentials were probably stale, try again with fresh ones
+                    if let Some(auth) = &auth {
+                        auth.invalidate(uri);
+                    }
+                    reauthorized = true;
+                }
+                Err(err) if err.is_transient() && self.retry.should_retry(attempt) => {
+                    let backoff = self.retry.backoff(attempt);
+                    warn!("{err}, retrying in {backoff:?}");
+                    sleep(backoff).await;
+                    attempt += 1;
+                }
+                Err(err) if err.status() == Some(304) => return Err(err),
+                Err(err) => {
+                    warn!("Failed to fetch asset: {err}");
+                    return Err(err);
+                }
+                Ok(()) => return Ok((uri.to_string(), download)),
+            }
+        }
+    }
+}
+
+impl WebAssetIo {
+    /// Sends a `HEAD` request for the asset downloaded with `options` from `uri`, so
+    /// its size is counted by [`WebAssetLoadProgress`] while it waits for a request slot
+    async fn ask_size(
+        &self,
+        uri: &str,
+        storage: Storage,
+        options: &RequestOptions,
+        headers: &HeaderMap,
+    ) {
+        let Some(asset) = options.asset.as_deref() else {
+            return;
+        };
+        // Signed requests are only valid for their method
+        if !self.size_requests
+            || options.method() != Method::Get
+            || storage != Storage::Http
+            || self.load_progress.knows_size(asset)
+        {
+            return;
+        }
+        #[allow(unused_mut)]
+        let mut headers = headers.clone();
+        // The size of the asset itself, not of a compressed transfer
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = headers.insert("Accept-Encoding", "identity");
+        let request = HttpRequest {
+            method: Method::Head,
+            headers,
+            ..HttpRequest::get(uri)
+        };
+        let sent = Instant::now();
+        let connect = self.timeouts_for(uri).connect;
+        match with_timeout(connect, self.client.send(request)).await {
+            Ok(Ok(response)) => {
+                self.net_stats.responded(response.status, sent.elapsed());
+                if let Some(size) = response.content_length().filter(|_| response.is_success()) {
+                    self.load_progress.expect(asset, size);
+                }
+            }
+            _ => self.net_stats.transport_error(),
+        }
+    }
+
+    /// Runs the body of `uri` through the response transforms
+    fn transform_body(&self, uri: &str, mut body: Vec<u8>) -> Result<Vec<u8>, WebAssetError> {
+        for transform in &self.response_transforms {
+            body = transform
+                .transform(uri, body)
+                .map_err(|message| WebAssetError::Decode {
+                    url: uri.to_string(),
+                    message,
+                })?;
+        }
+        Ok(body)
+    }
+
+    /// Cancels the download of the remote asset at `path`, along with every load and
+    /// prefetch waiting for it, which fail with [`WebAssetError::Cancelled`]. Returns
+    /// whether it was being downloaded.
+    pub fn cancel(&self, path: &Path) -> bool {
+        let resolved = self.resolve_path(path);
+        let path = resolved.as_deref().unwrap_or(path);
+        let uri = path.to_string_lossy();
+        let url = archive::split(&uri).map_or(&*uri, |(url, _)| url);
+        self.in_flight.cancel(url)
+    }
+
+    /// Downloads `uri`, sharing the request with concurrent loads of the same url
+    pub(crate) async fn fetch_shared(
+        &self,
+        uri: &str,
+        priority: i32,
+    ) -> Result<Vec<u8>, WebAssetError> {
+        loop {
+            match self.in_flight.join(uri) {
+                Join::Lead(leader) => {
+                    let mut tracked = self.load_progress.track(uri);
+                    let cancelled = async {
+                        leader.cancelled().await;
+                        Err(WebAssetError::Cancelled {
+                            url: uri.to_string(),
+                        })
+                    };
+                    // Dropping the download aborts the transfer
+                    let result =
+                        futures_lite::future::or(self.fetch_cached(uri, priority), cancelled).await;
+                    if let Some(err) = result
+                        .as_ref()
+                        .err()
+                        .filter(|err| !matches!(err, WebAssetError::Cancelled { .. }))
+                    {
+                        // Reported once, however many loads were waiting for it
+                        let _ = self.failed.send(err.into());
+                        self.net_stats.failed();
+                    }
+                    if let Ok(body) = &result {
+                        tracked.loaded(body.len());
+                    }
+                    drop(tracked);
+                    leader.finish(&result);
+                    return result;
+                }
+                Join::Wait(receiver) => {
+                    if let Ok(result) = receiver.recv().await {
+                        return result;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Loads `uri` from memory if it was loaded recently, downloading it otherwise.
+    ///
+    /// Archives aren't kept, they're in memory already.
+    async fn fetch_remembered(&self, uri: &str, priority: i32) -> Result<Vec<u8>, WebAssetError> {
+        let Some(memory) = &self.memory_cache else {
+            return self.fetch_shared(uri, priority).await;
+        };
+        if let Some(body) = memory.get(uri) {
+            self.cache_stats.hit(body.len());
+            return Ok(body);
+        }
+        let body = self.fetch_shared(uri, priority).await?;
+        memory.insert(uri, &body);
+        Ok(body)
+    }
+
+    /// Downloads the asset `uri`, from the cache if it's still fresh there, revalidating
+    /// and updating the cached copy otherwise
+    async fn fetch_cached(&self, uri: &str, priority: i32) -> Result<Vec<u8>, WebAssetError> {
+        if let Some(body) = self
+            .watcher
+            .as_ref()
+            .and_then(|watcher| watcher.take_change(uri))
+        {
+            return Ok(body);
+        }
+        let (options, policy) = self.asset_options(uri, priority).await?;
+        let offline = || WebAssetError::Offline {
+            url: uri.to_string(),
+        };
+        let cache = self
+            .cache
+            .as_ref()
+            .filter(|_| policy != Some(CachePolicy::NoStore));
+        let Some(cache) = cache else {
+            if self.offline.get() {
+                return Err(offline());
+            }
+            let fetched = self.fetch_from_origin(uri, &options).await?;
+            return Ok(fetched.body);
+        };
+        let Some(cached) = cache.get(uri).await else {
+            if self.offline.get() {
+                return Err(offline());
+            }
+            let body = self.refresh(cache, uri, options, None).await?;
+            // Nothing was cached, so there's always a body
+            let body = body.unwrap_or_default();
+            self.cache_stats.miss(body.len());
+            return Ok(body);
+        };
+        let strategy = match policy {
+            Some(CachePolicy::Strategy(strategy)) => strategy,
+            _ => cache.strategy(uri),
+        };
+        let stale_while_revalidate = strategy == CacheStrategy::StaleWhileRevalidate;
+        // Stale copies are better than nothing while offline
+        let fresh = cached.is_fresh() || is_content_addressed(uri);
+        if fresh || self.offline.get() || stale_while_revalidate {
+            if !fresh && !self.offline.get() {
+                let _ = self.stale.send(uri.to_string());
+            }
+            self.record_cached_version(uri, cached.validator.as_deref());
+            self.cache_stats.hit(cached.body.len());
+            return Ok(cached.body);
+        }
+        match self.refresh(cache, uri, options, Some(&cached)).await? {
+            Some(body) => {
+                self.cache_stats.miss(body.len());
+                Ok(body)
+            }
+            None => {
+                self.cache_stats.hit(cached.body.len());
+                Ok(cached.body)
+            }
+        }
+    }
+
+    /// Downloads `uri` and caches it, unless the server says the `cached` version is
+    /// still current, in which case it returns `None`
+    async fn refresh(
+        &self,
+        cache: &Cache,
+        uri: &str,
+        mut options: RequestOptions,
+        cached: Option<&CacheEntry>,
+    ) -> Result<Option<Vec<u8>>, WebAssetError> {
+        if let Some(cached) = cached {
+            cached.add_conditional_headers(&mut options.headers);
+        }
+        match (self.fetch_from_origin(uri, &options).await, cached) {
+            (
+                Err(WebAssetError::Status {
+                    status: 304,
+                    mut headers,
+                    ..
+                }),
+                Some(cached),
+            ) => {
+                self.record_cached_version(uri, cached.validator.as_deref());
+                // The `304` says how long the cached body stays fresh now, and may leave
+                // out the validators it was matched against
+                for (name, value) in [
+                    ("ETag", &cached.etag),
+                    ("Last-Modified", &cached.last_modified),
+                ] {
+                    if let (None, Some(value)) = (headers.get(name), value) {
+                        let _ = headers.insert(name, value.clone());
+                    }
+                }
+                let fetched = Fetched {
+                    body: cached.body.clone(),
+                    validator: cached.validator.clone(),
+                    headers,
+                };
+                if let Some(entry) = CacheEntry::new(uri, &fetched) {
+                    cache.put(&entry).await;
+                }
+                Ok(None)
+            }
+            (Ok(fetched), _) => {
+                if let Some(entry) = CacheEntry::new(uri, &fetched) {
+                    cache.put(&entry).await;
+                }
+                Ok(Some(fetched.body))
+            }
+            (Err(err), _) => Err(err),
+        }
+    }
+
+    /// Checks whether the cached copy of `uri` is still current, downloading it if not.
+    /// Returns whether it changed.
+    pub(crate) async fn revalidate(&self, uri: &str) -> Result<bool, WebAssetError> {
+        let (Some(cache), false) = (&self.cache, self.offline.get()) else {
+            return Ok(false);
+        };
+        let (options, policy) = self.asset_options(uri, BACKGROUND_PRIORITY).await?;
+        if policy == Some(CachePolicy::NoStore) {
+            return Ok(false);
+        }
+        let cached = cache.get(uri).await;
+        let body = self.refresh(cache, uri, options, cached.as_ref()).await?;
+        Ok(body.is_some())
+    }
+
+    /// The options of requests for the asset `uri`, with its sidecar merged in if there
+    /// is one, and how its sidecar says it's cached
+    pub(crate) async fn asset_options(
+        &self,
+        uri: &str,
+        priority: i32,
+    ) -> Result<(RequestOptions, Option<CachePolicy>), WebAssetError> {
+        let mut options = RequestOptions {
+            asset: Some(uri.to_string()),
+            priority,
+            ..RequestOptions::default()
+        };
+        if let Some((_, request)) = self
+            .asset_requests
+            .iter()
+            .find(|(pattern, _)| crate::fallback::matches(pattern, uri))
+        {
+            options.method = Some(request.method.clone());
+            options.body = request.body.clone();
+            options.headers = request.headers.clone();
+        }
+        let Some(sidecar) = self.sidecar(uri).await? else {
+            return Ok((options, None));
+        };
+        options.method = sidecar.method.or(options.method);
+        options.body = sidecar.body.or(options.body);
+        for (name, value) in sidecar.headers.iter() {
+            let _ = options.headers.insert(name, value);
+        }
+        options.timeout = sidecar.timeout;
+        Ok((options, sidecar.cache))
+    }
+
+    /// The sidecar of `uri`, if sidecars are enabled and it has one
+    async fn sidecar(&self, uri: &str) -> Result<Option<Sidecar>, WebAssetError> {
+        let Some(suffix) = &self.request_sidecar else {
+            return Ok(None);
+        };
+        let url = sidecar_url(uri, suffix);
+        let options = RequestOptions {
+            priority: BACKGROUND_PRIORITY,
+            ..RequestOptions::default()
+        };
+        let fetched = match self.fetch_from_origin(&url, &options).await {
+            Ok(fetched) => fetched,
+            // Most assets don't have one, and offline loads use the defaults
+            Err(err)
+                if err.status() == Some(404) || matches!(err, WebAssetError::Offline { .. }) =>
+            {
+                return Ok(None)
+            }
+            Err(err) => return Err(err),
+        };
+        Sidecar::parse(&fetched.body)
+            .map(Some)
+            .map_err(|message| WebAssetError::Decode { url, message })
+    }
+
+    /// Lets the watcher know the version of `uri` with `validator` was loaded from the
+    /// cache, as if it was downloaded
+    fn record_cached_version(&self, uri: &str, validator: Option<&str>) {
+        if let (Some(watcher), Some(validator)) = (&self.watcher, validator) {
+            watcher.set_version(uri, Version::Validator(validator.to_string()));
+        }
+    }
+
+    /// Downloads `uri` from wherever it's served
+    pub(crate) async fn fetch_from_origin(
+        &self,
+        uri: &str,
+        options: &RequestOptions,
+    ) -> Result<Fetched, WebAssetError> {
+        match uri.starts_with("ipfs://") {
+            true => self.fetch_from_gateways(uri, options).await,
+            false => self.fetch_from_mirrors(uri, options).await,
+        }
+    }
+
+    /// Downloads `ipfs://` content, trying each gateway until one succeeds
+    async fn fetch_from_gateways(
+        &self,
+        uri: &str,
+        options: &RequestOptions,
+    ) -> Result<Fetched, WebAssetError> {
+        let invalid = |message: &str| WebAssetError::Transport {
+            url: uri.to_string(),
+            message: message.to_string(),
+        };
+        let urls = self
+            .ipfs
+            .gateway_urls(uri)
+            .ok_or_else(|| invalid("invalid path, expected ipfs://<cid>/path"))?;
+        let mut last_error = invalid("no ipfs gateways configured");
+        for url in urls {
+            match self.fetch_response(&url, options).await {
+                Ok(fetched) => return Ok(fetched),
+                // Content addressed, so any gateway's copy is the cached one
+                Err(err) if err.status() == Some(304) => return Err(err),
+                Err(err) => last_error = err,
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Downloads `uri`, trying its mirrors in order if it fails
+    async fn fetch_from_mirrors(
+        &self,
+        uri: &str,
+        options: &RequestOptions,
+    ) -> Result<Fetched, WebAssetError> {
+        let mut result = self.fetch_response(uri, options).await;
+        for mirror in self.mirror_urls(uri) {
+            // Not modified isn't a failure
+            let Some(err) = result
+                .as_ref()
+                .err()
+                .filter(|err| err.status() != Some(304))
+            else {
+                break;
+            };
+            warn!("{err}, trying mirror {mirror}");
+            result = self.fetch_response(&mirror, options).await;
+        }
+        result
+    }
+
+    /// `uri` on each mirror of its base url
+    fn mirror_urls(&self, uri: &str) -> Vec<String> {
+        let base = self
+            .mirrors
+            .keys()
+            .filter(|base| uri.starts_with(base.as_str()))
+            .max_by_key(|base| base.len());
+        let Some(base) = base else {
+            return Vec::new();
+        };
+        let rest = &uri[base.len()..];
+        self.mirrors[base]
+            .iter()
+            .map(|mirror| format!("{mirror}{rest}"))
+            .collect()
+    }
+
+    /// The archive at `url`, downloading and indexing it if needed
+    pub(crate) async fn archive(
+        &self,
+        url: &str,
+        priority: i32,
+    ) -> Result<Arc<Archive>, WebAssetError> {
+        if let Some(archive) = self.archives.get(url) {
+            return Ok(archive);
+        }
+        let bytes = self.fetch_shared(url, priority).await?;
+        let archive = Archive::parse(url, bytes, self.max_download_size).map_err(|message| {
+            WebAssetError::Decode {
+                url: url.to_string(),
+                message,
+            }
+        })?;
+        Ok(self.archives.insert(url, archive))
+    }
+
+    /// Loads the file `entry` from the archive at `url`
+    async fn load_from_archive(
+        &self,
+        url: &str,
+        entry: &str,
+        priority: i32,
+    ) -> Result<Vec<u8>, AssetIoError> {
+        let archive_path = Path::new(url);
+        let archive = self
+            .archive(url, priority)
+            .await
+            .map_err(|err| err.into_asset_io_error(archive_path, &self.status_errors))?;
+        match archive.read(entry, self.max_download_size) {
+            Some(Ok(bytes)) => Ok(bytes),
+            Some(Err(message)) => {
+                let err = WebAssetError::Decode {
+                    url: url.to_string(),
+                    message,
+                };
+                Err(err.into_asset_io_error(archive_path, &self.status_errors))
+            }
+            None => Err(AssetIoError::NotFound(archive_path.join(entry))),
+        }
+    }
+
+    /// The archive at `url`, blocking until it has been downloaded
+    #[cfg(not(target_arch = "wasm32"))]
+    fn archive_blocking(&self, url: &str) -> Result<Arc<Archive>, AssetIoError> {
+        futures_lite::future::block_on(self.archive(url, 0))
+            .map_err(|err| err.into_asset_io_error(Path::new(url), &self.status_errors))
+    }
+
+    /// The entries of the remote directory `url`, from its manifest
+    fn read_manifest(&self, url: &str) -> Result<Vec<String>, AssetIoError> {
+        let Some(name) = &self.directory_manifest else {
+            return Err(AssetIoError::Io(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "only archives can be listed over http",
+            )));
+        };
+        let manifest_url = format!("{}/{name}", url.trim_end_matches('/'));
+        let options = RequestOptions {
+            max_size: Some(MAX_MANIFEST_SIZE),
+            ..RequestOptions::default()
+        };
+        let bytes = self.fetch_blocking(&manifest_url, &options)?;
+        manifest::parse(&bytes).map_err(|message| {
+            let err = WebAssetError::Decode {
+                url: manifest_url.clone(),
+                message,
+            };
+            err.into_asset_io_error(Path::new(&manifest_url), &self.status_errors)
+        })
+    }
+
+    /// Sends a request to `url`, blocking until it's done
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn fetch_blocking(
+        &self,
+        url: &str,
+        options: &RequestOptions,
+    ) -> Result<Vec<u8>, AssetIoError> {
+        futures_lite::future::block_on(self.fetch_with_options(url, options))
+            .map_err(|err| err.into_asset_io_error(Path::new(url), &self.status_errors))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn fetch_blocking(
+        &self,
+        url: &str,
+        _options: &RequestOptions,
+    ) -> Result<Vec<u8>, AssetIoError> {
+        Err(AssetIoError::Io(io::Error::new(
+            io::ErrorKind::WouldBlock,
+            format!("can't download {url} without blocking the browser"),
+        )))
+    }
+
+    /// The archive at `url`, if it has been downloaded already
+    #[cfg(target_arch = "wasm32")]
+    fn archive_blocking(&self, url: &str) -> Result<Arc<Archive>, AssetIoError> {
+        // Blocking would freeze the browser, the archive has to be loaded already
+        self.archives.get(url).ok_or_else(|| {
+            AssetIoError::Io(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                format!("{url} must be loaded before its contents can be listed"),
+            ))
+        })
+    }
+}
+
+impl WebAssetIo {
+    /// Expands `path` if its scheme is an alias, e.g. `cdn://hero.png`
+    fn expand_alias(&self, path: &Path) -> Option<PathBuf> {
+        let base = self.aliases.get(&scheme::scheme(path)?)?;
+        let (_, rest) = path.to_str()?.split_once("://")?;
+        let expanded = format!(
+            "{}/{}",
+            base.trim_end_matches('/'),
+            rest.trim_start_matches('/')
+        );
+        Some(PathBuf::from(expanded))
+    }
+
+    /// The path actually loaded for `path`, with aliases expanded and remote urls
+    /// rewritten and normalized, or `None` if it's unchanged
+    pub(crate) fn resolve_path(&self, path: &Path) -> Option<PathBuf> {
+        let pinned = self
+            .pinned
+            .as_ref()
+            .zip(path.to_str())
+            .and_then(|(pinned, path)| pinned.get(path));
+        match pinned {
+            Some(url) => Some(
+                self.resolve_unpinned(Path::new(url))
+                    .unwrap_or_else(|| PathBuf::from(url)),
+            ),
+            None => self.resolve_unpinned(path),
+        }
+    }
 
-            fut
+    /// [`Self::resolve_path`], without looking `path` up in the pinned manifest
+    fn resolve_unpinned(&self, path: &Path) -> Option<PathBuf> {
+        let expanded = self.expand_alias(path);
+        let resolved = expanded.as_deref().unwrap_or(path);
+        if !is_remote(resolved) {
+            return expanded;
+        }
+        let resolved = resolved.to_str()?;
+        // Shared by every path with the same content, whatever its extension
+        if let Some(digest) = cas::digest(resolved) {
+            return Some(PathBuf::from(format!("cas://{digest}")));
+        }
+        let mut url =
+            rewrite::strip_loader_extension(resolved).unwrap_or_else(|| resolved.to_string());
+        for rewriter in &self.url_rewriters {
+            if let Some(new_url) = rewriter.rewrite(&url) {
+                url = new_url;
+            }
+        }
+        let url = rewrite::normalize(&url).unwrap_or(url);
+        let url = self.url_credentials.strip(&url).unwrap_or(url);
+        (Path::new(&url) != path).then(|| PathBuf::from(url))
+    }
+
+    fn load_resolved<'a>(
+        &'a self,
+        path: &'a Path,
+        priority: i32,
+    ) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        if let Some(handler) = self.scheme_handler(path) {
+            handler.load(path)
+        } else if is_remote(path) {
+            Box::pin(async move {
+                let uri = remote_uri(path)?;
+                if let Some(local) = self
+                    .local_overrides
+                    .as_deref()
+                    .and_then(|dir| overrides::path(dir, uri))
+                {
+                    match self.default_io.load_path(&local).await {
+                        Err(AssetIoError::NotFound(_)) => {}
+                        loaded => return loaded,
+                    }
+                }
+                let body = match archive::split(uri) {
+                    Some((url, entry)) if !entry.is_empty() => {
+                        self.load_from_archive(url, entry, priority).await
+                    }
+                    _ => self
+                        .fetch_remembered(uri, priority)
+                        .await
+                        .map_err(|err| err.into_asset_io_error(path, &self.status_errors)),
+                }?;
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Some(dir) = &self.record_to {
+                    overrides::record(dir, uri, &body).await;
+                }
+                Ok(body)
+            })
         } else {
             self.default_io.load_path(path)
         }
     }
 
+    /// The custom handler registered for the scheme of `path`, if any
+    pub(crate) fn scheme_handler(&self, path: &Path) -> Option<&dyn SchemeHandler> {
+        let scheme = scheme::scheme(path)?;
+        self.scheme_handlers.get(&scheme).map(|handler| &**handler)
+    }
+}
+
+impl AssetIo for WebAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        if self
+            .pinned
+            .as_ref()
+            .is_some_and(|pinned| !pinned.is_loaded())
+        {
+            return Box::pin(async move {
+                self.load_pinned_manifest().await;
+                self.load_path(path).await
+            });
+        }
+        let resolved = self.resolve_path(path);
+        let url = resolved.as_deref().unwrap_or(path);
+        let priority = [path, url]
+            .iter()
+            .find_map(|path| self.priorities.get(path.to_str()?))
+            .unwrap_or_default();
+        if resolved.is_none() && self.fallbacks.is_empty() {
+            return self.load_resolved(path, priority);
+        }
+        Box::pin(async move {
+            let url = resolved.as_deref().unwrap_or(path);
+            let err = match self.load_resolved(url, priority).await {
+                Ok(bytes) => return Ok(bytes),
+                Err(err) => err,
+            };
+            // Nobody wants a cancelled asset, not even its fallback
+            let cancelled = matches!(
+                WebAssetError::from_asset_io_error(&err),
+                Some(WebAssetError::Cancelled { .. })
+            );
+            let fallback = match (is_remote(url), path.to_str(), url.to_str()) {
+                (true, Some(path), Some(url)) if !cancelled => self.fallbacks.get(path, url),
+                _ => None,
+            };
+            let Some(fallback) = fallback else {
+                return Err(err);
+            };
+            warn!(
+                "Loading {} instead of {}: {err}",
+                fallback.display(),
+                path.display()
+            );
+            self.default_io.load_path(fallback).await
+        })
+    }
+
     fn read_directory(
         &self,
         path: &Path,
     ) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        let requested = path;
+        let resolved = self.resolve_path(path);
+        let path = resolved.as_deref().unwrap_or(path);
+        if let Some(handler) = self.scheme_handler(path) {
+            return handler.read_directory(path);
+        }
+        if is_remote(path) {
+            let uri = remote_uri(path)?;
+            let Some((url, dir)) = archive::split(uri) else {
+                // Entries are listed under the requested path, so aliases stay intact
+                let requested = requested.to_str().unwrap_or(uri);
+                #[cfg(feature = "webdav")]
+                if crate::webdav::is_http(uri) {
+                    match self.read_dav_directory(requested, uri) {
+                        Ok(entries) => return Ok(Box::new(entries.into_iter())),
+                        Err(err) if crate::webdav::unsupported(&err) => {}
+                        Err(err) => return Err(err),
+                    }
+                }
+                let root = requested.trim_end_matches('/');
+                let entries: Vec<_> = self
+                    .read_manifest(uri)?
+                    .into_iter()
+                    .map(|name| PathBuf::from(format!("{root}/{name}")))
+                    .collect();
+                return Ok(Box::new(entries.into_iter()));
+            };
+            let archive = self.archive_blocking(url)?;
+            if !archive.is_dir(dir) {
+                return Err(AssetIoError::NotFound(path.to_path_buf()));
+            }
+            let root = Path::new(url);
+            let entries: Vec<_> = archive.list(dir).map(|name| root.join(name)).collect();
+            return Ok(Box::new(entries.into_iter()));
+        }
         self.default_io.read_directory(path)
     }
 
@@ -72,15 +1415,21 @@ impl AssetIo for WebAssetIo {
         to_watch: &Path,
         to_reload: Option<PathBuf>,
     ) -> Result<(), AssetIoError> {
-        if is_http(to_watch) {
-            // TODO: we could potentially start polling over http here
-            // but should probably only be done if the server supports caching
-
-            // This is where we would write to a self.network_watcher
-
-            Ok(()) // Pretend everything is fine
+        let resolved = self.resolve_path(to_watch);
+        let to_reload = to_reload.unwrap_or_else(|| to_watch.to_path_buf());
+        let to_watch = resolved.as_deref().unwrap_or(to_watch);
+        if self.scheme_handler(to_watch).is_some() {
+            Ok(())
+        } else if is_remote(to_watch) {
+            let uri = to_watch.to_str().unwrap_or_default();
+            if let (Some(watcher), false) = (&self.watcher, is_content_addressed(uri)) {
+                let url = archive::split(uri).map_or(uri, |(url, _)| url);
+                watcher.watch(url, to_reload);
+            }
+            Ok(())
         } else {
-            self.default_io.watch_path_for_changes(to_watch, to_reload)
+            self.default_io
+                .watch_path_for_changes(to_watch, Some(to_reload))
         }
     }
 
@@ -89,14 +1438,262 @@ impl AssetIo for WebAssetIo {
     }
 
     fn is_dir(&self, path: &Path) -> bool {
-        if is_http(path) {
-            false
+        let requested = path.to_str().unwrap_or_default();
+        let resolved = self.resolve_path(path);
+        let path = resolved.as_deref().unwrap_or(path);
+        if let Some(handler) = self.scheme_handler(path) {
+            handler.is_dir(path)
+        } else if is_remote(path) {
+            let uri = path.to_str().unwrap_or_default();
+            match archive::split(uri) {
+                Some((url, dir)) => self
+                    .archive_blocking(url)
+                    .is_ok_and(|archive| archive.is_dir(dir)),
+                #[cfg(feature = "webdav")]
+                None if crate::webdav::is_http(uri) && !requested.ends_with('/') => {
+                    self.is_dav_collection(requested, uri).unwrap_or(false)
+                }
+                // Outside archives, urls ending with a slash are directories
+                None => {
+                    requested.ends_with('/')
+                        && (cfg!(feature = "webdav") || self.directory_manifest.is_some())
+                }
+            }
         } else {
             self.default_io.is_dir(path)
         }
     }
 
     fn get_metadata(&self, path: &Path) -> Result<bevy::asset::Metadata, AssetIoError> {
+        #[cfg(feature = "webdav")]
+        let requested = path.to_str().unwrap_or_default();
+        let resolved = self.resolve_path(path);
+        let path = resolved.as_deref().unwrap_or(path);
+        if let Some(handler) = self.scheme_handler(path) {
+            return handler.get_metadata(path);
+        }
+        let uri = path.to_str().unwrap_or_default();
+        if is_remote(path) && archive::split(uri).is_none() && !is_content_addressed(uri) {
+            #[cfg(feature = "webdav")]
+            if crate::webdav::is_http(uri) {
+                match self.dav_metadata(requested, uri) {
+                    Err(err) if crate::webdav::unsupported(&err) => {}
+                    result => return result,
+                }
+            }
+            return self.head_metadata(uri);
+        }
         self.default_io.get_metadata(path)
     }
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use bevy::prelude::*;
+    use futures_lite::future;
+
+    use super::*;
+    use crate::{
+        test_util::{MockResponse, MockServer},
+        CacheConfig, WebAssetPlugin,
+    };
+
+    fn app(plugin: WebAssetPlugin) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(plugin);
+        app
+    }
+
+    fn asset_io(app: &App) -> &WebAssetIo {
+        let asset_server = app.world.resource::<AssetServer>();
+        asset_server.asset_io().downcast_ref().unwrap()
+    }
+
+    fn load(asset_io: &WebAssetIo, path: &str) -> Result<Vec<u8>, AssetIoError> {
+        future::block_on(asset_io.load_path(Path::new(path)))
+    }
+
+    #[test]
+    fn retries_transient_errors() {
+        let server = MockServer::start();
+        server.mock_sequence(
+            "/flaky.png",
+            [
+                MockResponse::new(503),
+                MockResponse::new(503),
+                MockResponse::ok("png"),
+            ],
+        );
+        server.mock("/down.png", MockResponse::new(503));
+        server.mock("/gone.png", MockResponse::new(410));
+        let app = app(server.plugin());
+        let asset_io = asset_io(&app);
+
+        assert_eq!(load(asset_io, "mock://flaky.png").unwrap(), b"png");
+        assert_eq!(server.requests_to("/flaky.png").len(), 3);
+        assert!(load(asset_io, "mock://down.png").is_err());
+        assert_eq!(server.requests_to("/down.png").len(), 3);
+        assert!(load(asset_io, "mock://gone.png").is_err());
+        assert_eq!(server.requests_to("/gone.png").len(), 1);
+    }
+
+    #[test]
+    fn resumes_interrupted_downloads() {
+        let server = MockServer::start();
+        server.mock_sequence(
+            "/level.bin",
+            [
+                MockResponse::ok("0123456789")
+                    .header("Accept-Ranges", "bytes")
+                    .etag("\"v1\"")
+                    .cut_off(4),
+                MockResponse::new(206)
+                    .body("456789")
+                    .header("Content-Range", "bytes 4-9/10")
+                    .etag("\"v1\""),
+            ],
+        );
+        let app = app(server.plugin());
+
+        assert_eq!(
+            load(asset_io(&app), "mock://level.bin").unwrap(),
+            b"0123456789"
+        );
+        let requests = server.requests_to("/level.bin");
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].headers.get("range"), None);
+        assert_eq!(requests[1].headers.get("range"), Some("bytes=4-"));
+        assert_eq!(requests[1].headers.get("if-range"), Some("\"v1\""));
+    }
+
+    #[test]
+    fn shares_concurrent_downloads() {
+        let server = MockServer::start();
+        server.mock(
+            "/hero.png",
+            MockResponse::ok("png").delay(Duration::from_millis(200)),
+        );
+        let app = app(server.plugin());
+        let asset_io = asset_io(&app);
+
+        let (first, second) = future::block_on(future::zip(
+            asset_io.load_path(Path::new("mock://hero.png")),
+            asset_io.load_path(Path::new("mock://hero.png")),
+        ));
+        assert_eq!(first.unwrap(), b"png");
+        assert_eq!(second.unwrap(), b"png");
+        assert_eq!(server.requests_to("/hero.png").len(), 1);
+    }
+
+    #[test]
+    fn cancels_downloads() {
+        let server = MockServer::start();
+        server.mock(
+            "/hero.png",
+            MockResponse::ok("png").delay(Duration::from_secs(1)),
+        );
+        let app = app(server.plugin());
+        let asset_io = asset_io(&app);
+
+        let url = server.url("hero.png");
+        let (result, cancelled) =
+            future::block_on(future::zip(asset_io.fetch_shared(&url, 0), async {
+                sleep(Duration::from_millis(100)).await;
+                asset_io.cancel(Path::new("mock://hero.png"))
+            }));
+        assert!(cancelled);
+        assert!(matches!(result, Err(WebAssetError::Cancelled { .. })));
+        assert!(!asset_io.cancel(Path::new("mock://hero.png")));
+
+        // Nothing is left waiting for the cancelled download
+        server.mock("/hero.png", MockResponse::ok("png"));
+        assert_eq!(load(asset_io, "mock://hero.png").unwrap(), b"png");
+    }
+
+    #[test]
+    fn revalidates_cached_assets() {
+        let directory = std::env::temp_dir().join(format!("bevy_web_asset_{}", fastrand::u64(..)));
+        let server = MockServer::start();
+        let last_modified = "Wed, 21 Oct 2015 07:28:00 GMT";
+        server.mock(
+            "/hero.png",
+            MockResponse::ok("v1")
+                .etag("\"1\"")
+                .last_modified(last_modified),
+        );
+        let app = app(WebAssetPlugin {
+            cache: Some(CacheConfig {
+                directory: Some(directory.clone()),
+                ..default()
+            }),
+            ..server.plugin()
+        });
+        let asset_io = asset_io(&app);
+
+        assert_eq!(load(asset_io, "mock://hero.png").unwrap(), b"v1");
+        // Not modified, so the cached body is used
+        assert_eq!(load(asset_io, "mock://hero.png").unwrap(), b"v1");
+        let requests = server.requests_to("/hero.png");
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].headers.get("if-none-match"), None);
+        assert_eq!(requests[1].headers.get("if-none-match"), Some("\"1\""));
+        assert_eq!(
+            requests[1].headers.get("if-modified-since"),
+            Some(last_modified)
+        );
+
+        server.mock("/hero.png", MockResponse::ok("v2").etag("\"2\""));
+        assert_eq!(load(asset_io, "mock://hero.png").unwrap(), b"v2");
+        assert_eq!(load(asset_io, "mock://hero.png").unwrap(), b"v2");
+        let requests = server.requests_to("/hero.png");
+        assert_eq!(requests[3].headers.get("if-none-match"), Some("\"2\""));
+
+        // Fresh entries aren't revalidated
+        server.mock(
+            "/fresh.png",
+            MockResponse::ok("fresh").header("Cache-Control", "max-age=3600"),
+        );
+        assert_eq!(load(asset_io, "mock://fresh.png").unwrap(), b"fresh");
+        assert_eq!(load(asset_io, "mock://fresh.png").unwrap(), b"fresh");
+        assert_eq!(server.requests_to("/fresh.png").len(), 1);
+
+        drop(app);
+        let _ = std::fs::remove_dir_all(directory);
+    }
+
+    #[test]
+    fn keeps_not_modified_assets_fresh() {
+        let directory = std::env::temp_dir().join(format!("bevy_web_asset_{}", fastrand::u64(..)));
+        let server = MockServer::start();
+        server.mock_sequence(
+            "/hero.png",
+            [
+                MockResponse::ok("v1")
+                    .etag("\"1\"")
+                    .header("Cache-Control", "no-cache"),
+                MockResponse::ok("v1")
+                    .etag("\"1\"")
+                    .header("Cache-Control", "max-age=3600"),
+            ],
+        );
+        let app = app(WebAssetPlugin {
+            cache: Some(CacheConfig {
+                directory: Some(directory.clone()),
+                ..default()
+            }),
+            ..server.plugin()
+        });
+        let asset_io = asset_io(&app);
+
+        assert_eq!(load(asset_io, "mock://hero.png").unwrap(), b"v1");
+        assert_eq!(load(asset_io, "mock://hero.png").unwrap(), b"v1");
+        // The `304` made the cached copy fresh for another hour
+        assert_eq!(load(asset_io, "mock://hero.png").unwrap(), b"v1");
+        let requests = server.requests_to("/hero.png");
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].headers.get("if-none-match"), Some("\"1\""));
+
+        drop(app);
+        let _ = std::fs::remove_dir_all(directory);
+    }
+}
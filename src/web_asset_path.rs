@@ -0,0 +1,82 @@
+use bevy::asset::AssetPath;
+use std::path::PathBuf;
+
+/// A url to load, kept intact on its way through the asset server.
+///
+/// Bevy reads everything after a `#` in asset paths as the label of a sub-asset, e.g.
+/// `Scene0` in `scene.gltf#Scene0`, so urls with a fragment lose their end, or the
+/// fragment ends up in the extension Bevy picks the asset loader by. Fragments are
+/// never sent to servers, so they're dropped here, and labels are set separately. A
+/// `#` that's part of the url's path or query has to be escaped as `%23`, and is sent
+/// that way.
+///
+/// Bevy also picks the asset loader by the end of the path, so the extension of urls
+/// with a query, like `tex.png?sig=abc`, is repeated after it for Bevy, and removed
+/// again before the request is sent.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::WebAssetPath;
+/// fn load_level(asset_server: Res<AssetServer>) {
+///     let scene = asset_server.load_untyped(
+///         WebAssetPath::new("https://example.com/levels/forest.gltf#v2").with_label("Scene0"),
+///     );
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WebAssetPath {
+    url: String,
+    label: Option<String>,
+}
+
+impl WebAssetPath {
+    /// The asset at `url`, with any fragment removed
+    pub fn new(url: impl Into<String>) -> Self {
+        let mut url = url.into();
+        if let Some(fragment) = url.find('#') {
+            url.truncate(fragment);
+        }
+        Self { url, label: None }
+    }
+
+    /// Loads the sub-asset `label` of the asset, e.g. `Scene0` of a glTF file
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// The url that's requested, without a fragment
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The sub-asset label, if any
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+}
+
+impl From<WebAssetPath> for AssetPath<'static> {
+    fn from(path: WebAssetPath) -> Self {
+        AssetPath::new(PathBuf::from(with_loader_extension(path.url)), path.label)
+    }
+}
+
+/// `url` with the extension of its path added after its query, where Bevy looks for it
+fn with_loader_extension(url: String) -> String {
+    let Some((path, _)) = url.split_once('?') else {
+        return url;
+    };
+    let name = path.rsplit('/').next().unwrap_or_default();
+    match name.rsplit_once('.') {
+        Some((_, extension))
+            if !extension.is_empty()
+                && !url
+                    .to_ascii_lowercase()
+                    .ends_with(&format!(".{}", extension.to_ascii_lowercase())) =>
+        {
+            format!("{url}&.{extension}")
+        }
+        _ => url,
+    }
+}
@@ -1,6 +1,27 @@
 use bevy::prelude::*;
 
-use super::WebAssetIo;
+use bevy::utils::HashMap;
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use super::{
+    cache::{revalidate_stale, Cache, CacheStrategy, StaleReceiver, WebAssetCacheStats},
+    error::{send_failure_events, FailureReceiver},
+    faults::FaultyClient,
+    host_pattern::HostFilter,
+    http_client::HttpClient,
+    integrity::{send_tamper_events, TamperReceiver},
+    memory_cache::MemoryCache,
+    pinned::PinnedManifest,
+    preload::{self, send_preloaded_events, PreloadReceiver},
+    priority::RequestSlots,
+    progress::{send_progress_events, ProgressReceiver},
+    sse,
+    throttle::{Throttle, Throttles},
+    watch::{poll_for_changes, Watcher},
+    websocket, HeaderMap, InvalidHeader, RetryPolicy, Timeouts, WebAssetAuth, WebAssetFailed,
+    WebAssetHeaders, WebAssetIo, WebAssetLoadProgress, WebAssetNetStats, WebAssetOffline,
+    WebAssetPriorities, WebAssetProgress, WebAssetTampered, WebAssetsPreloaded,
+};
 
 /// Add this plugin to bevy to support loading http and https urls.
 ///
@@ -13,19 +34,670 @@ use super::WebAssetIo;
 /// # use bevy_web_asset::WebAssetPlugin;
 ///
 /// let mut app = App::new();
-/// app.add_plugin(WebAssetPlugin);
+/// app.add_plugin(WebAssetPlugin::default());
+/// app.add_plugins(DefaultPlugins);
+/// ```
+///
+/// Flaky connections are retried with exponential backoff. Use the fields to
+/// tune this:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{RetryPolicy, WebAssetPlugin};
+/// # use std::time::Duration;
+///
+/// let mut app = App::new();
+/// app.add_plugin(WebAssetPlugin {
+///     retry: RetryPolicy {
+///         max_attempts: 5,
+///         initial_backoff: Duration::from_millis(500),
+///         ..default()
+///     },
+///     ..default()
+/// });
+/// app.add_plugins(DefaultPlugins);
+/// ```
+///
+/// Timeouts can be set globally, and overridden for specific hosts:
+///
+/// ```no_run
+/// # use bevy::{prelude::*, utils::HashMap};
+/// # use bevy_web_asset::{Timeouts, WebAssetPlugin};
+/// # use std::time::Duration;
+///
+/// let mut app = App::new();
+/// app.add_plugin(WebAssetPlugin {
+///     timeouts: Timeouts {
+///         total: Some(Duration::from_secs(60)),
+///         ..default()
+///     },
+///     domain_timeouts: HashMap::from_iter([(
+///         "slow.example.com".to_string(),
+///         Timeouts::none(),
+///     )]),
+///     ..default()
+/// });
+/// app.add_plugins(DefaultPlugins);
+/// ```
+pub struct WebAssetPlugin {
+    /// How failed requests are retried
+    pub retry: RetryPolicy,
+    /// Timeouts used for all requests, unless overridden in `domain_timeouts`
+    pub timeouts: Timeouts,
+    /// Timeouts for specific hosts, e.g. `"cdn.example.com"`
+    pub domain_timeouts: HashMap<String, Timeouts>,
+    /// Maximum number of requests in flight at the same time, `None`, the default,
+    /// means unlimited. Any further requests wait until a slot frees up, in the order
+    /// of their [`priorities`](Self::priorities).
+    pub max_concurrent_requests: Option<usize>,
+    /// Whether a `HEAD` request is sent for each asset download waiting for a request
+    /// slot, so [`WebAssetLoadProgress`] knows its size
+    /// before it starts. They don't wait for a slot themselves. Off by default.
+    pub size_requests: bool,
+    /// Responses larger than this many bytes, after decompression, fail with
+    /// [`WebAssetError::TooLarge`](crate::WebAssetError::TooLarge) instead of being
+    /// buffered. `None`, the default, means unlimited.
+    pub max_download_size: Option<u64>,
+    /// Maximum bytes per second downloaded by all requests together, so downloads
+    /// don't starve other network traffic. `None`, the default, means unlimited.
+    pub max_bandwidth: Option<u64>,
+    /// Maximum bytes per second downloaded from specific hosts, e.g. `"cdn.example.com"`,
+    /// in addition to `max_bandwidth`
+    pub domain_max_bandwidth: HashMap<String, u64>,
+    /// Fail with [`WebAssetError::ContentType`](crate::WebAssetError::ContentType) when
+    /// the `Content-Type` of a response doesn't fit the extension of its url, e.g. an
+    /// html error page sent with status `200` instead of a `.png`. Off by default.
+    pub validate_content_type: bool,
+    /// Which [`AssetIoError`](bevy::asset::AssetIoError)s responses with an error
+    /// status are reported as, e.g. `410 Gone` apart from `404 Not Found`
+    pub status_errors: crate::StatusErrors,
+    /// Check every download against the sha256 published next to it, e.g.
+    /// `https://cdn/level.glb.sha256` for `https://cdn/level.glb`, failing with
+    /// [`WebAssetError::Integrity`](crate::WebAssetError::Integrity) on a mismatch.
+    /// Assets without a checksum fail to load. Off by default.
+    pub checksum_sidecars: bool,
+    /// The expected sha256 of remote assets, e.g. to detect tampering on mirrors
+    pub integrity: crate::IntegrityManifest,
+    /// The only hosts assets may be downloaded from, e.g. when loading urls found
+    /// in user generated content. `None`, the default, allows every host.
+    pub allowed_hosts: Option<Vec<crate::HostPattern>>,
+    /// Hosts assets are never downloaded from, even if allowed by `allowed_hosts`
+    pub blocked_hosts: Vec<crate::HostPattern>,
+    /// A local asset folder checked before the network, e.g. `"web_overrides"` loads
+    /// `https://cdn.example.com/textures/hero.png` from
+    /// `assets/web_overrides/cdn.example.com/textures/hero.png` if it exists, for mods,
+    /// or working offline against a copy of the CDN
+    pub local_overrides: Option<PathBuf>,
+    /// Writes every remote asset that loads to this folder, laid out like
+    /// `local_overrides`, e.g. `"assets/web_overrides"`, so a later build can ship
+    /// with the exact assets that were downloaded. Native only.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub record_to: Option<PathBuf>,
+    /// Local assets loaded when remote ones fail to download
+    pub fallbacks: crate::FallbackAssets,
+    /// Alternative base urls by base url, tried in order when a download fails, e.g.
+    /// `"https://cdn.example.com/"` to `["https://mirror.example.org/"]` retries
+    /// `https://cdn.example.com/hero.png` from `https://mirror.example.org/hero.png`
+    pub mirrors: HashMap<String, Vec<String>>,
+    /// Keeps downloaded assets between runs. `None`, the default, never reads or
+    /// writes any cache storage, e.g. for browsers partitioning it in iframes.
+    pub cache: Option<crate::CacheConfig>,
+    /// Bytes of recently loaded remote assets kept in memory, so loading them again
+    /// needs neither the cache nor the network. They're used without asking the server,
+    /// until evicted or found to have changed by [`watch_interval`](Self::watch_interval)
+    /// and the like. `None`, the default, keeps nothing.
+    pub memory_cache_size: Option<u64>,
+    /// Remote assets downloaded into the [`cache`](Self::cache) when the app starts,
+    /// e.g. the first level's, after which [`WebAssetsPreloaded`] is sent
+    pub preload: Vec<String>,
+    /// Url of a JSON array of further assets to preload, relative ones resolved
+    /// against it, e.g. `["hero.png", "music/theme.ogg"]`
+    pub preload_list_url: Option<String>,
+    /// Whether remote assets are only loaded from the cache, inserted as a resource
+    pub offline: WebAssetOffline,
+    /// Pauses and resumes every download, inserted as a resource
+    pub paused: crate::WebAssetPaused,
+    /// Which waiting requests get a free request slot first, inserted as a resource
+    pub priorities: WebAssetPriorities,
+    /// Extra headers sent with requests, inserted as a resource
+    pub headers: WebAssetHeaders,
+    /// Sent as the `User-Agent` of every request that doesn't have one in `headers`,
+    /// e.g. [`WebAssetPlugin::game_user_agent`]. Defaults to `bevy_web_asset/<version>`
+    /// on native, and to `None` in browsers, which send their own and make
+    /// cross-origin requests with a custom one wait for a CORS preflight.
+    pub user_agent: Option<String>,
+    /// Sends the actual requests, defaults to [`SurfClient`](crate::SurfClient)
+    /// on native and [`FetchClient`](crate::FetchClient) on wasm
+    pub http_client: Option<Arc<dyn HttpClient>>,
+    /// Simulated latency, bandwidth limits and failures added to every request, for
+    /// testing loading screens and error handling. `None`, the default, leaves
+    /// requests alone.
+    pub faults: Option<crate::FaultInjection>,
+    /// Records every response to a folder, or replays them from it without going
+    /// online, see [`ResponseRecording`](crate::ResponseRecording). Native only.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub response_recording: Option<crate::ResponseRecording>,
+    /// Options of the default wasm client's `fetch` requests, e.g. to send session cookies
+    #[cfg(target_arch = "wasm32")]
+    pub fetch: crate::FetchOptions,
+    /// Proxies used by the default native client, read from the environment by default
+    #[cfg(not(target_arch = "wasm32"))]
+    pub proxy: crate::ProxyConfig,
+    /// How the default native client resolves host names, e.g. to point a host at a
+    /// local test server
+    #[cfg(not(target_arch = "wasm32"))]
+    pub dns: crate::DnsConfig,
+    /// How the default native client keeps connections open between requests
+    #[cfg(not(target_arch = "wasm32"))]
+    pub connection_pool: crate::PoolConfig,
+    /// Keeps cookies asset hosts set, e.g. sessions, and sends them back with later
+    /// requests, inserted as a resource. `None`, the default, ignores cookies. Browsers
+    /// keep cookies themselves, see `fetch` on wasm.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub cookies: Option<crate::WebAssetCookies>,
+    /// Where `s3://bucket/key` paths are loaded from, and how they're signed
+    pub s3: crate::S3Config,
+    /// Where `gs://bucket/object` paths are loaded from, and how they're authorized
+    pub gcs: crate::GcsConfig,
+    /// Where `azure://container/blob` paths are loaded from, and how they're authorized
+    pub azure: crate::AzureConfig,
+    /// The gateways `ipfs://<cid>/path` paths are loaded through
+    pub ipfs: crate::IpfsConfig,
+    /// Where `cas://<sha256>` paths are loaded from
+    pub cas: crate::CasConfig,
+    /// Handlers for custom schemes, by scheme without the `://`, e.g. `"mod"`
+    pub scheme_handlers: HashMap<String, Arc<dyn crate::SchemeHandler>>,
+    /// Schemes that expand to a base url, e.g. `"cdn"` to `"https://assets.mygame.com/v3"`
+    /// makes `cdn://textures/hero.png` load `https://assets.mygame.com/v3/textures/hero.png`
+    pub aliases: HashMap<String, String>,
+    /// The url of a manifest mapping logical asset paths, like `textures/hero.png`, to
+    /// urls of the versions they're pinned to. It's downloaded before the first asset
+    /// loads and kept for the session, so assets changing on the server never mix
+    /// versions mid-session.
+    pub pinned_manifest: Option<String>,
+    /// Rewrite remote urls before they're requested, applied in order
+    pub url_rewriters: Vec<Arc<dyn crate::UrlRewriter>>,
+    /// A query parameter added to every http url, after `url_rewriters`, e.g.
+    /// `("v".into(), env!("BUILD_HASH").into())` so every build of the game misses the
+    /// copies CDNs cached for older ones
+    pub cache_bust: Option<(String, String)>,
+    /// Change requests right before they're sent, called in order
+    pub request_interceptors: Vec<Arc<dyn crate::RequestInterceptor>>,
+    /// Sign requests right before they're sent, called in order after the interceptors
+    pub request_signers: Vec<Arc<dyn crate::RequestSigner>>,
+    /// Renews signed urls once they're rejected with `403 Forbidden`, e.g. expired
+    /// presigned urls
+    pub url_renewer: Option<Arc<dyn crate::UrlRenewer>>,
+    /// Change downloaded bodies before they reach the asset loaders, applied in order
+    pub response_transforms: Vec<Arc<dyn crate::ResponseTransform>>,
+    /// How often loaded remote assets are checked for changes with conditional requests,
+    /// reloading them when they changed. `None`, the default, disables hot reloading
+    /// of remote assets.
+    pub watch_interval: Option<Duration>,
+    /// An endpoint sending Server-Sent Events whenever assets change, instead of or
+    /// in addition to polling. Each line of the event data is the url or asset
+    /// path of a changed asset, which is reloaded.
+    pub change_events_url: Option<String>,
+    /// A WebSocket endpoint, e.g. `ws://localhost:8080/changes`, sending a text message
+    /// whenever assets change. Each line of a message is the url or asset path of a
+    /// changed asset, which is reloaded.
+    pub change_socket_url: Option<String>,
+    /// The manifest listing the entries of remote directories, so
+    /// `asset_server.load_folder("https://cdn/levels/")` reads `https://cdn/levels/index.json`.
+    /// Defaults to `index.json`, `None` disables listing remote directories.
+    pub directory_manifest: Option<String>,
+    /// Appended to the path of remote assets to find their request options, e.g.
+    /// `Some(".http.json".into())` reads the method, headers, timeout and cache policy
+    /// of `https://cdn/hero.png` from `https://cdn/hero.png.http.json`, merged over the
+    /// global configuration. `None`, the default, since it's a request per asset.
+    pub request_sidecar: Option<String>,
+    /// How assets of urls matching patterns are requested, e.g. with a `POST` to a
+    /// GraphQL endpoint, where `*` matches anything. The first matching pattern applies,
+    /// other urls are loaded with a plain `GET`.
+    pub asset_requests: Vec<(String, crate::AssetRequest)>,
+    /// Extensions of asset loaders by content type, e.g. `"image/png"` to `"png"`, for
+    /// loading urls without an extension with
+    /// [`WebAssets::load_by_content_type`](crate::WebAssets::load_by_content_type).
+    /// Defaults to the content types of common assets.
+    pub content_type_extensions: HashMap<String, String>,
+    /// TLS settings of the default native client, e.g. extra root certificates. Invalid
+    /// ones are logged as errors, and the default settings are used instead.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub tls: crate::TlsConfig,
+}
+
+impl Default for WebAssetPlugin {
+    fn default() -> Self {
+        Self {
+            retry: default(),
+            timeouts: default(),
+            domain_timeouts: default(),
+            max_concurrent_requests: None,
+            size_requests: false,
+            max_download_size: None,
+            max_bandwidth: None,
+            domain_max_bandwidth: default(),
+            validate_content_type: false,
+            status_errors: default(),
+            checksum_sidecars: false,
+            integrity: default(),
+            allowed_hosts: None,
+            blocked_hosts: default(),
+            local_overrides: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            record_to: None,
+            fallbacks: default(),
+            mirrors: default(),
+            cache: None,
+            memory_cache_size: None,
+            preload: Vec::new(),
+            preload_list_url: None,
+            offline: default(),
+            paused: default(),
+            priorities: default(),
+            headers: default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            user_agent: Some(format!("bevy_web_asset/{}", env!("CARGO_PKG_VERSION"))),
+            #[cfg(target_arch = "wasm32")]
+            user_agent: None,
+            http_client: None,
+            faults: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            response_recording: None,
+            #[cfg(target_arch = "wasm32")]
+            fetch: default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            proxy: crate::ProxyConfig::from_env(),
+            #[cfg(not(target_arch = "wasm32"))]
+            dns: default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            connection_pool: default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            cookies: None,
+            s3: default(),
+            gcs: default(),
+            azure: default(),
+            ipfs: default(),
+            cas: default(),
+            scheme_handlers: default(),
+            aliases: default(),
+            pinned_manifest: None,
+            url_rewriters: default(),
+            cache_bust: None,
+            request_interceptors: default(),
+            request_signers: default(),
+            url_renewer: None,
+            response_transforms: default(),
+            watch_interval: None,
+            change_events_url: None,
+            change_socket_url: None,
+            directory_manifest: Some("index.json".to_string()),
+            request_sidecar: None,
+            asset_requests: default(),
+            content_type_extensions: crate::content_type::default_extensions(),
+            #[cfg(not(target_arch = "wasm32"))]
+            tls: default(),
+        }
+    }
+}
+
+impl WebAssetPlugin {
+    /// A `User-Agent` naming the game and its version along with this crate, e.g.
+    /// `MyGame/1.2 bevy_web_asset/0.6.0`
+    pub fn game_user_agent(name: &str, version: &str) -> String {
+        format!(
+            "{name}/{version} bevy_web_asset/{}",
+            env!("CARGO_PKG_VERSION")
+        )
+    }
+
+    /// Configures the plugin with chained calls, as an alternative to setting its fields
+    pub fn builder() -> WebAssetPluginBuilder {
+        WebAssetPluginBuilder(Self::default())
+    }
+
+    /// Whether any cached assets are revalidated in the background
+    fn stale_while_revalidate(&self) -> bool {
+        self.cache.as_ref().is_some_and(|cache| {
+            cache.strategy == CacheStrategy::StaleWhileRevalidate
+                || cache
+                    .strategies
+                    .iter()
+                    .any(|(_, strategy)| *strategy == CacheStrategy::StaleWhileRevalidate)
+        })
+    }
+
+    /// The client requests are sent with, recording or replaying responses and
+    /// disturbed by `faults` if set
+    fn client(&self) -> Arc<dyn HttpClient> {
+        let client = self
+            .http_client
+            .clone()
+            .unwrap_or_else(|| self.default_http_client());
+        #[cfg(not(target_arch = "wasm32"))]
+        let client: Arc<dyn HttpClient> = match &self.response_recording {
+            Some(recording) => Arc::new(crate::replay::RecordingClient::new(
+                client,
+                recording.clone(),
+            )),
+            None => client,
+        };
+        match &self.faults {
+            Some(faults) => Arc::new(FaultyClient::new(client, faults.clone())),
+            None => client,
+        }
+    }
+
+    /// The client used if `http_client` isn't set
+    fn default_http_client(&self) -> Arc<dyn HttpClient> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let client = crate::SurfClient::default()
+                .with_proxy(self.proxy.clone())
+                .with_dns(self.dns.clone());
+            let client = match self.connection_pool == crate::PoolConfig::default() {
+                true => client,
+                false => client.with_pool(&self.connection_pool),
+            };
+            if self.tls.is_default() {
+                return Arc::new(client);
+            }
+            match client.clone().with_tls(&self.tls) {
+                Ok(client) => Arc::new(client),
+                Err(err) => {
+                    error!("Invalid TLS configuration, using the default one: {err}");
+                    Arc::new(client)
+                }
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        return Arc::new(crate::FetchClient::default().with_options(self.fetch));
+    }
+
+    /// Connects to `wss://` change notification sockets
+    #[cfg(not(target_arch = "wasm32"))]
+    fn tls_connector(&self) -> Arc<async_tls::TlsConnector> {
+        match self.tls.to_rustls() {
+            Ok(config) => Arc::new(config.into()),
+            Err(err) => {
+                error!("Invalid TLS configuration of change sockets, using the default one: {err}");
+                default()
+            }
+        }
+    }
+}
+
+/// Builds a [`WebAssetPlugin`], see [`WebAssetPlugin::builder`]
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_web_asset::{Timeouts, WebAssetPlugin};
+/// # use std::time::Duration;
+/// let plugin = WebAssetPlugin::builder()
+///     .cache_name("my_game_assets")
+///     .timeouts(Timeouts {
+///         total: Some(Duration::from_secs(60)),
+///         ..default()
+///     })
+///     .max_concurrent_requests(Some(8))
+///     .user_agent("MyGame/1.2")?
+///     .header("X-Api-Key", "secret")?
+///     .build();
+///
+/// let mut app = App::new();
+/// app.add_plugin(plugin);
 /// app.add_plugins(DefaultPlugins);
+/// # Ok::<(), bevy_web_asset::InvalidHeader>(())
 /// ```
-///});
-#[derive(Default)]
-pub struct WebAssetPlugin;
+pub struct WebAssetPluginBuilder(WebAssetPlugin);
+
+impl WebAssetPluginBuilder {
+    /// Sets [`WebAssetPlugin::retry`]
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.0.retry = retry;
+        self
+    }
+
+    /// Sets [`WebAssetPlugin::timeouts`]
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.0.timeouts = timeouts;
+        self
+    }
+
+    /// Sets [`WebAssetPlugin::max_concurrent_requests`]
+    pub fn max_concurrent_requests(mut self, max: Option<usize>) -> Self {
+        self.0.max_concurrent_requests = max;
+        self
+    }
+
+    /// Sets [`WebAssetPlugin::size_requests`]
+    pub fn size_requests(mut self, enabled: bool) -> Self {
+        self.0.size_requests = enabled;
+        self
+    }
+
+    /// Sets [`WebAssetPlugin::cache`]
+    pub fn cache(mut self, cache: crate::CacheConfig) -> Self {
+        self.0.cache = Some(cache);
+        self
+    }
+
+    /// Turns the [cache](WebAssetPlugin::cache) on, with assets kept under `name`
+    pub fn cache_name(mut self, name: impl Into<String>) -> Self {
+        let cache = self.0.cache.get_or_insert_with(default);
+        cache.name = name.into();
+        self
+    }
+
+    /// Sends `name: value` with every request
+    pub fn header(
+        self,
+        name: impl AsRef<str>,
+        value: impl Into<String>,
+    ) -> Result<Self, InvalidHeader> {
+        self.0.headers.insert(name, value)?;
+        Ok(self)
+    }
+
+    /// Sets [`WebAssetPlugin::user_agent`]
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Result<Self, InvalidHeader> {
+        let user_agent = user_agent.into();
+        HeaderMap::new().insert("User-Agent", user_agent.clone())?;
+        self.0.user_agent = Some(user_agent);
+        Ok(self)
+    }
+
+    /// Sets [`WebAssetPlugin::http_client`]
+    pub fn http_client(mut self, client: impl HttpClient + 'static) -> Self {
+        self.0.http_client = Some(Arc::new(client));
+        self
+    }
+
+    /// Sets [`WebAssetPlugin::watch_interval`]
+    pub fn watch_interval(mut self, interval: Duration) -> Self {
+        self.0.watch_interval = Some(interval);
+        self
+    }
+
+    /// The configured plugin
+    pub fn build(self) -> WebAssetPlugin {
+        self.0
+    }
+}
+
+impl From<WebAssetPluginBuilder> for WebAssetPlugin {
+    fn from(builder: WebAssetPluginBuilder) -> Self {
+        builder.build()
+    }
+}
 
 impl Plugin for WebAssetPlugin {
     fn build(&self, app: &mut App) {
+        let (progress_sender, progress_receiver) = crossbeam_channel::unbounded();
+        let (tamper_sender, tamper_receiver) = crossbeam_channel::unbounded();
+        let (failure_sender, failure_receiver) = crossbeam_channel::unbounded();
+        let (stale_sender, stale_receiver) = crossbeam_channel::unbounded();
+        let auth = WebAssetAuth::default();
+        let cache_stats = WebAssetCacheStats::default();
+        let net_stats = WebAssetNetStats::default();
+        let load_progress = WebAssetLoadProgress::default();
+
         let asset_io = WebAssetIo {
             default_io: AssetPlugin::default().create_platform_default_asset_io(),
+            retry: self.retry.clone(),
+            timeouts: self.timeouts.clone(),
+            domain_timeouts: self.domain_timeouts.clone(),
+            progress: progress_sender,
+            in_flight: default(),
+            live_handles: default(),
+            archives: default(),
+            s3: self.s3.resolve(),
+            gcs: self.gcs.resolve(),
+            azure: self.azure.resolve(),
+            ipfs: self.ipfs.clone(),
+            cas: self.cas.clone(),
+            scheme_handlers: self
+                .scheme_handlers
+                .iter()
+                .map(|(scheme, handler)| (scheme.to_ascii_lowercase(), handler.clone()))
+                .collect(),
+            aliases: self
+                .aliases
+                .iter()
+                .map(|(alias, base)| (alias.to_ascii_lowercase(), base.clone()))
+                .collect(),
+            pinned: self.pinned_manifest.clone().map(PinnedManifest::new),
+            url_rewriters: self
+                .url_rewriters
+                .iter()
+                .cloned()
+                .chain(self.cache_bust.clone().map(|(name, value)| {
+                    Arc::new(crate::rewrite::CacheBust { name, value })
+                        as Arc<dyn crate::UrlRewriter>
+                }))
+                .collect(),
+            request_interceptors: self.request_interceptors.clone(),
+            request_signers: self.request_signers.clone(),
+            url_renewer: self.url_renewer.clone(),
+            renewed_urls: default(),
+            response_transforms: self.response_transforms.clone(),
+            // Stale assets are reloaded like changed ones when they're revalidated
+            watcher: (self.watch_interval.is_some()
+                || self.change_events_url.is_some()
+                || self.change_socket_url.is_some()
+                || self.stale_while_revalidate())
+            .then(|| Watcher::new(self.watch_interval)),
+            directory_manifest: self.directory_manifest.clone(),
+            request_sidecar: self.request_sidecar.clone(),
+            asset_requests: self.asset_requests.clone(),
+            content_type_extensions: self
+                .content_type_extensions
+                .iter()
+                .map(|(content_type, extension)| {
+                    (content_type.to_ascii_lowercase(), extension.clone())
+                })
+                .collect(),
+            #[cfg(feature = "webdav")]
+            dav_entries: default(),
+            request_slots: self.max_concurrent_requests.map(RequestSlots::new),
+            priorities: self.priorities.clone(),
+            max_download_size: self.max_download_size,
+            throttles: Throttles {
+                global: self.max_bandwidth.map(Throttle::new),
+                domains: self
+                    .domain_max_bandwidth
+                    .iter()
+                    .map(|(host, &limit)| (host.clone(), Throttle::new(limit)))
+                    .collect(),
+            },
+            validate_content_type: self.validate_content_type,
+            status_errors: self.status_errors.clone(),
+            checksum_sidecars: self.checksum_sidecars,
+            integrity: self.integrity.clone(),
+            tampered: tamper_sender,
+            failed: failure_sender,
+            net_stats: net_stats.clone(),
+            load_progress: load_progress.clone(),
+            size_requests: self.size_requests,
+            local_overrides: self.local_overrides.clone(),
+            #[cfg(not(target_arch = "wasm32"))]
+            record_to: self.record_to.clone(),
+            fallbacks: self.fallbacks.clone(),
+            mirrors: self.mirrors.clone(),
+            cache: self
+                .cache
+                .as_ref()
+                .map(|config| Cache::new(config, cache_stats.clone())),
+            memory_cache: self.memory_cache_size.map(MemoryCache::new),
+            cache_stats: cache_stats.clone(),
+            offline: self.offline.clone(),
+            paused: self.paused.clone(),
+            stale: stale_sender,
+            hosts: HostFilter {
+                allowed: self.allowed_hosts.clone(),
+                blocked: self.blocked_hosts.clone(),
+            },
+            auth: auth.clone(),
+            url_credentials: default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            cookies: self.cookies.clone(),
+            headers: self.headers.clone(),
+            user_agent: self.user_agent.clone().filter(|user_agent| {
+                let mut headers = HeaderMap::new();
+                let valid = headers.insert("User-Agent", user_agent.clone());
+                valid
+                    .map_err(|err| warn!("Not sending User-Agent {user_agent:?}: {err}"))
+                    .is_ok()
+            }),
+            client: self.client(),
         };
 
-        app.insert_resource(AssetServer::new(asset_io));
+        app.insert_resource(AssetServer::new(asset_io))
+            .insert_resource(ProgressReceiver(progress_receiver))
+            .insert_resource(TamperReceiver(tamper_receiver))
+            .insert_resource(FailureReceiver(failure_receiver))
+            .insert_resource(StaleReceiver(stale_receiver))
+            .insert_resource(auth)
+            .insert_resource(self.headers.clone())
+            .insert_resource(self.priorities.clone())
+            .insert_resource(cache_stats)
+            .insert_resource(net_stats)
+            .insert_resource(load_progress)
+            .insert_resource(self.offline.clone())
+            .insert_resource(self.paused.clone())
+            .add_event::<WebAssetProgress>()
+            .add_event::<WebAssetTampered>()
+            .add_event::<WebAssetFailed>()
+            .add_event::<WebAssetsPreloaded>()
+            .add_system(send_progress_events.in_base_set(CoreSet::PreUpdate))
+            .add_system(send_tamper_events.in_base_set(CoreSet::PreUpdate))
+            .add_system(send_failure_events.in_base_set(CoreSet::PreUpdate))
+            .add_system(poll_for_changes.in_base_set(CoreSet::PreUpdate))
+            .add_system(revalidate_stale.in_base_set(CoreSet::PreUpdate));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(cookies) = &self.cookies {
+            app.insert_resource(cookies.clone());
+        }
+        if !self.preload.is_empty() || self.preload_list_url.is_some() {
+            let (sender, receiver) = crossbeam_channel::unbounded();
+            app.insert_resource(PreloadReceiver(receiver))
+                .add_startup_system(preload::preload(
+                    self.preload.clone(),
+                    self.preload_list_url.clone(),
+                    sender,
+                ))
+                .add_system(send_preloaded_events.in_base_set(CoreSet::PreUpdate));
+        }
+        if let Some(url) = &self.change_events_url {
+            app.add_startup_system(sse::subscribe(url.clone()));
+        }
+        if let Some(url) = &self.change_socket_url {
+            app.add_startup_system(websocket::subscribe(websocket::Endpoint {
+                url: url.clone(),
+                #[cfg(not(target_arch = "wasm32"))]
+                tls: self.tls_connector(),
+            }));
+        }
     }
 }
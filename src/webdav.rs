@@ -0,0 +1,174 @@
+use bevy::{
+    asset::{AssetIoError, FileType, Metadata},
+    utils::HashMap,
+};
+use percent_encoding::percent_decode_str;
+use std::{io, path::PathBuf, sync::Mutex};
+use url::Url;
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::{web_asset_io::RequestOptions, HeaderMap, Method, WebAssetError, WebAssetIo};
+
+const DAV: &str = "DAV:";
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<propfind xmlns="DAV:"><prop><resourcetype/></prop></propfind>"#;
+
+/// A resource in a `207 Multi-Status` response
+struct DavEntry {
+    href: String,
+    is_collection: bool,
+}
+
+fn parse_multistatus(xml: &[u8]) -> Result<Vec<DavEntry>, String> {
+    let mut entries = Vec::new();
+    let mut current: Option<DavEntry> = None;
+    let mut in_href = false;
+    for event in EventReader::new(xml) {
+        match event.map_err(|err| format!("invalid PROPFIND response: {err}"))? {
+            XmlEvent::StartElement { name, .. } if name.namespace_ref() == Some(DAV) => {
+                match name.local_name.as_str() {
+                    "response" => {
+                        current = Some(DavEntry {
+                            href: String::new(),
+                            is_collection: false,
+                        })
+                    }
+                    "href" => in_href = true,
+                    "collection" => {
+                        if let Some(entry) = &mut current {
+                            entry.is_collection = true;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            XmlEvent::Characters(text) if in_href => {
+                if let Some(entry) = &mut current {
+                    entry.href.push_str(text.trim());
+                }
+            }
+            XmlEvent::EndElement { name } if name.namespace_ref() == Some(DAV) => {
+                match name.local_name.as_str() {
+                    "href" => in_href = false,
+                    "response" => entries.extend(current.take()),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(entries)
+}
+
+/// Whether listed paths are collections, so listing a folder doesn't need a
+/// request for each entry
+#[derive(Default)]
+pub(crate) struct DavEntries(Mutex<HashMap<String, bool>>);
+
+/// Whether `url` is served over http, where WebDAV is tried
+pub(crate) fn is_http(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+impl WebAssetIo {
+    fn propfind(&self, url: &str, depth: u8) -> Result<Vec<DavEntry>, AssetIoError> {
+        let mut headers = HeaderMap::new();
+        let _ = headers.insert("Depth", depth.to_string());
+        let _ = headers.insert("Content-Type", "application/xml; charset=utf-8");
+        let options = RequestOptions {
+            method: Some(Method::Other("PROPFIND".to_string())),
+            body: Some(PROPFIND_BODY.as_bytes().to_vec()),
+            headers,
+            ..RequestOptions::default()
+        };
+        let body = self.fetch_blocking(url, &options)?;
+        parse_multistatus(&body).map_err(|message| {
+            let err = WebAssetError::Decode {
+                url: url.to_string(),
+                message,
+            };
+            err.into_asset_io_error(std::path::Path::new(url), &self.status_errors)
+        })
+    }
+
+    /// The entries of the WebDAV collection at `url`, listed under `requested`.
+    /// Collections end with a slash.
+    pub(crate) fn read_dav_directory(
+        &self,
+        requested: &str,
+        url: &str,
+    ) -> Result<Vec<PathBuf>, AssetIoError> {
+        let base = Url::parse(url).map_err(|err| {
+            let err = WebAssetError::Transport {
+                url: url.to_string(),
+                message: err.to_string(),
+            };
+            err.into_asset_io_error(std::path::Path::new(url), &self.status_errors)
+        })?;
+        let root = requested.trim_end_matches('/');
+        let entries = self.propfind(url, 1)?;
+        let mut children = Vec::new();
+        let mut known = self.dav_entries.0.lock().unwrap();
+        for entry in entries {
+            let Ok(href) = base.join(&entry.href) else {
+                continue;
+            };
+            // The collection itself is listed too
+            if href.path().trim_end_matches('/') == base.path().trim_end_matches('/') {
+                if !entry.is_collection {
+                    let message = format!("{url} is not a directory");
+                    return Err(AssetIoError::Io(io::Error::other(message)));
+                }
+                continue;
+            }
+            let Some(name) = href.path().trim_end_matches('/').rsplit('/').next() else {
+                continue;
+            };
+            let name = percent_decode_str(name).decode_utf8_lossy();
+            let child = match entry.is_collection {
+                true => format!("{root}/{name}/"),
+                false => format!("{root}/{name}"),
+            };
+            known.insert(child.clone(), entry.is_collection);
+            children.push(PathBuf::from(child));
+        }
+        Ok(children)
+    }
+
+    /// Whether `url`, requested as `requested`, is a WebDAV collection
+    pub(crate) fn is_dav_collection(
+        &self,
+        requested: &str,
+        url: &str,
+    ) -> Result<bool, AssetIoError> {
+        if let Some(&is_collection) = self.dav_entries.0.lock().unwrap().get(requested) {
+            return Ok(is_collection);
+        }
+        let is_collection = self
+            .propfind(url, 0)?
+            .first()
+            .is_some_and(|entry| entry.is_collection);
+        let mut known = self.dav_entries.0.lock().unwrap();
+        known.insert(requested.to_string(), is_collection);
+        Ok(is_collection)
+    }
+
+    pub(crate) fn dav_metadata(
+        &self,
+        requested: &str,
+        url: &str,
+    ) -> Result<Metadata, AssetIoError> {
+        match self.is_dav_collection(requested, url)? {
+            true => Ok(Metadata::new(FileType::Directory)),
+            false => Ok(Metadata::new(FileType::File)),
+        }
+    }
+}
+
+/// Whether the server doesn't speak WebDAV, so listing should fall back to manifests
+pub(crate) fn unsupported(err: &AssetIoError) -> bool {
+    WebAssetError::from_asset_io_error(err)
+        .and_then(WebAssetError::status)
+        .is_some_and(|status| matches!(status, 400 | 403 | 405 | 501))
+}
@@ -0,0 +1,505 @@
+use bevy::{log::warn, prelude::*, tasks::IoTaskPool};
+use std::time::Duration;
+
+use crate::{retry::sleep, WebAssetIo};
+
+#[cfg(not(target_arch = "wasm32"))]
+use native::Socket;
+#[cfg(target_arch = "wasm32")]
+use wasm::Socket;
+
+/// Delay before reconnecting a closed socket
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+
+/// Where change notifications are received from
+pub(crate) struct Endpoint {
+    pub(crate) url: String,
+    /// Used for `wss://` urls
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) tls: std::sync::Arc<async_tls::TlsConnector>,
+}
+
+/// Connects to the WebSocket at `endpoint`, reconnecting whenever the connection drops
+pub(crate) fn subscribe(endpoint: Endpoint) -> impl Fn(Res<AssetServer>) {
+    let endpoint = std::sync::Arc::new(endpoint);
+    move |asset_server: Res<AssetServer>| {
+        let endpoint = endpoint.clone();
+        let asset_server = asset_server.clone();
+        IoTaskPool::get()
+            .spawn(async move {
+                let Some(asset_io) = asset_server.asset_io().downcast_ref::<WebAssetIo>() else {
+                    return;
+                };
+                let url = &endpoint.url;
+                loop {
+                    match Socket::connect(asset_io, &endpoint).await {
+                        Ok(mut socket) => loop {
+                            match socket.next_message().await {
+                                Ok(Some(message)) => {
+                                    asset_io.reload_notified(&asset_server, &message)
+                                }
+                                Ok(None) => {
+                                    info!("Change notifications from {url} ended, reconnecting");
+                                    break;
+                                }
+                                Err(err) => {
+                                    warn!("Change notifications from {url} failed: {err}");
+                                    break;
+                                }
+                            }
+                        },
+                        Err(err) => warn!("Failed to connect to {url}: {err}"),
+                    }
+                    sleep(RECONNECT_DELAY).await;
+                }
+            })
+            .detach();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use async_std::net::TcpStream;
+    use futures_lite::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+    use ring::digest::{digest, SHA1_FOR_LEGACY_USE_ONLY};
+    use url::Url;
+
+    use super::Endpoint;
+    use crate::WebAssetIo;
+
+    /// Appended to the key before hashing, from RFC 6455
+    const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+    /// Larger handshake responses are rejected
+    const MAX_HANDSHAKE_LEN: usize = 16 * 1024;
+    /// Larger messages close the connection, change notifications are small
+    const MAX_MESSAGE_LEN: usize = 1024 * 1024;
+
+    const CONTINUATION: u8 = 0x0;
+    const TEXT: u8 = 0x1;
+    const BINARY: u8 = 0x2;
+    const CLOSE: u8 = 0x8;
+    const PING: u8 = 0x9;
+    const PONG: u8 = 0xa;
+
+    trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+    impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+    /// A WebSocket client connection, receiving text messages
+    pub(super) struct Socket {
+        stream: Box<dyn Stream>,
+    }
+
+    fn error(err: impl ToString) -> String {
+        err.to_string()
+    }
+
+    impl Socket {
+        pub(super) async fn connect(
+            asset_io: &WebAssetIo,
+            endpoint: &Endpoint,
+        ) -> Result<Self, String> {
+            let url = Url::parse(&endpoint.url).map_err(error)?;
+            let secure = match url.scheme() {
+                "ws" => false,
+                "wss" => true,
+                scheme => return Err(format!("unsupported scheme {scheme}, expected ws or wss")),
+            };
+            let host = url.host_str().ok_or("url has no host")?;
+            let port = url.port_or_known_default().ok_or("url has no port")?;
+            let tcp = TcpStream::connect((host, port)).await.map_err(error)?;
+            let stream: Box<dyn Stream> = match secure {
+                true => Box::new(endpoint.tls.connect(host, tcp).await.map_err(error)?),
+                false => Box::new(tcp),
+            };
+            let mut socket = Self { stream };
+            socket.handshake(asset_io, &url).await?;
+            Ok(socket)
+        }
+
+        async fn handshake(&mut self, asset_io: &WebAssetIo, url: &Url) -> Result<(), String> {
+            let key = base64::encode(fastrand::u128(..).to_ne_bytes());
+            let mut headers = asset_io.headers.for_url(url);
+            if let Some(auth) = asset_io.auth.get() {
+                if let Some(authorization) = auth.authorization(url.as_str()).await {
+                    let _ = headers.insert("Authorization", authorization);
+                }
+            }
+            let host = match url.port() {
+                Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+                None => url.host_str().unwrap_or_default().to_string(),
+            };
+            let target = match url.query() {
+                Some(query) => format!("{}?{query}", url.path()),
+                None => url.path().to_string(),
+            };
+            let mut request = format!(
+                "GET {target} HTTP/1.1\r\nHost: {host}\r\nUpgrade: websocket\r\n\
+                 Connection: Upgrade\r\nSec-WebSocket-Key: {key}\r\nSec-WebSocket-Version: 13\r\n"
+            );
+            for (name, value) in headers.iter() {
+                request += &format!("{name}: {value}\r\n");
+            }
+            request += "\r\n";
+            self.stream
+                .write_all(request.as_bytes())
+                .await
+                .map_err(error)?;
+
+            // Read byte by byte, so no frame data is consumed along with the headers
+            let mut response = Vec::new();
+            while !response.ends_with(b"\r\n\r\n") {
+                if response.len() > MAX_HANDSHAKE_LEN {
+                    return Err("handshake response too large".to_string());
+                }
+                let mut byte = [0];
+                self.stream.read_exact(&mut byte).await.map_err(error)?;
+                response.push(byte[0]);
+            }
+            let response = String::from_utf8_lossy(&response);
+            let mut lines = response.lines();
+            let status = lines.next().unwrap_or_default();
+            if status.split_whitespace().nth(1) != Some("101") {
+                return Err(format!("server refused the upgrade: {status}"));
+            }
+            let accept = lines.find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.trim()
+                    .eq_ignore_ascii_case("sec-websocket-accept")
+                    .then(|| value.trim())
+            });
+            let expected = base64::encode(digest(
+                &SHA1_FOR_LEGACY_USE_ONLY,
+                format!("{key}{ACCEPT_GUID}").as_bytes(),
+            ));
+            if accept != Some(expected.as_str()) {
+                return Err("invalid Sec-WebSocket-Accept".to_string());
+            }
+            Ok(())
+        }
+
+        /// The next text message, or `None` once the server closed the connection
+        pub(super) async fn next_message(&mut self) -> Result<Option<String>, String> {
+            let mut message = Vec::new();
+            let mut opcode = None;
+            loop {
+                let (fin, frame_opcode, payload) = self.read_frame().await?;
+                match frame_opcode {
+                    TEXT | BINARY => {
+                        opcode = Some(frame_opcode);
+                        message = payload;
+                    }
+                    CONTINUATION => {
+                        message.extend(payload);
+                        if message.len() > MAX_MESSAGE_LEN {
+                            return Err("message too large".to_string());
+                        }
+                    }
+                    CLOSE => {
+                        let _ = self
+                            .write_frame(CLOSE, &payload[..payload.len().min(2)])
+                            .await;
+                        return Ok(None);
+                    }
+                    PING => {
+                        self.write_frame(PONG, &payload).await?;
+                        continue;
+                    }
+                    PONG => continue,
+                    other => return Err(format!("unknown opcode {other}")),
+                }
+                if !fin {
+                    continue;
+                }
+                match opcode.take() {
+                    Some(TEXT) => return String::from_utf8(message).map(Some).map_err(error),
+                    // Not a change notification
+                    _ => message.clear(),
+                }
+            }
+        }
+
+        async fn read_frame(&mut self) -> Result<(bool, u8, Vec<u8>), String> {
+            let mut head = [0; 2];
+            self.stream.read_exact(&mut head).await.map_err(error)?;
+            let fin = head[0] & 0x80 != 0;
+            let opcode = head[0] & 0x0f;
+            let masked = head[1] & 0x80 != 0;
+            let len = match head[1] & 0x7f {
+                126 => {
+                    let mut len = [0; 2];
+                    self.stream.read_exact(&mut len).await.map_err(error)?;
+                    u16::from_be_bytes(len) as u64
+                }
+                127 => {
+                    let mut len = [0; 8];
+                    self.stream.read_exact(&mut len).await.map_err(error)?;
+                    u64::from_be_bytes(len)
+                }
+                len => len as u64,
+            };
+            if len > MAX_MESSAGE_LEN as u64 {
+                return Err("message too large".to_string());
+            }
+            let mut mask = [0; 4];
+            if masked {
+                self.stream.read_exact(&mut mask).await.map_err(error)?;
+            }
+            let mut payload = vec![0; len as usize];
+            self.stream.read_exact(&mut payload).await.map_err(error)?;
+            if masked {
+                for (i, byte) in payload.iter_mut().enumerate() {
+                    *byte ^= mask[i % 4];
+                }
+            }
+            Ok((fin, opcode, payload))
+        }
+
+        /// Sends a single frame, masked as clients have to
+        async fn write_frame(&mut self, opcode: u8, payload: &[u8]) -> Result<(), String> {
+            let mut frame = vec![0x80 | opcode];
+            match payload.len() {
+                len @ 0..=125 => frame.push(0x80 | len as u8),
+                len @ 126..=0xffff => {
+                    frame.push(0x80 | 126);
+                    frame.extend((len as u16).to_be_bytes());
+                }
+                len => {
+                    frame.push(0x80 | 127);
+                    frame.extend((len as u64).to_be_bytes());
+                }
+            }
+            let mask = fastrand::u32(..).to_ne_bytes();
+            frame.extend(mask);
+            frame.extend(
+                payload
+                    .iter()
+                    .enumerate()
+                    .map(|(i, byte)| byte ^ mask[i % 4]),
+            );
+            self.stream.write_all(&frame).await.map_err(error)?;
+            self.stream.flush().await.map_err(error)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use async_std::{
+            net::{TcpListener, TcpStream},
+            task,
+        };
+        use bevy::prelude::*;
+        use futures_lite::{future, AsyncReadExt, AsyncWriteExt};
+        use ring::digest::{digest, SHA1_FOR_LEGACY_USE_ONLY};
+        use std::sync::Arc;
+
+        use super::{Socket, ACCEPT_GUID, CLOSE, PONG};
+        use crate::{websocket::Endpoint, WebAssetIo, WebAssetPlugin};
+
+        /// Accepts a connection, answering the handshake with `accept` computed from
+        /// the key, or a wrong one
+        async fn accept(listener: &TcpListener, valid: bool) -> TcpStream {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                let mut byte = [0];
+                stream.read_exact(&mut byte).await.unwrap();
+                request.push(byte[0]);
+            }
+            let request = String::from_utf8(request).unwrap();
+            assert!(request.starts_with("GET /changes?v=1 HTTP/1.1\r\n"));
+            assert!(request.contains("Upgrade: websocket\r\n"));
+            let key = request
+                .lines()
+                .find_map(|line| line.strip_prefix("Sec-WebSocket-Key: "))
+                .unwrap();
+            let key = match valid {
+                true => key.to_string(),
+                false => format!("{key}x"),
+            };
+            let accept = base64::encode(digest(
+                &SHA1_FOR_LEGACY_USE_ONLY,
+                format!("{key}{ACCEPT_GUID}").as_bytes(),
+            ));
+            let response = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+                 Connection: Upgrade\r\nSec-WebSocket-Accept: {accept}\r\n\r\n"
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream
+        }
+
+        /// Reads a frame sent by the client, checking that it's masked
+        async fn read_client_frame(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+            let mut head = [0; 6];
+            stream.read_exact(&mut head).await.unwrap();
+            assert_eq!(head[0] & 0x80, 0x80, "client frames are final");
+            assert_eq!(head[1] & 0x80, 0x80, "client frames are masked");
+            let mut payload = vec![0; (head[1] & 0x7f) as usize];
+            stream.read_exact(&mut payload).await.unwrap();
+            for (i, byte) in payload.iter_mut().enumerate() {
+                *byte ^= head[2 + i % 4];
+            }
+            (head[0] & 0x0f, payload)
+        }
+
+        fn connect(app: &App, port: u16) -> Result<Socket, String> {
+            let asset_server = app.world.resource::<AssetServer>();
+            let asset_io = asset_server
+                .asset_io()
+                .downcast_ref::<WebAssetIo>()
+                .unwrap();
+            let endpoint = Endpoint {
+                url: format!("ws://127.0.0.1:{port}/changes?v=1"),
+                tls: Arc::default(),
+            };
+            future::block_on(Socket::connect(asset_io, &endpoint))
+        }
+
+        fn app() -> App {
+            let mut app = App::new();
+            app.add_plugins(MinimalPlugins)
+                .add_plugin(WebAssetPlugin::default());
+            app
+        }
+
+        #[test]
+        fn receives_text_messages() {
+            let listener = future::block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let server = task::spawn(async move {
+                let mut stream = accept(&listener, true).await;
+                let mut frames = Vec::new();
+                // A text message in two fragments, with a ping in between
+                frames.extend([0x01, 4]);
+                frames.extend(b"hero");
+                frames.extend([0x89, 2]);
+                frames.extend(b"hi");
+                frames.extend([0x80, 4]);
+                frames.extend(b".png");
+                // Binary messages are skipped
+                frames.extend([0x82, 1, 0xff]);
+                // A 16 bit length
+                frames.extend([0x81, 126, 0, 200]);
+                frames.extend([b'x'; 200]);
+                // Masked, which servers don't do but is still valid framing
+                let mask = [1, 2, 3, 4];
+                frames.extend([0x81, 0x80 | 6]);
+                frames.extend(mask);
+                frames.extend(b"masked".iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+                // Close with status 1000 and a reason
+                frames.extend([0x88, 4, 0x03, 0xe8, b'o', b'k']);
+                stream.write_all(&frames).await.unwrap();
+
+                let pong = read_client_frame(&mut stream).await;
+                let close = read_client_frame(&mut stream).await;
+                (pong, close)
+            });
+
+            let app = app();
+            let mut socket = connect(&app, port).unwrap();
+            let mut next = || future::block_on(socket.next_message()).unwrap();
+            assert_eq!(next().as_deref(), Some("hero.png"));
+            assert_eq!(next(), Some("x".repeat(200)));
+            assert_eq!(next().as_deref(), Some("masked"));
+            assert_eq!(next(), None);
+
+            let (pong, close) = future::block_on(server);
+            assert_eq!(pong, (PONG, b"hi".to_vec()));
+            assert_eq!(close, (CLOSE, vec![0x03, 0xe8]));
+        }
+
+        #[test]
+        fn rejects_invalid_accept() {
+            let listener = future::block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let server = task::spawn(async move { accept(&listener, false).await });
+
+            let app = app();
+            let err = connect(&app, port).err().unwrap();
+            assert_eq!(err, "invalid Sec-WebSocket-Accept");
+            drop(future::block_on(server));
+        }
+
+        #[test]
+        fn rejects_oversized_frames() {
+            let listener = future::block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let server = task::spawn(async move {
+                let mut stream = accept(&listener, true).await;
+                let mut frame = vec![0x81, 127];
+                frame.extend(u64::MAX.to_be_bytes());
+                stream.write_all(&frame).await.unwrap();
+                stream
+            });
+
+            let app = app();
+            let mut socket = connect(&app, port).unwrap();
+            let err = future::block_on(socket.next_message()).unwrap_err();
+            assert_eq!(err, "message too large");
+            drop(future::block_on(server));
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use async_channel::Receiver;
+    use wasm_bindgen::{prelude::*, JsCast};
+    use web_sys::{MessageEvent, WebSocket};
+
+    use super::Endpoint;
+    use crate::WebAssetIo;
+
+    /// A browser WebSocket, receiving text messages
+    pub(super) struct Socket {
+        socket: WebSocket,
+        messages: Receiver<String>,
+        _on_message: Closure<dyn FnMut(MessageEvent)>,
+        _on_close: Closure<dyn FnMut(JsValue)>,
+    }
+
+    impl Socket {
+        /// Browsers don't allow setting headers on WebSockets, so `asset_io` is unused
+        pub(super) async fn connect(
+            _asset_io: &WebAssetIo,
+            endpoint: &Endpoint,
+        ) -> Result<Self, String> {
+            let socket = WebSocket::new(&endpoint.url).map_err(|err| format!("{err:?}"))?;
+            let (sender, messages) = async_channel::unbounded();
+
+            let message_sender = sender.clone();
+            let on_message = Closure::wrap(Box::new(move |event: MessageEvent| {
+                if let Some(text) = event.data().as_string() {
+                    let _ = message_sender.try_send(text);
+                }
+            }) as Box<dyn FnMut(MessageEvent)>);
+            socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+            // Errors are followed by a close event
+            let on_close = Closure::wrap(Box::new(move |_: JsValue| {
+                sender.close();
+            }) as Box<dyn FnMut(JsValue)>);
+            socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+            Ok(Self {
+                socket,
+                messages,
+                _on_message: on_message,
+                _on_close: on_close,
+            })
+        }
+
+        /// The next text message, or `None` once the connection closed
+        pub(super) async fn next_message(&mut self) -> Result<Option<String>, String> {
+            Ok(self.messages.recv().await.ok())
+        }
+    }
+
+    impl Drop for Socket {
+        fn drop(&mut self) {
+            self.socket.set_onmessage(None);
+            self.socket.set_onclose(None);
+            let _ = self.socket.close();
+        }
+    }
+}